@@ -0,0 +1,42 @@
+// build.rs
+// Exposes the git commit as GIT_SHA for the health endpoints. CI can pass
+// GIT_SHA explicitly; otherwise ask git, and fall back to "unknown".
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    watch_git_head();
+
+    let sha = std::env::var("GIT_SHA")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short=12", "HEAD"])
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .and_then(|o| String::from_utf8(o.stdout).ok())
+                .map(|s| s.trim().to_owned())
+        })
+        .unwrap_or_else(|| "unknown".to_owned());
+
+    println!("cargo:rustc-env=GIT_SHA={sha}");
+}
+
+/// HEAD itself only changes on a branch switch; a commit moves the branch ref
+/// it points to, which lives either as a loose file or in packed-refs. Cargo
+/// treats a missing watched file as always stale, so only existing ones are
+/// watched.
+fn watch_git_head() {
+    let mut watched = vec![".git/HEAD".to_owned(), ".git/packed-refs".to_owned()];
+    if let Ok(head) = std::fs::read_to_string(".git/HEAD")
+        && let Some(reference) = head.trim().strip_prefix("ref: ")
+    {
+        watched.push(format!(".git/{reference}"));
+    }
+    for path in watched.iter().filter(|p| Path::new(p).exists()) {
+        println!("cargo:rerun-if-changed={path}");
+    }
+}
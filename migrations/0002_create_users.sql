@@ -0,0 +1,14 @@
+-- migrations/0002_create_users.sql
+CREATE TABLE IF NOT EXISTS users (
+  id BIGSERIAL PRIMARY KEY,
+  username TEXT NOT NULL,
+  password_hash TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Usernames are unique regardless of case
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_lower_key ON users (lower(username));
+
+-- Rows created before accounts existed keep a NULL owner and are only visible to the admin token
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS owner_id BIGINT REFERENCES users (id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS todos_owner_id_id_idx ON todos (owner_id, id);
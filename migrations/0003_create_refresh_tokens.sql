@@ -0,0 +1,16 @@
+-- migrations/0003_create_refresh_tokens.sql
+-- Opaque refresh tokens, stored as SHA-256 digests. Each login starts a family;
+-- every refresh marks the presented token used and inserts its successor.
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+  id BIGSERIAL PRIMARY KEY,
+  user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+  family_id UUID NOT NULL DEFAULT gen_random_uuid(),
+  parent_id BIGINT REFERENCES refresh_tokens (id) ON DELETE SET NULL,
+  token_hash BYTEA NOT NULL UNIQUE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  used_at TIMESTAMPTZ,
+  revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS refresh_tokens_family_id_idx ON refresh_tokens (family_id);
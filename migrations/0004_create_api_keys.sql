@@ -0,0 +1,16 @@
+-- migrations/0004_create_api_keys.sql
+-- Keys are `tk_<prefix>_<secret>`; only the prefix (for lookup and display)
+-- and a SHA-256 digest of the full key are stored.
+CREATE TABLE IF NOT EXISTS api_keys (
+  id BIGSERIAL PRIMARY KEY,
+  name TEXT NOT NULL,
+  prefix TEXT NOT NULL UNIQUE,
+  key_hash BYTEA NOT NULL,
+  scopes TEXT[] NOT NULL,
+  -- Acts on this user's todos; NULL means every user's
+  user_id BIGINT REFERENCES users (id) ON DELETE CASCADE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ,
+  last_used_at TIMESTAMPTZ,
+  revoked_at TIMESTAMPTZ
+);
@@ -0,0 +1,5 @@
+-- migrations/0005_add_user_roles.sql
+-- RBAC role; what each role may do is configured by RBAC_POLICY, not stored.
+ALTER TABLE users
+  ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'member'
+  CHECK (role IN ('admin', 'member', 'read_only'));
@@ -0,0 +1,27 @@
+-- migrations/0006_create_oidc.sql
+-- Accounts created through OIDC have no local password.
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
+
+-- External identities, keyed by the IdP's (issuer, subject) pair.
+CREATE TABLE IF NOT EXISTS user_identities (
+  id BIGSERIAL PRIMARY KEY,
+  user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+  issuer TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  email TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_login_at TIMESTAMPTZ,
+  UNIQUE (issuer, subject)
+);
+
+CREATE INDEX IF NOT EXISTS user_identities_user_id_idx ON user_identities (user_id);
+
+-- Logins in flight between /auth/oidc/login and the callback. Only a digest
+-- of `state` is stored; rows are consumed by the callback or swept on expiry.
+CREATE TABLE IF NOT EXISTS oidc_logins (
+  state_hash BYTEA PRIMARY KEY,
+  nonce TEXT NOT NULL,
+  code_verifier TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,16 @@
+-- migrations/0007_create_sessions.sql
+-- Browser sessions. The cookie carries a random token; only its SHA-256
+-- digest is stored. csrf_token is the synchronizer token for unsafe methods.
+CREATE TABLE IF NOT EXISTS sessions (
+  id BIGSERIAL PRIMARY KEY,
+  user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+  token_hash BYTEA NOT NULL UNIQUE,
+  csrf_token TEXT NOT NULL,
+  user_agent TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS sessions_user_id_idx ON sessions (user_id);
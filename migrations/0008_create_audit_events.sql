@@ -0,0 +1,24 @@
+-- migrations/0008_create_audit_events.sql
+-- Append-only record of mutating and admin requests. Each row's hash covers
+-- its own fields and the previous row's hash, so editing, deleting or
+-- reordering rows breaks the chain (see `audit::AuditLog::verify`).
+CREATE TABLE IF NOT EXISTS audit_events (
+  id BIGSERIAL PRIMARY KEY,
+  occurred_at TIMESTAMPTZ NOT NULL,
+  -- "admin", "user:<id>" or "api_key:<id>"; NULL for anonymous callers
+  principal TEXT,
+  method TEXT NOT NULL,
+  -- Matched route template, e.g. /todos/{id}; NULL when nothing matched
+  route TEXT,
+  path TEXT NOT NULL,
+  status INTEGER NOT NULL,
+  source_ip TEXT,
+  request_id TEXT,
+  -- SHA-256 of the body with secrets redacted; NULL for an empty body
+  body_digest BYTEA,
+  prev_hash BYTEA NOT NULL,
+  hash BYTEA NOT NULL UNIQUE
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_principal_idx ON audit_events (principal, id);
+CREATE INDEX IF NOT EXISTS audit_events_occurred_at_idx ON audit_events (occurred_at);
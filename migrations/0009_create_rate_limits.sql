@@ -0,0 +1,8 @@
+-- migrations/0009_create_rate_limits.sql
+-- GCRA state for the Postgres rate limit store: one theoretical arrival time
+-- per "<route group>:<client>" key. UNLOGGED because losing it in a crash
+-- only hands every client a fresh quota.
+CREATE UNLOGGED TABLE IF NOT EXISTS rate_limits (
+  key TEXT PRIMARY KEY,
+  tat TIMESTAMPTZ NOT NULL
+);
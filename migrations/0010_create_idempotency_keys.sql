@@ -0,0 +1,18 @@
+-- migrations/0010_create_idempotency_keys.sql
+-- Responses to requests sent with an Idempotency-Key, replayed on retries.
+-- Keys are scoped to the client ("user:1", "ip:203.0.113.7", ...) so one
+-- client can never receive another's response. status is NULL while the
+-- first request is still being handled.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+  client TEXT NOT NULL,
+  key TEXT NOT NULL,
+  fingerprint BYTEA NOT NULL,
+  status INTEGER,
+  headers JSONB,
+  body BYTEA,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (client, key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_expires_at_idx ON idempotency_keys (expires_at);
@@ -0,0 +1,20 @@
+-- migrations/0011_add_todo_version.sql
+-- Row version behind the todo ETag. The trigger bumps it on every update that
+-- changes the row, whoever issues it; bump_version() can be attached to any
+-- table with a version column.
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION bump_version() RETURNS trigger AS $$
+BEGIN
+  NEW.version := OLD.version + 1;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- CREATE TRIGGER has no IF NOT EXISTS (and OR REPLACE needs PostgreSQL 14)
+DROP TRIGGER IF EXISTS todos_bump_version ON todos;
+CREATE TRIGGER todos_bump_version
+  BEFORE UPDATE ON todos
+  FOR EACH ROW
+  WHEN (OLD.* IS DISTINCT FROM NEW.*)
+  EXECUTE FUNCTION bump_version();
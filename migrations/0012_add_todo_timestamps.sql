@@ -0,0 +1,45 @@
+-- migrations/0012_add_todo_timestamps.sql
+-- Lifecycle timestamps for todos, kept by the database rather than the
+-- handlers. deleted_at marks a soft-deleted (trashed) row; the API hides
+-- those except under /todos/trash until they are restored or purged.
+ALTER TABLE todos
+  ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+-- Inserts get the transaction time whatever the client sent; updates keep
+-- created_at and move updated_at. Both functions fit any table with these
+-- columns.
+CREATE OR REPLACE FUNCTION stamp_created() RETURNS trigger AS $$
+BEGIN
+  NEW.created_at := now();
+  NEW.updated_at := NEW.created_at;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION stamp_updated() RETURNS trigger AS $$
+BEGIN
+  NEW.created_at := OLD.created_at;
+  NEW.updated_at := now();
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS todos_stamp_created ON todos;
+CREATE TRIGGER todos_stamp_created
+  BEFORE INSERT ON todos
+  FOR EACH ROW
+  EXECUTE FUNCTION stamp_created();
+
+-- Fires after todos_bump_version (triggers run in name order), under the
+-- same condition, so no-op updates leave both version and updated_at alone.
+DROP TRIGGER IF EXISTS todos_stamp_updated ON todos;
+CREATE TRIGGER todos_stamp_updated
+  BEFORE UPDATE ON todos
+  FOR EACH ROW
+  WHEN (OLD.* IS DISTINCT FROM NEW.*)
+  EXECUTE FUNCTION stamp_updated();
+
+-- The trash listing and the retention purge only look at deleted rows
+CREATE INDEX IF NOT EXISTS todos_deleted_at_idx ON todos (deleted_at) WHERE deleted_at IS NOT NULL;
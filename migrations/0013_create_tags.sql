@@ -0,0 +1,20 @@
+-- migrations/0013_create_tags.sql
+-- Per-owner labels on todos. Names are unique per owner ignoring case; the
+-- admin token's tags (owner_id NULL) share one namespace.
+CREATE TABLE IF NOT EXISTS tags (
+  id         BIGSERIAL PRIMARY KEY,
+  owner_id   BIGINT REFERENCES users (id) ON DELETE CASCADE,
+  name       TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS tags_owner_name_key ON tags ((COALESCE(owner_id, 0)), (lower(name)));
+
+CREATE TABLE IF NOT EXISTS todo_tags (
+  todo_id BIGINT NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+  tag_id  BIGINT NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+  PRIMARY KEY (todo_id, tag_id)
+);
+
+-- Tag -> todos, for filtering, counts and renames
+CREATE INDEX IF NOT EXISTS todo_tags_tag_id_idx ON todo_tags (tag_id, todo_id);
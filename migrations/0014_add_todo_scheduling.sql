@@ -0,0 +1,46 @@
+-- migrations/0014_add_todo_scheduling.sql
+-- Scheduling fields for todos, plus the per-user timezone that date-based
+-- queries ("due today") are evaluated in. completed_at is kept by the
+-- database from done, like the other timestamps.
+ALTER TABLE todos
+  ADD COLUMN IF NOT EXISTS due_at TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS priority TEXT NOT NULL DEFAULT 'normal'
+    CHECK (priority IN ('low', 'normal', 'high', 'urgent')),
+  ADD COLUMN IF NOT EXISTS completed_at TIMESTAMPTZ;
+
+-- Best guess for rows completed before this column existed. The version and
+-- updated_at triggers are paused so the backfill doesn't look like an edit.
+ALTER TABLE todos DISABLE TRIGGER todos_bump_version;
+ALTER TABLE todos DISABLE TRIGGER todos_stamp_updated;
+UPDATE todos SET completed_at = updated_at WHERE done AND completed_at IS NULL;
+ALTER TABLE todos ENABLE TRIGGER todos_bump_version;
+ALTER TABLE todos ENABLE TRIGGER todos_stamp_updated;
+
+-- Set when done becomes true, cleared when it becomes false, and otherwise
+-- left alone whatever the client sent.
+CREATE OR REPLACE FUNCTION stamp_completed() RETURNS trigger AS $$
+BEGIN
+  IF NOT NEW.done THEN
+    NEW.completed_at := NULL;
+  ELSIF TG_OP = 'INSERT' OR NOT OLD.done THEN
+    NEW.completed_at := now();
+  ELSE
+    NEW.completed_at := OLD.completed_at;
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS todos_stamp_completed ON todos;
+CREATE TRIGGER todos_stamp_completed
+  BEFORE INSERT OR UPDATE ON todos
+  FOR EACH ROW
+  EXECUTE FUNCTION stamp_completed();
+
+-- Overdue and due-date filters only look at live rows with a due date
+CREATE INDEX IF NOT EXISTS todos_due_at_idx ON todos (due_at)
+  WHERE due_at IS NOT NULL AND deleted_at IS NULL;
+
+-- IANA name such as 'Europe/Berlin'; validated by the API on the way in.
+ALTER TABLE users
+  ADD COLUMN IF NOT EXISTS timezone TEXT NOT NULL DEFAULT 'UTC';
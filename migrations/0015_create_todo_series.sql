@@ -0,0 +1,38 @@
+-- migrations/0015_create_todo_series.sql
+-- Recurring todos. A series holds the RFC 5545 rule, its first occurrence
+-- (dtstart) and the timezone its wall-clock times repeat in, plus the
+-- template that new occurrences are created from. Only the open occurrence
+-- exists as a todo row; completing it creates the next one.
+CREATE TABLE IF NOT EXISTS todo_series (
+  id          BIGSERIAL PRIMARY KEY,
+  owner_id    BIGINT REFERENCES users (id) ON DELETE CASCADE,
+  rrule       TEXT NOT NULL,
+  dtstart     TIMESTAMPTZ NOT NULL,
+  timezone    TEXT NOT NULL DEFAULT 'UTC',
+  -- Occurrences at or after this are cut off, after a "this and following"
+  -- edit split the series or deleted its tail
+  ends_before TIMESTAMPTZ,
+  title       TEXT NOT NULL,
+  description TEXT NOT NULL,
+  priority    TEXT NOT NULL DEFAULT 'normal'
+    CHECK (priority IN ('low', 'normal', 'high', 'urgent')),
+  created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS todo_series_tags (
+  series_id BIGINT NOT NULL REFERENCES todo_series (id) ON DELETE CASCADE,
+  tag_id    BIGINT NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+  PRIMARY KEY (series_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS todo_series_tags_tag_id_idx ON todo_series_tags (tag_id, series_id);
+
+-- occurrence_at is the slot in the series a todo stands for (RECURRENCE-ID);
+-- it stays put when that one todo's due_at is moved. A slot is used once,
+-- trashed todos included, so skipping an occurrence doesn't bring it back.
+ALTER TABLE todos
+  ADD COLUMN IF NOT EXISTS series_id BIGINT REFERENCES todo_series (id) ON DELETE SET NULL,
+  ADD COLUMN IF NOT EXISTS occurrence_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX IF NOT EXISTS todos_series_occurrence_key ON todos (series_id, occurrence_at)
+  WHERE series_id IS NOT NULL;
@@ -0,0 +1,257 @@
+// src/audit/chain.rs
+use crate::audit::to_hex;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::fmt;
+
+/// `prev_hash` of the first event.
+pub const GENESIS_HASH: [u8; 32] = [0; 32];
+
+/// Transaction-level advisory lock serializing appends, so two requests can
+/// never link to the same predecessor.
+const CHAIN_LOCK: i64 = 0x6175_6469_745f_6c6f; // "audit_lo"
+
+const VERIFY_BATCH: i64 = 1000;
+
+/// The hashed fields of one audit event.
+#[derive(Clone, Debug, PartialEq, Eq, sqlx::FromRow)]
+pub struct NewAuditEvent {
+    /// Truncated to microseconds, the precision Postgres stores.
+    pub occurred_at: DateTime<Utc>,
+    pub principal: Option<String>,
+    pub method: String,
+    pub route: Option<String>,
+    pub path: String,
+    pub status: i32,
+    pub source_ip: Option<String>,
+    pub request_id: Option<String>,
+    pub body_digest: Option<Vec<u8>>,
+}
+
+impl NewAuditEvent {
+    /// SHA-256 over the predecessor's hash and every field, each length
+    /// prefixed (with a distinct marker for NULL) so no two events encode alike.
+    pub fn chain_hash(&self, prev_hash: &[u8]) -> [u8; 32] {
+        let occurred_at = self
+            .occurred_at
+            .to_rfc3339_opts(SecondsFormat::Micros, true);
+        let status = self.status.to_string();
+        let fields: [Option<&[u8]>; 9] = [
+            Some(occurred_at.as_bytes()),
+            self.principal.as_deref().map(str::as_bytes),
+            Some(self.method.as_bytes()),
+            self.route.as_deref().map(str::as_bytes),
+            Some(self.path.as_bytes()),
+            Some(status.as_bytes()),
+            self.source_ip.as_deref().map(str::as_bytes),
+            self.request_id.as_deref().map(str::as_bytes),
+            self.body_digest.as_deref(),
+        ];
+
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash);
+        for field in fields {
+            match field {
+                Some(bytes) => {
+                    hasher.update((bytes.len() as u32).to_be_bytes());
+                    hasher.update(bytes);
+                }
+                None => hasher.update(u32::MAX.to_be_bytes()),
+            }
+        }
+        hasher.finalize().into()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkError {
+    /// `prev_hash` is not the preceding row's hash: a row was deleted,
+    /// inserted or reordered before this one.
+    PrevHashMismatch,
+    /// The row's fields no longer produce its hash: it was edited.
+    HashMismatch,
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LinkError::PrevHashMismatch => "prev_hash does not match the preceding event",
+            LinkError::HashMismatch => "hash does not match the event's contents",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrokenLink {
+    pub id: i64,
+    pub error: LinkError,
+}
+
+/// Outcome of [`AuditLog::verify`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainReport {
+    pub events: u64,
+    /// Hash of the last event, hex encoded. Record it elsewhere: the chain
+    /// alone cannot reveal events cut off the end.
+    pub head: Option<String>,
+    pub broken: Vec<BrokenLink>,
+}
+
+impl ChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.broken.is_empty()
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredEvent {
+    id: i64,
+    prev_hash: Vec<u8>,
+    hash: Vec<u8>,
+    #[sqlx(flatten)]
+    event: NewAuditEvent,
+}
+
+/// Appends to and verifies the hash-chained `audit_events` table.
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    pool: PgPool,
+}
+
+impl AuditLog {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn append(&self, event: &NewAuditEvent) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(CHAIN_LOCK)
+            .execute(&mut *tx)
+            .await?;
+        let prev_hash: Vec<u8> =
+            sqlx::query_scalar("SELECT hash FROM audit_events ORDER BY id DESC LIMIT 1")
+                .fetch_optional(&mut *tx)
+                .await?
+                .unwrap_or_else(|| GENESIS_HASH.to_vec());
+
+        sqlx::query(
+            r#"
+            INSERT INTO audit_events (
+                occurred_at, principal, method, route, path, status,
+                source_ip, request_id, body_digest, prev_hash, hash
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(event.occurred_at)
+        .bind(&event.principal)
+        .bind(&event.method)
+        .bind(&event.route)
+        .bind(&event.path)
+        .bind(event.status)
+        .bind(&event.source_ip)
+        .bind(&event.request_id)
+        .bind(&event.body_digest)
+        .bind(&prev_hash)
+        .bind(event.chain_hash(&prev_hash).as_slice())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    /// Walks the whole chain in id order, recomputing every hash.
+    pub async fn verify(&self) -> Result<ChainReport, sqlx::Error> {
+        let mut report = ChainReport::default();
+        let mut prev_hash = GENESIS_HASH.to_vec();
+        let mut after = 0_i64;
+
+        loop {
+            let batch = sqlx::query_as::<_, StoredEvent>(
+                r#"
+                SELECT id, occurred_at, principal, method, route, path, status,
+                       source_ip, request_id, body_digest, prev_hash, hash
+                FROM audit_events
+                WHERE id > $1
+                ORDER BY id
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(VERIFY_BATCH)
+            .fetch_all(&self.pool)
+            .await?;
+            if batch.is_empty() {
+                break;
+            }
+
+            for row in batch {
+                report.events += 1;
+                if row.prev_hash != prev_hash {
+                    report.broken.push(BrokenLink {
+                        id: row.id,
+                        error: LinkError::PrevHashMismatch,
+                    });
+                }
+                // Hashed against the row's own prev_hash, so one edit is
+                // reported once rather than for every later row
+                if row.event.chain_hash(&row.prev_hash).as_slice() != row.hash {
+                    report.broken.push(BrokenLink {
+                        id: row.id,
+                        error: LinkError::HashMismatch,
+                    });
+                }
+                after = row.id;
+                prev_hash = row.hash;
+            }
+        }
+
+        if report.events > 0 {
+            report.head = Some(to_hex(&prev_hash));
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> NewAuditEvent {
+        NewAuditEvent {
+            occurred_at: DateTime::parse_from_rfc3339("2025-01-01T00:00:00.123456Z")
+                .unwrap()
+                .to_utc(),
+            principal: Some("user:1".into()),
+            method: "POST".into(),
+            route: Some("/todos".into()),
+            path: "/todos".into(),
+            status: 201,
+            source_ip: Some("127.0.0.1".into()),
+            request_id: None,
+            body_digest: None,
+        }
+    }
+
+    #[test]
+    fn hash_covers_fields_and_predecessor() {
+        let base = event().chain_hash(&GENESIS_HASH);
+        assert_eq!(base, event().chain_hash(&GENESIS_HASH));
+        assert_ne!(base, event().chain_hash(&[1; 32]));
+
+        let edited = NewAuditEvent {
+            status: 200,
+            ..event()
+        };
+        assert_ne!(base, edited.chain_hash(&GENESIS_HASH));
+
+        // NULL and empty are different values
+        let empty = NewAuditEvent {
+            request_id: Some(String::new()),
+            ..event()
+        };
+        assert_ne!(base, empty.chain_hash(&GENESIS_HASH));
+    }
+}
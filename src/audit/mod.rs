@@ -0,0 +1,11 @@
+// src/audit/mod.rs
+mod chain;
+mod redact;
+
+pub use chain::{AuditLog, BrokenLink, ChainReport, GENESIS_HASH, LinkError, NewAuditEvent};
+pub use redact::{REDACTED, REDACTED_FIELDS, body_digest};
+
+/// Lowercase hex, as hashes are shown in the API and by `audit-verify`.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,96 @@
+// src/audit/redact.rs
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// JSON keys (compared case-insensitively, at any depth) whose values are
+/// replaced before digesting. A plain digest of a body containing a password
+/// could be brute-forced back to the password.
+pub const REDACTED_FIELDS: &[&str] = &[
+    "password",
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "client_secret",
+    "code_verifier",
+    "token",
+    "secret",
+];
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// SHA-256 of a request body, `None` when it is empty. JSON bodies are
+/// redacted and re-serialized with sorted keys first, so the digest doesn't
+/// depend on key order or whitespace; anything else is digested as sent.
+pub fn body_digest(content_type: Option<&str>, body: &[u8]) -> Option<Vec<u8>> {
+    if body.is_empty() {
+        return None;
+    }
+    let is_json = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(str::trim)
+        .is_some_and(|mime| mime == "application/json" || mime.ends_with("+json"));
+
+    let digest = match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) if is_json => {
+            redact(&mut value);
+            Sha256::digest(value.to_string().as_bytes())
+        }
+        _ => Sha256::digest(body),
+    };
+    Some(digest.to_vec())
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS
+                    .iter()
+                    .any(|field| key.eq_ignore_ascii_case(field))
+                {
+                    *value = Value::String(REDACTED.to_owned());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_digests_ignore_secrets_and_formatting() {
+        let a = body_digest(
+            Some("application/json"),
+            br#"{"username":"alice","password":"hunter22"}"#,
+        );
+        let b = body_digest(
+            Some("application/json; charset=utf-8"),
+            br#"{ "password": "other", "username": "alice" }"#,
+        );
+        assert_eq!(a, b);
+
+        let expected = Sha256::digest(br#"{"password":"[REDACTED]","username":"alice"}"#);
+        assert_eq!(a, Some(expected.to_vec()));
+
+        let other_user = body_digest(
+            Some("application/json"),
+            br#"{"username":"bob","password":"hunter22"}"#,
+        );
+        assert_ne!(a, other_user);
+    }
+
+    #[test]
+    fn other_bodies_are_digested_verbatim() {
+        assert_eq!(body_digest(Some("application/json"), b""), None);
+        assert_eq!(
+            body_digest(Some("text/plain"), br#"{"password":"x"}"#),
+            Some(Sha256::digest(br#"{"password":"x"}"#).to_vec())
+        );
+    }
+}
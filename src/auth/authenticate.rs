@@ -0,0 +1,280 @@
+// src/auth/authenticate.rs
+use crate::auth::{
+    API_KEY_MARKER, AccessClaims, Principal, RbacPolicy, SessionManager, TokenService, check_csrf,
+    resolve_api_key,
+};
+use crate::config::AuthConfig;
+use crate::error::ApiError;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+/// Always reachable without credentials, even when ADMIN_TOKEN locks the API
+/// down: they authenticate with the request body or through the IdP instead. Configurable public
+/// routes come on top of these.
+const LOCKDOWN_EXEMPT: &[&str] = &[
+    "/auth/token",
+    "/auth/refresh",
+    "/auth/logout",
+    "/auth/session",
+    "/auth/oidc/login",
+    "/auth/oidc/callback",
+];
+
+/// Resolves the `Authorization: Bearer` credential into a [`Principal`].
+#[derive(Clone)]
+pub struct Authenticator {
+    /// SHA-256 of each accepted admin token, so comparisons are fixed-length.
+    admin_digests: Arc<[[u8; 32]]>,
+    public_routes: Arc<[String]>,
+    tokens: TokenService,
+    sessions: SessionManager,
+    policy: RbacPolicy,
+    pool: PgPool,
+}
+
+impl Authenticator {
+    /// `pool` is used to look up API keys.
+    pub fn new(cfg: &AuthConfig, pool: PgPool) -> Self {
+        Self {
+            admin_digests: cfg
+                .admin_tokens
+                .iter()
+                .map(|t| digest(t.expose()))
+                .collect(),
+            public_routes: cfg.public_routes.iter().cloned().collect(),
+            tokens: TokenService::new(cfg),
+            sessions: SessionManager::new(&cfg.session),
+            policy: cfg.policy.clone(),
+            pool,
+        }
+    }
+
+    pub fn tokens(&self) -> &TokenService {
+        &self.tokens
+    }
+
+    pub fn sessions(&self) -> &SessionManager {
+        &self.sessions
+    }
+
+    /// With an admin token configured, anonymous requests are refused outright
+    /// instead of being left to the handlers.
+    pub fn locked_down(&self) -> bool {
+        !self.admin_digests.is_empty()
+    }
+
+    /// Whether `path` may be requested anonymously under lockdown.
+    pub fn is_public(&self, path: &str) -> bool {
+        LOCKDOWN_EXEMPT.contains(&path)
+            || self
+                .public_routes
+                .iter()
+                .any(|route| match route.strip_suffix("/*") {
+                    Some(prefix) => path
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+                    None => route == path,
+                })
+    }
+
+    /// Checks every configured token without short-circuiting, comparing
+    /// digests in constant time, so timing reveals neither which token matched
+    /// nor how much of it was right.
+    fn is_admin_token(&self, bearer: &str) -> bool {
+        let presented = digest(bearer);
+        self.admin_digests
+            .iter()
+            .fold(subtle::Choice::from(0), |matched, d| {
+                matched | d.ct_eq(&presented)
+            })
+            .into()
+    }
+
+    /// The principal behind `bearer`, plus the access token claims for users.
+    pub async fn resolve(
+        &self,
+        bearer: &str,
+    ) -> Result<(Principal, Option<AccessClaims>), ApiError> {
+        if self.is_admin_token(bearer) {
+            return Ok((Principal::Admin, None));
+        }
+
+        if bearer.starts_with(API_KEY_MARKER) {
+            let key = resolve_api_key(&self.pool, bearer)
+                .await?
+                .ok_or_else(|| ApiError::Unauthorized("Invalid or expired API key".to_owned()))?;
+            tracing::debug!(api_key_id = key.id, "authenticated with API key");
+            let principal = Principal::ApiKey {
+                id: key.id,
+                user_id: key.user_id,
+                role: key.role,
+                scopes: key.scopes,
+            };
+            return Ok((principal, None));
+        }
+
+        // Neither a key nor JWT-shaped: a static token that isn't (or is no
+        // longer) accepted. Re-authenticating won't help, so this is 403.
+        if self.locked_down() && bearer.split('.').count() != 3 {
+            tracing::info!("rejected unknown static bearer token");
+            return Err(ApiError::Forbidden("Invalid admin token".to_owned()));
+        }
+
+        let claims = self.tokens.verify(bearer).map_err(|e| {
+            tracing::debug!(error = %e, "rejected access token");
+            ApiError::Unauthorized("Invalid or expired token".to_owned())
+        })?;
+        let id = claims
+            .user_id()
+            .ok_or_else(|| ApiError::Unauthorized("Invalid or expired token".to_owned()))?;
+        let role = claims.role;
+        Ok((Principal::User { id, role }, Some(claims)))
+    }
+}
+
+/// Marks a response on which `authenticate` refused the request's credentials,
+/// so outer layers can charge the failure to the client's address.
+#[derive(Clone, Copy, Debug)]
+pub struct CredentialsRejected;
+
+fn reject(e: ApiError) -> Response {
+    let mut res = e.into_response();
+    if res.status().is_client_error() {
+        res.extensions_mut().insert(CredentialsRejected);
+    }
+    res
+}
+
+/// `Some(Ok(token))` for a bearer credential, `Some(Err)` for any other scheme.
+fn bearer_token(headers: &HeaderMap) -> Option<Result<&str, ApiError>> {
+    let value = headers.get(header::AUTHORIZATION)?;
+    let token = value
+        .to_str()
+        .ok()
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    Some(token.ok_or_else(|| {
+        ApiError::Unauthorized("Authorization header must use the Bearer scheme".to_owned())
+    }))
+}
+
+/// Inserts the caller's [`Principal`] and its [`Permissions`](crate::auth::Permissions)
+/// into request extensions. A bad credential is refused (401, or 403 for an
+/// unknown static token); a missing one only under lockdown, and never on
+/// public routes.
+///
+/// Without an Authorization header the session cookie is tried. Cookie
+/// requests with unsafe methods must pass the CSRF check; an invalid or
+/// expired cookie is simply ignored, so the request continues anonymously.
+///
+/// The principal is copied onto the response as well, for outer layers such
+/// as the audit log; refusals carry [`CredentialsRejected`] instead.
+pub async fn authenticate(
+    State(auth): State<Authenticator>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let principal = match bearer_token(req.headers()) {
+        Some(Ok(token)) => match auth.resolve(token).await {
+            Ok(principal) => Some(principal),
+            Err(e) => return reject(e),
+        },
+        Some(Err(e)) => return reject(e),
+        None => match auth.sessions.resolve(&auth.pool, req.headers()).await {
+            Ok(Some(session)) => {
+                if let Err(e) = check_csrf(req.method(), req.headers(), &session) {
+                    tracing::info!(
+                        session_id = session.id,
+                        "rejected request failing CSRF check"
+                    );
+                    return reject(e);
+                }
+                let principal = Principal::User {
+                    id: session.user_id,
+                    role: session.role,
+                };
+                req.extensions_mut().insert(session);
+                Some((principal, None))
+            }
+            Ok(None) => None,
+            Err(e) => return ApiError::from(e).into_response(),
+        },
+    };
+
+    let mut caller = None;
+    match principal {
+        Some((principal, claims)) => {
+            req.extensions_mut()
+                .insert(auth.policy.permissions(&principal));
+            caller = Some(principal.clone());
+            req.extensions_mut().insert(principal);
+            if let Some(claims) = claims {
+                req.extensions_mut().insert(claims);
+            }
+        }
+        None if auth.locked_down() && !auth.is_public(req.uri().path()) => {
+            return reject(ApiError::Unauthorized("Authentication required".to_owned()));
+        }
+        None => {}
+    }
+
+    let mut res = next.run(req).await;
+    if let Some(principal) = caller {
+        res.extensions_mut().insert(principal);
+    }
+    res
+}
+
+fn digest(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Secret;
+
+    fn authenticator(admin_tokens: &[&str], public_routes: &[&str]) -> Authenticator {
+        let cfg = AuthConfig {
+            admin_tokens: admin_tokens.iter().map(|&t| Secret::new(t)).collect(),
+            public_routes: public_routes.iter().map(|&p| p.to_owned()).collect(),
+            ..Default::default()
+        };
+        let pool = PgPool::connect_lazy("postgres://127.0.0.1/postgres").expect("lazy pool");
+        Authenticator::new(&cfg, pool)
+    }
+
+    #[tokio::test]
+    async fn accepts_every_configured_admin_token() {
+        let auth = authenticator(&["current", "next"], &[]);
+        assert!(auth.is_admin_token("current"));
+        assert!(auth.is_admin_token("next"));
+        assert!(!auth.is_admin_token("curren"));
+
+        let err = auth.resolve("stale").await.unwrap_err();
+        assert!(matches!(err, ApiError::Forbidden(_)));
+        let err = auth.resolve("a.b.c").await.unwrap_err();
+        assert!(matches!(err, ApiError::Unauthorized(_)));
+    }
+
+    #[tokio::test]
+    async fn public_routes_match_exactly_or_by_subtree() {
+        let auth = authenticator(&["t"], &["/health", "/docs/*"]);
+        assert!(auth.is_public("/health"));
+        assert!(!auth.is_public("/health/ready"));
+        assert!(auth.is_public("/docs"));
+        assert!(auth.is_public("/docs/api"));
+        assert!(!auth.is_public("/docsx"));
+        assert!(auth.is_public("/auth/token"));
+        assert!(!auth.is_public("/todos"));
+    }
+}
@@ -0,0 +1,26 @@
+mod api_key;
+mod authenticate;
+pub(crate) mod keys;
+pub mod oidc;
+mod password;
+mod policy;
+mod principal;
+mod refresh;
+mod scope;
+mod session;
+mod tokens;
+
+pub use api_key::{
+    API_KEY_MARKER, ApiKeyIdentity, GeneratedApiKey, generate_api_key, resolve_api_key,
+};
+pub use authenticate::{Authenticator, CredentialsRejected, authenticate};
+pub use keys::JwtKey;
+pub use password::{hash_password, verify_password};
+pub use policy::{Permissions, RbacPolicy, Role, ScopeGuard, require_scope};
+pub use principal::{AuthUser, Principal};
+pub use refresh::{
+    RefreshError, RotatedRefresh, issue_refresh_token, revoke_refresh_token, rotate_refresh_token,
+};
+pub use scope::Scope;
+pub use session::{CSRF_HEADER, NewSession, Session, SessionManager, check_csrf};
+pub use tokens::{AccessClaims, TokenService};
@@ -0,0 +1,274 @@
+// src/auth/oidc/login.rs
+use crate::auth::Role;
+use crate::auth::oidc::{IdTokenClaims, OidcError, OidcProvider, pkce};
+use crate::models::{USERNAME_MAX_CHARS, USERNAME_MIN_CHARS};
+use axum::http::{HeaderMap, HeaderValue, header};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+
+/// Carries `state` in the browser that started the login, so a callback
+/// URL from someone else's login (login CSRF) is refused.
+pub const STATE_COOKIE: &str = "oidc_state";
+
+/// Where to send the browser, and the `state` it must come back with.
+pub struct LoginRedirect {
+    pub url: String,
+    pub state: String,
+}
+
+/// Starts a login: stores state, nonce and PKCE verifier for the callback and
+/// returns the IdP authorization URL to send the browser to.
+pub async fn begin_login(
+    pool: &PgPool,
+    provider: &OidcProvider,
+) -> Result<LoginRedirect, OidcError> {
+    let metadata = provider.metadata().await?;
+    let cfg = provider.config();
+    let (state, nonce, verifier) = (
+        pkce::random_token(),
+        pkce::random_token(),
+        pkce::random_token(),
+    );
+
+    // Abandoned logins are swept here rather than by a background job
+    sqlx::query("DELETE FROM oidc_logins WHERE expires_at < now()")
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO oidc_logins (state_hash, nonce, code_verifier, expires_at)
+        VALUES ($1, $2, $3, now() + make_interval(secs => $4))
+        "#,
+    )
+    .bind(digest(&state))
+    .bind(&nonce)
+    .bind(&verifier)
+    .bind(cfg.login_ttl.as_secs_f64())
+    .execute(pool)
+    .await?;
+
+    let mut url = reqwest::Url::parse(&metadata.authorization_endpoint)
+        .map_err(|e| OidcError::Provider(format!("invalid authorization_endpoint: {e}")))?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &cfg.client_id)
+        .append_pair("redirect_uri", &cfg.redirect_url)
+        .append_pair("scope", &cfg.scopes.join(" "))
+        .append_pair("state", &state)
+        .append_pair("nonce", &nonce)
+        .append_pair("code_challenge", &pkce::challenge(&verifier))
+        .append_pair("code_challenge_method", "S256");
+    Ok(LoginRedirect {
+        url: url.into(),
+        state,
+    })
+}
+
+/// `Set-Cookie` value for [`STATE_COOKIE`]. SameSite=Lax, since the IdP's
+/// redirect back is a cross-site navigation; an empty value clears it.
+pub fn state_cookie(state: &str, max_age: Duration, secure: bool) -> HeaderValue {
+    let mut cookie = format!(
+        "{STATE_COOKIE}={state}; Path=/auth/oidc; Max-Age={}; HttpOnly; SameSite=Lax",
+        max_age.as_secs()
+    );
+    if secure {
+        cookie.push_str("; Secure");
+    }
+    HeaderValue::from_str(&cookie).expect("state tokens are header-safe")
+}
+
+/// The [`STATE_COOKIE`] value the browser sent, if any.
+pub fn browser_state(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == STATE_COOKIE)
+        .map(|(_, value)| value)
+}
+
+/// A finished login, mapped onto a local account.
+#[derive(Clone, Debug)]
+pub struct OidcLogin {
+    pub user_id: i64,
+    pub role: Role,
+    pub created: bool,
+}
+
+/// Finishes a login: checks `state` against the one in the browser's cookie,
+/// consumes it (each works once), redeems `code`, validates the ID token and
+/// finds or creates the local user.
+pub async fn complete_login(
+    pool: &PgPool,
+    provider: &OidcProvider,
+    browser_state: Option<&str>,
+    state: &str,
+    code: &str,
+) -> Result<OidcLogin, OidcError> {
+    if !browser_state.is_some_and(|b| bool::from(b.as_bytes().ct_eq(state.as_bytes()))) {
+        return Err(OidcError::InvalidState);
+    }
+    let pending: Option<(String, String)> = sqlx::query_as(
+        r#"
+        DELETE FROM oidc_logins
+        WHERE state_hash = $1 AND expires_at > now()
+        RETURNING nonce, code_verifier
+        "#,
+    )
+    .bind(digest(state))
+    .fetch_optional(pool)
+    .await?;
+    let (nonce, verifier) = pending.ok_or(OidcError::InvalidState)?;
+
+    let id_token = provider.exchange_code(code, &verifier).await?;
+    let claims = provider.validate_id_token(&id_token, &nonce).await?;
+    link_identity(pool, &claims).await
+}
+
+/// Identities are keyed by (issuer, subject) only. Emails are informational:
+/// linking on them would let anyone who controls an IdP account with a
+/// matching address take over the local user.
+async fn link_identity(pool: &PgPool, claims: &IdTokenClaims) -> Result<OidcLogin, OidcError> {
+    if let Some(login) = find_identity(pool, claims).await? {
+        return Ok(login);
+    }
+    match provision_user(pool, claims).await {
+        // A concurrent first login for the same subject won the unique key and
+        // our user was rolled back; log in as the one it linked instead
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => find_identity(pool, claims)
+            .await?
+            .ok_or(OidcError::Database(sqlx::Error::Database(e))),
+        res => Ok(res?),
+    }
+}
+
+async fn find_identity(
+    pool: &PgPool,
+    claims: &IdTokenClaims,
+) -> Result<Option<OidcLogin>, sqlx::Error> {
+    let existing: Option<(i64, Role)> = sqlx::query_as(
+        r#"
+        UPDATE user_identities ui SET last_login_at = now(), email = $3
+        FROM users u
+        WHERE ui.user_id = u.id AND ui.issuer = $1 AND ui.subject = $2
+        RETURNING u.id, u.role
+        "#,
+    )
+    .bind(&claims.iss)
+    .bind(&claims.sub)
+    .bind(claims.email.as_deref())
+    .fetch_optional(pool)
+    .await?;
+    Ok(existing.map(|(user_id, role)| OidcLogin {
+        user_id,
+        role,
+        created: false,
+    }))
+}
+
+async fn provision_user(pool: &PgPool, claims: &IdTokenClaims) -> Result<OidcLogin, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let base = username_hint(claims);
+    let mut candidate = base.clone();
+    let user = loop {
+        let inserted: Option<(i64, Role)> = sqlx::query_as(
+            r#"
+            INSERT INTO users (username, password_hash) VALUES ($1, NULL)
+            ON CONFLICT (lower(username)) DO NOTHING
+            RETURNING id, role
+            "#,
+        )
+        .bind(&candidate)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(user) = inserted {
+            break user;
+        }
+        candidate = with_suffix(&base);
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_identities (user_id, issuer, subject, email, last_login_at)
+        VALUES ($1, $2, $3, $4, now())
+        "#,
+    )
+    .bind(user.0)
+    .bind(&claims.iss)
+    .bind(&claims.sub)
+    .bind(claims.email.as_deref())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(user_id = user.0, username = %candidate, issuer = %claims.iss, "provisioned user from OIDC login");
+    Ok(OidcLogin {
+        user_id: user.0,
+        role: user.1,
+        created: true,
+    })
+}
+
+/// A username from `preferred_username` or the email's local part, reduced
+/// to characters that are unambiguous in URLs and logs.
+fn username_hint(claims: &IdTokenClaims) -> String {
+    let raw = claims
+        .preferred_username
+        .as_deref()
+        .or_else(|| claims.email.as_deref().and_then(|e| e.split('@').next()))
+        .unwrap_or("user");
+    let mut name: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        .take(USERNAME_MAX_CHARS - 5)
+        .collect();
+    if name.chars().count() < USERNAME_MIN_CHARS {
+        name = format!("user{name}");
+    }
+    name
+}
+
+fn with_suffix(base: &str) -> String {
+    format!("{base}-{:04}", rand::thread_rng().gen_range(0..10_000))
+}
+
+fn digest(state: &str) -> Vec<u8> {
+    Sha256::digest(state.as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::oidc::Audience;
+
+    fn claims(preferred_username: Option<&str>, email: Option<&str>) -> IdTokenClaims {
+        IdTokenClaims {
+            iss: "https://idp".into(),
+            sub: "1".into(),
+            aud: Audience::One("app".into()),
+            azp: None,
+            nonce: None,
+            preferred_username: preferred_username.map(Into::into),
+            email: email.map(Into::into),
+            email_verified: false,
+        }
+    }
+
+    #[test]
+    fn usernames_are_derived_and_sanitised() {
+        assert_eq!(username_hint(&claims(Some("Jane Doe!"), None)), "JaneDoe");
+        assert_eq!(
+            username_hint(&claims(None, Some("jd@example.com"))),
+            "userjd"
+        );
+        assert_eq!(username_hint(&claims(None, None)), "user");
+        let long = "x".repeat(100);
+        let name = username_hint(&claims(Some(&long), None));
+        assert!(with_suffix(&name).len() <= USERNAME_MAX_CHARS);
+    }
+}
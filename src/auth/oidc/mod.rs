@@ -0,0 +1,29 @@
+// src/auth/oidc/mod.rs
+mod login;
+mod pkce;
+mod provider;
+
+pub use login::{
+    LoginRedirect, OidcLogin, STATE_COOKIE, begin_login, browser_state, complete_login,
+    state_cookie,
+};
+pub use provider::{Audience, IdTokenClaims, OidcProvider, ProviderMetadata};
+
+/// Why an OIDC login failed.
+#[derive(Debug, thiserror::Error)]
+pub enum OidcError {
+    /// Unknown, reused or expired `state`, or one the browser doesn't hold:
+    /// not a login we started for this browser.
+    #[error("unknown or expired login state")]
+    InvalidState,
+    /// The IdP refused the authorization code or our client credentials.
+    #[error("authorization code was rejected")]
+    Rejected,
+    #[error("invalid ID token: {0}")]
+    InvalidIdToken(String),
+    /// The IdP is unreachable or answered with something unusable.
+    #[error("identity provider error: {0}")]
+    Provider(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
@@ -0,0 +1,299 @@
+// src/auth/oidc/provider.rs
+use crate::auth::oidc::OidcError;
+use crate::config::OidcConfig;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation,
+    jwk::{Jwk, JwkSet, PublicKeyUse},
+};
+use serde::Deserialize;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Only asymmetric algorithms: an ID token signed with a shared secret (or
+/// `none`) proves nothing about who minted it.
+const ALLOWED_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Unknown `kid`s trigger a JWKS refetch, but no more often than this, so
+/// forged tokens can't turn into a request flood against the IdP.
+const MIN_JWKS_REFRESH: Duration = Duration::from_secs(30);
+
+/// The subset of the discovery document we use.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// Verified ID token claims.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    #[serde(default)]
+    pub aud: Audience,
+    pub azp: Option<String>,
+    pub nonce: Option<String>,
+    pub preferred_username: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    #[default]
+    None,
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn len(&self) -> usize {
+        match self {
+            Audience::None => 0,
+            Audience::One(_) => 1,
+            Audience::Many(v) => v.len(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Default)]
+struct Cache {
+    metadata: Option<ProviderMetadata>,
+    jwks: Option<JwkSet>,
+    jwks_fetched_at: Option<Instant>,
+}
+
+/// Talks to one OpenID provider: discovery, JWKS, code exchange and ID token
+/// validation. Discovery happens on first use, so the API starts even while
+/// the IdP is down.
+#[derive(Clone)]
+pub struct OidcProvider {
+    cfg: Arc<OidcConfig>,
+    http: reqwest::Client,
+    leeway: Duration,
+    cache: Arc<RwLock<Cache>>,
+}
+
+impl OidcProvider {
+    pub fn new(cfg: &OidcConfig, leeway: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("static reqwest configuration is valid");
+        Self {
+            cfg: Arc::new(cfg.clone()),
+            http,
+            leeway,
+            cache: Arc::default(),
+        }
+    }
+
+    pub fn config(&self) -> &OidcConfig {
+        &self.cfg
+    }
+
+    pub async fn metadata(&self) -> Result<ProviderMetadata, OidcError> {
+        if let Some(metadata) = self
+            .cache
+            .read()
+            .expect("oidc cache poisoned")
+            .metadata
+            .clone()
+        {
+            return Ok(metadata);
+        }
+
+        let url = format!("{}/.well-known/openid-configuration", self.cfg.issuer);
+        let metadata: ProviderMetadata = self.get_json(&url).await?;
+        // Discovery must describe the issuer we were configured with (OIDC Discovery 4.3)
+        if metadata.issuer.trim_end_matches('/') != self.cfg.issuer {
+            return Err(OidcError::Provider(format!(
+                "discovery document is for issuer {}, expected {}",
+                metadata.issuer, self.cfg.issuer
+            )));
+        }
+        self.cache.write().expect("oidc cache poisoned").metadata = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Redeems an authorization code and returns the raw ID token.
+    pub async fn exchange_code(&self, code: &str, verifier: &str) -> Result<String, OidcError> {
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.cfg.redirect_url.as_str()),
+            ("code_verifier", verifier),
+        ];
+        let mut req = self.http.post(&metadata.token_endpoint);
+        match &self.cfg.client_secret {
+            Some(secret) => req = req.basic_auth(&self.cfg.client_id, Some(secret.expose())),
+            None => form.push(("client_id", self.cfg.client_id.as_str())),
+        }
+
+        let res = req
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| OidcError::Provider(format!("token request failed: {e}")))?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.text().await.unwrap_or_default();
+            // 4xx here means the code, verifier or client credentials were rejected
+            return Err(if status.is_client_error() {
+                tracing::info!(%status, %body, "OIDC code exchange rejected");
+                OidcError::Rejected
+            } else {
+                OidcError::Provider(format!("token endpoint returned {status}"))
+            });
+        }
+        let body: TokenEndpointResponse = res
+            .json()
+            .await
+            .map_err(|e| OidcError::Provider(format!("malformed token response: {e}")))?;
+        body.id_token
+            .ok_or_else(|| OidcError::Provider("token response has no id_token".to_owned()))
+    }
+
+    /// Checks signature, issuer, audience, expiry and nonce (OIDC Core 3.1.3.7).
+    pub async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let header = jsonwebtoken::decode_header(id_token)
+            .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(OidcError::InvalidIdToken(format!(
+                "algorithm {:?} is not accepted",
+                header.alg
+            )));
+        }
+        let key = self.signing_key(header.kid.as_deref(), header.alg).await?;
+        let metadata = self.metadata().await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = self.leeway.as_secs();
+        // `iss` must match the discovered issuer exactly, trailing slash and
+        // all; the configured one has been normalised (OIDC Core 3.1.3.7)
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.cfg.client_id]);
+        validation.set_required_spec_claims(&["exp", "iat", "iss", "aud", "sub"]);
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?
+            .claims;
+
+        if claims.aud.len() > 1 && claims.azp.as_deref() != Some(self.cfg.client_id.as_str()) {
+            return Err(OidcError::InvalidIdToken(
+                "azp must name this client when there are several audiences".to_owned(),
+            ));
+        }
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".to_owned()));
+        }
+        Ok(claims)
+    }
+
+    /// Finds the JWK for `kid`, refetching the set when it is stale or the kid
+    /// is unknown (the IdP rotated keys).
+    async fn signing_key(
+        &self,
+        kid: Option<&str>,
+        alg: Algorithm,
+    ) -> Result<DecodingKey, OidcError> {
+        let (cached, fresh, may_refresh) = {
+            let cache = self.cache.read().expect("oidc cache poisoned");
+            let age = cache.jwks_fetched_at.map(|t| t.elapsed());
+            (
+                cache.jwks.clone(),
+                age.is_some_and(|a| a < self.cfg.jwks_ttl),
+                age.is_none_or(|a| a >= MIN_JWKS_REFRESH),
+            )
+        };
+
+        if fresh && let Some(jwk) = cached.as_ref().and_then(|set| find_key(set, kid)) {
+            return decoding_key(jwk, alg);
+        }
+        if !fresh || may_refresh {
+            let set = self.fetch_jwks().await?;
+            if let Some(jwk) = find_key(&set, kid) {
+                return decoding_key(jwk, alg);
+            }
+        }
+        Err(OidcError::InvalidIdToken(format!(
+            "no signing key with kid {}",
+            kid.unwrap_or("(none)")
+        )))
+    }
+
+    async fn fetch_jwks(&self) -> Result<JwkSet, OidcError> {
+        let metadata = self.metadata().await?;
+        let set: JwkSet = self.get_json(&metadata.jwks_uri).await?;
+        tracing::debug!(keys = set.keys.len(), "fetched OIDC signing keys");
+        let mut cache = self.cache.write().expect("oidc cache poisoned");
+        cache.jwks = Some(set.clone());
+        cache.jwks_fetched_at = Some(Instant::now());
+        Ok(set)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, OidcError> {
+        let res = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| OidcError::Provider(format!("GET {url} failed: {e}")))?;
+        res.json()
+            .await
+            .map_err(|e| OidcError::Provider(format!("GET {url} returned malformed JSON: {e}")))
+    }
+}
+
+/// Signing keys only; without a `kid` the set must hold exactly one candidate.
+fn find_key<'a>(set: &'a JwkSet, kid: Option<&str>) -> Option<&'a Jwk> {
+    let mut signing = set.keys.iter().filter(|k| {
+        !matches!(
+            k.common.public_key_use,
+            Some(PublicKeyUse::Encryption | PublicKeyUse::Other(_))
+        )
+    });
+    match kid {
+        Some(kid) => signing.find(|k| k.common.key_id.as_deref() == Some(kid)),
+        None => {
+            let first = signing.next();
+            signing.next().is_none().then_some(first).flatten()
+        }
+    }
+}
+
+fn decoding_key(jwk: &Jwk, alg: Algorithm) -> Result<DecodingKey, OidcError> {
+    // A key that declares its algorithm may only be used with that algorithm
+    if let Some(key_alg) = jwk.common.key_algorithm
+        && key_alg.to_string() != format!("{alg:?}")
+    {
+        return Err(OidcError::InvalidIdToken(format!(
+            "key is for {key_alg}, token uses {alg:?}"
+        )));
+    }
+    DecodingKey::from_jwk(jwk).map_err(|e| OidcError::InvalidIdToken(e.to_string()))
+}
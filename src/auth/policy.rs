@@ -0,0 +1,248 @@
+// src/auth/policy.rs
+use crate::auth::{Principal, Scope};
+use crate::error::ApiError;
+use anyhow::{Context, Result, bail};
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::request::Parts,
+    middleware::{FromFnLayer, Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::{fmt, future::Future, pin::Pin, str::FromStr};
+
+/// Account role, stored on `users.role` and carried in access tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    #[default]
+    Member,
+    ReadOnly,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Admin, Role::Member, Role::ReadOnly];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Member => "member",
+            Role::ReadOnly => "read_only",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str() == s)
+            .ok_or_else(|| format!("unknown role {s:?}"))
+    }
+}
+
+/// The scopes a request may exercise, resolved once by `authenticate` and
+/// checked by [`require_scope`] guards.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Permissions(BTreeSet<Scope>);
+
+impl Permissions {
+    pub fn all() -> Self {
+        Self(Scope::ALL.into_iter().collect())
+    }
+
+    pub fn contains(&self, scope: Scope) -> bool {
+        self.0.contains(&scope)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Scope> + '_ {
+        self.0.iter().copied()
+    }
+
+    fn intersect(&self, other: &Permissions) -> Self {
+        Self(self.0.intersection(&other.0).copied().collect())
+    }
+}
+
+impl FromIterator<Scope> for Permissions {
+    fn from_iter<I: IntoIterator<Item = Scope>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Permissions {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Permissions>()
+            .cloned()
+            .ok_or_else(|| ApiError::Unauthorized("Authentication required".to_owned()))
+    }
+}
+
+/// Role -> scope matrix. The ADMIN_TOKEN always has every scope; API keys get
+/// their own scopes, narrowed by the bound user's role when they have one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RbacPolicy {
+    roles: HashMap<Role, Permissions>,
+}
+
+impl Default for RbacPolicy {
+    fn default() -> Self {
+        use Scope::*;
+        Self {
+            roles: HashMap::from([
+                (Role::Admin, Permissions::all()),
+                (Role::Member, [TodosRead, TodosWrite].into_iter().collect()),
+                (Role::ReadOnly, [TodosRead].into_iter().collect()),
+            ]),
+        }
+    }
+}
+
+impl RbacPolicy {
+    /// Overrides the default matrix from `role=scope,scope;role=...`, e.g.
+    /// `member=todos:read;read_only=`. `*` stands for every scope; roles that
+    /// aren't mentioned keep their defaults.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut policy = Self::default();
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (role, scopes) = entry
+                .split_once('=')
+                .with_context(|| format!("RBAC_POLICY entry {entry:?} must be role=scopes"))?;
+            let role: Role = role.trim().parse().map_err(anyhow::Error::msg)?;
+            let scopes = scopes.trim();
+            let permissions = if scopes == "*" {
+                Permissions::all()
+            } else {
+                scopes
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.parse::<Scope>().map_err(anyhow::Error::msg))
+                    .collect::<Result<Permissions>>()?
+            };
+            policy.roles.insert(role, permissions);
+        }
+        if policy
+            .roles
+            .values()
+            .all(|p| !p.contains(Scope::UsersManage))
+        {
+            bail!("RBAC_POLICY leaves no role with users:manage; roles could never be changed");
+        }
+        Ok(policy)
+    }
+
+    pub fn role_permissions(&self, role: Role) -> Permissions {
+        self.roles.get(&role).cloned().unwrap_or_default()
+    }
+
+    pub fn permissions(&self, principal: &Principal) -> Permissions {
+        match principal {
+            Principal::Admin => Permissions::all(),
+            Principal::User { role, .. } => self.role_permissions(*role),
+            Principal::ApiKey { scopes, role, .. } => {
+                let granted: Permissions = scopes.iter().copied().collect();
+                match role {
+                    Some(role) => granted.intersect(&self.role_permissions(*role)),
+                    None => granted,
+                }
+            }
+        }
+    }
+}
+
+type AuthorizeFuture = Pin<Box<dyn Future<Output = Response> + Send>>;
+type AuthorizeFn = fn(State<Scope>, Request, Next) -> AuthorizeFuture;
+
+/// Layer returned by [`require_scope`].
+pub type ScopeGuard = FromFnLayer<AuthorizeFn, Scope, (State<Scope>, Request)>;
+
+/// Route guard: `get(handler.layer(require_scope(Scope::TodosRead)))`.
+/// Runs after `authenticate`; anonymous callers get 401, principals without
+/// the scope 403.
+pub fn require_scope(scope: Scope) -> ScopeGuard {
+    from_fn_with_state(scope, authorize as AuthorizeFn)
+}
+
+fn authorize(State(scope): State<Scope>, req: Request, next: Next) -> AuthorizeFuture {
+    Box::pin(async move {
+        let Some(principal) = req.extensions().get::<Principal>() else {
+            tracing::debug!(scope = %scope, "authorization denied: anonymous");
+            return ApiError::Unauthorized("Authentication required".to_owned()).into_response();
+        };
+        let allowed = req
+            .extensions()
+            .get::<Permissions>()
+            .is_some_and(|p| p.contains(scope));
+
+        if !allowed {
+            tracing::info!(principal = %principal, scope = %scope, "authorization denied");
+            return ApiError::Forbidden(format!("Missing required scope {scope}")).into_response();
+        }
+        tracing::debug!(principal = %principal, scope = %scope, "authorization granted");
+        next.run(req).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_matrix_and_overrides() {
+        let policy = RbacPolicy::default();
+        let member = policy.role_permissions(Role::Member);
+        assert!(member.contains(Scope::TodosWrite));
+        assert!(!member.contains(Scope::UsersManage));
+        assert!(
+            !policy
+                .role_permissions(Role::ReadOnly)
+                .contains(Scope::TodosWrite)
+        );
+
+        let custom = RbacPolicy::parse("member = todos:read ; read_only=").unwrap();
+        assert!(
+            !custom
+                .role_permissions(Role::Member)
+                .contains(Scope::TodosWrite)
+        );
+        assert_eq!(
+            custom.role_permissions(Role::ReadOnly),
+            Permissions::default()
+        );
+        assert_eq!(custom.role_permissions(Role::Admin), Permissions::all());
+
+        assert!(RbacPolicy::parse("owner=*").is_err());
+        assert!(RbacPolicy::parse("member=todos:delete").is_err());
+        assert!(RbacPolicy::parse("admin=todos:read").is_err());
+    }
+
+    #[test]
+    fn user_bound_keys_are_narrowed_by_role() {
+        let policy = RbacPolicy::default();
+        let key = Principal::ApiKey {
+            id: 1,
+            user_id: Some(2),
+            role: Some(Role::ReadOnly),
+            scopes: vec![Scope::TodosRead, Scope::TodosWrite],
+        };
+        let granted = policy.permissions(&key);
+        assert!(granted.contains(Scope::TodosRead));
+        assert!(!granted.contains(Scope::TodosWrite));
+    }
+}
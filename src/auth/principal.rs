@@ -0,0 +1,82 @@
+// src/auth/principal.rs
+use crate::auth::{AccessClaims, Role, Scope};
+use crate::error::ApiError;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::fmt;
+
+/// Who is making the request, as resolved by the `authenticate` middleware.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Principal {
+    /// Holder of ADMIN_TOKEN: unrestricted, sees every user's data.
+    Admin,
+    /// A registered user authenticated with an access token; the role is the
+    /// one stamped into the token when it was issued.
+    User { id: i64, role: Role },
+    /// An API key, limited to its scopes and, if bound to a user, that user's
+    /// rows and role.
+    ApiKey {
+        id: i64,
+        user_id: Option<i64>,
+        role: Option<Role>,
+        scopes: Vec<Scope>,
+    },
+}
+
+impl Principal {
+    /// Owner filter for per-user rows: `None` means "all rows".
+    pub fn owner_scope(&self) -> Option<i64> {
+        match self {
+            Principal::Admin => None,
+            Principal::User { id, .. } => Some(*id),
+            Principal::ApiKey { user_id, .. } => *user_id,
+        }
+    }
+}
+
+/// Short label for logs: `admin`, `user:7`, `api_key:3`.
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Principal::Admin => f.write_str("admin"),
+            Principal::User { id, .. } => write!(f, "user:{id}"),
+            Principal::ApiKey { id, .. } => write!(f, "api_key:{id}"),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Principal {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Principal>()
+            .cloned()
+            .ok_or_else(|| ApiError::Unauthorized("Authentication required".to_owned()))
+    }
+}
+
+/// A signed-in user, via access token or session cookie; rejects anonymous
+/// callers (401) and the admin token and API keys (403).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthUser {
+    pub id: i64,
+    /// Verified access token claims; `None` for cookie sessions.
+    pub claims: Option<AccessClaims>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Principal::from_request_parts(parts, state).await? {
+            Principal::User { id, .. } => Ok(AuthUser {
+                id,
+                claims: parts.extensions.get::<AccessClaims>().cloned(),
+            }),
+            Principal::Admin | Principal::ApiKey { .. } => Err(ApiError::Forbidden(
+                "This endpoint requires a user account".to_owned(),
+            )),
+        }
+    }
+}
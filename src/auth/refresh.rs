@@ -0,0 +1,146 @@
+// src/auth/refresh.rs
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Refresh tokens are opaque random strings. Only their SHA-256 is stored, and
+/// each one is single use: redeeming it marks it used and issues its successor
+/// in the same family. Presenting a used token again means it leaked, so the
+/// whole family is revoked.
+#[derive(Debug, Error)]
+pub enum RefreshError {
+    #[error("refresh token is unknown, expired or revoked")]
+    Invalid,
+    #[error("refresh token was already used; its family has been revoked")]
+    Reused,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct RotatedRefresh {
+    pub user_id: i64,
+    pub refresh_token: String,
+}
+
+/// Starts a new token family, e.g. on password login.
+pub async fn issue_refresh_token(
+    pool: &PgPool,
+    user_id: i64,
+    ttl: Duration,
+) -> Result<String, sqlx::Error> {
+    let token = generate();
+    sqlx::query(
+        r#"
+        INSERT INTO refresh_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, $2, now() + make_interval(secs => $3))
+        "#,
+    )
+    .bind(user_id)
+    .bind(digest(&token))
+    .bind(ttl.as_secs_f64())
+    .execute(pool)
+    .await?;
+    Ok(token)
+}
+
+/// Redeems `presented` for its successor.
+pub async fn rotate_refresh_token(
+    pool: &PgPool,
+    presented: &str,
+    ttl: Duration,
+) -> Result<RotatedRefresh, RefreshError> {
+    let mut tx = pool.begin().await?;
+
+    // Row lock so two concurrent redemptions can't both succeed
+    let row: Option<(i64, i64, bool, bool)> = sqlx::query_as(
+        r#"
+        SELECT id, user_id,
+               used_at IS NOT NULL OR revoked_at IS NOT NULL AS spent,
+               expires_at <= now() AS expired
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(digest(presented))
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((id, user_id, spent, expired)) = row else {
+        return Err(RefreshError::Invalid);
+    };
+    if spent {
+        revoke_family_of(&mut tx, id).await?;
+        tx.commit().await?;
+        tracing::warn!(user_id, token_id = id, "refresh token reuse detected");
+        return Err(RefreshError::Reused);
+    }
+    if expired {
+        return Err(RefreshError::Invalid);
+    }
+
+    sqlx::query("UPDATE refresh_tokens SET used_at = now() WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    let refresh_token = generate();
+    sqlx::query(
+        r#"
+        INSERT INTO refresh_tokens (user_id, family_id, parent_id, token_hash, expires_at)
+        SELECT user_id, family_id, id, $2, now() + make_interval(secs => $3)
+        FROM refresh_tokens WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(digest(&refresh_token))
+    .bind(ttl.as_secs_f64())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(RotatedRefresh {
+        user_id,
+        refresh_token,
+    })
+}
+
+/// Revokes every token in the family of `presented`. Unknown tokens are ignored.
+pub async fn revoke_refresh_token(pool: &PgPool, presented: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE refresh_tokens SET revoked_at = COALESCE(revoked_at, now())
+        WHERE family_id = (SELECT family_id FROM refresh_tokens WHERE token_hash = $1)
+        "#,
+    )
+    .bind(digest(presented))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn revoke_family_of(tx: &mut sqlx::PgConnection, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE refresh_tokens SET revoked_at = COALESCE(revoked_at, now())
+        WHERE family_id = (SELECT family_id FROM refresh_tokens WHERE id = $1)
+        "#,
+    )
+    .bind(id)
+    .execute(tx)
+    .await?;
+    Ok(())
+}
+
+fn generate() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn digest(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
@@ -0,0 +1,328 @@
+// src/auth/session.rs
+use crate::auth::Role;
+use crate::config::SessionConfig;
+use crate::error::ApiError;
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, header, request::Parts},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::convert::Infallible;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+/// Cookie-authenticated requests with unsafe methods must echo the session's
+/// CSRF token in this header.
+pub const CSRF_HEADER: HeaderName = HeaderName::from_static("x-csrf-token");
+
+/// A live cookie session, stored in request extensions by `authenticate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    pub id: i64,
+    pub user_id: i64,
+    pub role: Role,
+    pub csrf_token: String,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Session {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Session>()
+            .cloned()
+            .ok_or_else(|| ApiError::Unauthorized("A session cookie is required".to_owned()))
+    }
+}
+
+impl<S: Send + Sync> OptionalFromRequestParts<S> for Session {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<Session>().cloned())
+    }
+}
+
+/// A session just created by a login.
+pub struct NewSession {
+    pub session: Session,
+    pub expires_at: DateTime<Utc>,
+    /// `Set-Cookie` value carrying the signed session token.
+    pub set_cookie: HeaderValue,
+}
+
+/// Creates, resolves and revokes server-side sessions. The cookie holds a
+/// random token plus an HMAC over it: forged cookies are rejected without a
+/// database round trip, and the table only stores a digest of the token.
+#[derive(Clone)]
+pub struct SessionManager {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    cfg: SessionConfig,
+    key: Vec<u8>,
+}
+
+impl SessionManager {
+    pub fn new(cfg: &SessionConfig) -> Self {
+        let key = match &cfg.secret {
+            Some(secret) => secret.expose().as_bytes().to_vec(),
+            None => {
+                tracing::warn!(
+                    "no SESSION_SECRET configured; using a random key, so session cookies die \
+                     with this process and are not accepted by other instances"
+                );
+                random_bytes().to_vec()
+            }
+        };
+        Self {
+            inner: Arc::new(Inner {
+                cfg: cfg.clone(),
+                key,
+            }),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        user_id: i64,
+        role: Role,
+        user_agent: Option<&str>,
+    ) -> Result<NewSession, sqlx::Error> {
+        let token = URL_SAFE_NO_PAD.encode(random_bytes());
+        let csrf_token = URL_SAFE_NO_PAD.encode(random_bytes());
+        let cfg = &self.inner.cfg;
+
+        sqlx::query("DELETE FROM sessions WHERE expires_at < now()")
+            .execute(pool)
+            .await?;
+        let (id, expires_at): (i64, DateTime<Utc>) = sqlx::query_as(
+            r#"
+            INSERT INTO sessions (user_id, token_hash, csrf_token, user_agent, expires_at)
+            VALUES ($1, $2, $3, $4, now() + make_interval(secs => $5))
+            RETURNING id, expires_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(digest(&token))
+        .bind(&csrf_token)
+        .bind(user_agent)
+        .bind(cfg.absolute_timeout.as_secs_f64())
+        .fetch_one(pool)
+        .await?;
+
+        let value = format!("{token}.{}", self.sign(&token));
+        Ok(NewSession {
+            session: Session {
+                id,
+                user_id,
+                role,
+                csrf_token,
+            },
+            expires_at,
+            set_cookie: self.cookie(&value, cfg.absolute_timeout.as_secs()),
+        })
+    }
+
+    /// The session behind the request's cookie, if it is authentic, unrevoked
+    /// and within both timeouts. Use is recorded at most every half idle
+    /// period (capped at a minute), which keeps reads from all becoming writes.
+    pub async fn resolve(
+        &self,
+        pool: &PgPool,
+        headers: &HeaderMap,
+    ) -> Result<Option<Session>, sqlx::Error> {
+        let Some(token) = self.cookie_token(headers) else {
+            return Ok(None);
+        };
+
+        let row: Option<(i64, i64, Role, String)> = sqlx::query_as(
+            r#"
+            WITH s AS (
+                SELECT s.id, s.user_id, u.role, s.csrf_token, s.last_seen_at
+                FROM sessions s JOIN users u ON u.id = s.user_id
+                WHERE s.token_hash = $1
+                  AND s.revoked_at IS NULL
+                  AND s.expires_at > now()
+                  AND s.last_seen_at > now() - make_interval(secs => $2)
+            ), touch AS (
+                UPDATE sessions SET last_seen_at = now()
+                FROM s
+                WHERE sessions.id = s.id
+                  AND s.last_seen_at < now() - make_interval(secs => LEAST(60, $2 / 2))
+            )
+            SELECT id, user_id, role, csrf_token FROM s
+            "#,
+        )
+        .bind(digest(token))
+        .bind(self.inner.cfg.idle_timeout.as_secs_f64())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|(id, user_id, role, csrf_token)| Session {
+            id,
+            user_id,
+            role,
+            csrf_token,
+        }))
+    }
+
+    /// Revokes one of `user_id`'s sessions; `false` if there is no such live session.
+    pub async fn revoke(&self, pool: &PgPool, id: i64, user_id: i64) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query(
+            "UPDATE sessions SET revoked_at = now() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// `Set-Cookie` value that makes the browser drop the cookie.
+    pub fn clear_cookie(&self) -> HeaderValue {
+        self.cookie("", 0)
+    }
+
+    fn cookie(&self, value: &str, max_age: u64) -> HeaderValue {
+        let cfg = &self.inner.cfg;
+        let mut cookie = format!(
+            "{}={value}; Path=/; Max-Age={max_age}; HttpOnly; SameSite={}",
+            cfg.cookie_name, cfg.same_site
+        );
+        if cfg.secure {
+            cookie.push_str("; Secure");
+        }
+        HeaderValue::from_str(&cookie).expect("cookie name and value are header-safe")
+    }
+
+    /// The token from a correctly signed session cookie.
+    fn cookie_token<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        let value = headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == self.inner.cfg.cookie_name)
+            .map(|(_, value)| value)?;
+
+        let (token, signature) = value.rsplit_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        self.mac(token).verify_slice(&signature).ok()?;
+        Some(token)
+    }
+
+    fn sign(&self, token: &str) -> String {
+        URL_SAFE_NO_PAD.encode(self.mac(token).finalize().into_bytes())
+    }
+
+    fn mac(&self, token: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.inner.key).expect("HMAC accepts any key length");
+        mac.update(token.as_bytes());
+        mac
+    }
+}
+
+/// Synchronizer-token check for cookie-authenticated requests: unsafe
+/// methods must carry the session's token in [`CSRF_HEADER`]. A cross-site
+/// form or fetch can make the browser send the cookie, but cannot read the
+/// token to put it in a header.
+pub fn check_csrf(method: &Method, headers: &HeaderMap, session: &Session) -> Result<(), ApiError> {
+    if matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    ) {
+        return Ok(());
+    }
+    let presented = headers
+        .get(&CSRF_HEADER)
+        .map(HeaderValue::as_bytes)
+        .unwrap_or_default();
+    if bool::from(presented.ct_eq(session.csrf_token.as_bytes())) {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden(
+            "Missing or invalid CSRF token".to_owned(),
+        ))
+    }
+}
+
+fn random_bytes() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn digest(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Secret;
+
+    fn manager() -> SessionManager {
+        SessionManager::new(&SessionConfig {
+            secret: Some(Secret::new("test-secret")),
+            ..Default::default()
+        })
+    }
+
+    fn headers(cookie: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_str(cookie).unwrap());
+        headers
+    }
+
+    #[test]
+    fn only_signed_cookies_yield_a_token() {
+        let sessions = manager();
+        let signed = format!("abc.{}", sessions.sign("abc"));
+        assert_eq!(
+            sessions.cookie_token(&headers(&format!("theme=dark; session={signed}"))),
+            Some("abc")
+        );
+        assert_eq!(sessions.cookie_token(&headers("session=abc.forged")), None);
+        assert_eq!(
+            sessions.cookie_token(&headers(&format!("other={signed}"))),
+            None
+        );
+
+        let cookie = sessions.cookie("v", 60);
+        assert_eq!(
+            cookie,
+            "session=v; Path=/; Max-Age=60; HttpOnly; SameSite=Lax; Secure"
+        );
+    }
+
+    #[test]
+    fn unsafe_methods_need_the_csrf_token() {
+        let session = Session {
+            id: 1,
+            user_id: 1,
+            role: Role::Member,
+            csrf_token: "t0ken".into(),
+        };
+        let mut headers = HeaderMap::new();
+        assert!(check_csrf(&Method::GET, &headers, &session).is_ok());
+        assert!(check_csrf(&Method::POST, &headers, &session).is_err());
+        headers.insert(CSRF_HEADER, HeaderValue::from_static("wrong"));
+        assert!(check_csrf(&Method::DELETE, &headers, &session).is_err());
+        headers.insert(CSRF_HEADER, HeaderValue::from_static("t0ken"));
+        assert!(check_csrf(&Method::PATCH, &headers, &session).is_ok());
+    }
+}
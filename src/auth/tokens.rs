@@ -0,0 +1,226 @@
+// src/auth/tokens.rs
+use crate::auth::{JwtKey, Role};
+use crate::config::{AuthConfig, Secret};
+use anyhow::Context;
+use jsonwebtoken::{
+    Header, Validation,
+    errors::{Error as JwtError, ErrorKind},
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Claims carried by user access tokens.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessClaims {
+    /// User id, as a string per RFC 7519.
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+    /// Unique per token, for log correlation.
+    pub jti: String,
+    /// Role at issue time; role changes apply from the next token on.
+    #[serde(default)]
+    pub role: Role,
+}
+
+impl AccessClaims {
+    pub fn user_id(&self) -> Option<i64> {
+        self.sub.parse().ok()
+    }
+}
+
+/// Signs access tokens with the active key and verifies them against every
+/// configured key, selected by the `kid` header.
+#[derive(Clone)]
+pub struct TokenService {
+    inner: Arc<TokenServiceInner>,
+}
+
+struct TokenServiceInner {
+    signing: JwtKey,
+    keys: HashMap<String, JwtKey>,
+    leeway: Duration,
+    ttl: Duration,
+}
+
+impl TokenService {
+    pub fn new(cfg: &AuthConfig) -> Self {
+        let signing = cfg.signing_key.clone().unwrap_or_else(|| {
+            tracing::warn!(
+                "no JWT signing key configured; using a random key, so tokens die with this \
+                 process and are not accepted by other instances"
+            );
+            let mut key = [0u8; 32];
+            rand::rngs::OsRng.fill_bytes(&mut key);
+            let secret = Secret::new(base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                key,
+            ));
+            JwtKey::hs256("ephemeral", &secret).expect("static kid is valid")
+        });
+
+        let keys = cfg
+            .verification_keys
+            .iter()
+            .chain(std::iter::once(&signing))
+            .map(|k| (k.kid().to_owned(), k.clone()))
+            .collect();
+
+        Self {
+            inner: Arc::new(TokenServiceInner {
+                signing,
+                keys,
+                leeway: cfg.leeway,
+                ttl: cfg.access_token_ttl,
+            }),
+        }
+    }
+
+    /// Lifetime of newly issued access tokens.
+    pub fn ttl(&self) -> Duration {
+        self.inner.ttl
+    }
+
+    pub fn issue(&self, user_id: i64, role: Role) -> anyhow::Result<String> {
+        let now = unix_now();
+        self.sign(&AccessClaims {
+            sub: user_id.to_string(),
+            iat: now,
+            exp: now + self.inner.ttl.as_secs(),
+            jti: uuid::Uuid::new_v4().to_string(),
+            role,
+        })
+    }
+
+    fn sign(&self, claims: &AccessClaims) -> anyhow::Result<String> {
+        let key = &self.inner.signing;
+        let encoding = key
+            .encoding()
+            .with_context(|| format!("JWT signing key {} has no private key", key.kid()))?;
+        let mut header = Header::new(key.algorithm());
+        header.kid = Some(key.kid().to_owned());
+        Ok(jsonwebtoken::encode(&header, claims, encoding)?)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<AccessClaims, JwtError> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let key = header
+            .kid
+            .as_deref()
+            .and_then(|kid| self.inner.keys.get(kid))
+            .ok_or_else(|| JwtError::from(ErrorKind::InvalidKeyFormat))?;
+        // The key pins the algorithm; a token can't pick a weaker one via its header
+        if header.alg != key.algorithm() {
+            return Err(ErrorKind::InvalidAlgorithm.into());
+        }
+
+        let mut validation = Validation::new(key.algorithm());
+        validation.leeway = self.inner.leeway.as_secs();
+        validation.set_required_spec_claims(&["exp", "sub"]);
+
+        jsonwebtoken::decode::<AccessClaims>(token, key.decoding(), &validation)
+            .map(|data| data.claims)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::keys::tests::{ED25519_PRIVATE, ED25519_PUBLIC};
+
+    fn hs(kid: &str, secret: &str) -> JwtKey {
+        JwtKey::hs256(kid, &Secret::new(secret)).unwrap()
+    }
+
+    fn service(signing: JwtKey, retired: Vec<JwtKey>) -> TokenService {
+        TokenService::new(&AuthConfig {
+            signing_key: Some(signing),
+            verification_keys: retired,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn issued_tokens_verify_with_the_same_key_only() {
+        let token = service(hs("k1", "one"), vec![])
+            .issue(42, Role::Member)
+            .unwrap();
+
+        let claims = service(hs("k1", "one"), vec![]).verify(&token).unwrap();
+        assert_eq!(claims.user_id(), Some(42));
+        assert!(service(hs("k1", "two"), vec![]).verify(&token).is_err());
+    }
+
+    #[test]
+    fn rotated_keys_keep_verifying_by_kid() {
+        let old = service(hs("k1", "one"), vec![])
+            .issue(7, Role::Member)
+            .unwrap();
+
+        let rotated = service(hs("k2", "two"), vec![hs("k1", "one")]);
+        assert_eq!(rotated.verify(&old).unwrap().user_id(), Some(7));
+        let new = rotated.issue(7, Role::Member).unwrap();
+        assert_eq!(
+            jsonwebtoken::decode_header(&new).unwrap().kid.as_deref(),
+            Some("k2")
+        );
+
+        // Once k1 is dropped from the config its tokens stop working
+        assert!(service(hs("k2", "two"), vec![]).verify(&old).is_err());
+    }
+
+    #[test]
+    fn eddsa_tokens_round_trip() {
+        let signing =
+            JwtKey::ed25519("ed", Some(&Secret::new(ED25519_PRIVATE)), ED25519_PUBLIC).unwrap();
+        let token = service(signing, vec![]).issue(3, Role::Member).unwrap();
+        assert_eq!(
+            jsonwebtoken::decode_header(&token).unwrap().alg,
+            jsonwebtoken::Algorithm::EdDSA
+        );
+
+        // A verifier holding only the public key accepts it
+        let verifier = service(
+            hs("other", "x"),
+            vec![JwtKey::ed25519("ed", None, ED25519_PUBLIC).unwrap()],
+        );
+        assert_eq!(verifier.verify(&token).unwrap().user_id(), Some(3));
+    }
+
+    #[test]
+    fn leeway_tolerates_small_clock_skew() {
+        let now = unix_now();
+        let expired = AccessClaims {
+            sub: "1".into(),
+            iat: now - 120,
+            exp: now - 10,
+            jti: "j".into(),
+            role: Role::Member,
+        };
+
+        let lenient = TokenService::new(&AuthConfig {
+            signing_key: Some(hs("k1", "one")),
+            leeway: Duration::from_secs(30),
+            ..Default::default()
+        });
+        let token = lenient.sign(&expired).unwrap();
+        assert!(lenient.verify(&token).is_ok());
+
+        let strict = TokenService::new(&AuthConfig {
+            signing_key: Some(hs("k1", "one")),
+            leeway: Duration::ZERO,
+            ..Default::default()
+        });
+        assert!(strict.verify(&token).is_err());
+    }
+}
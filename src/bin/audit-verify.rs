@@ -0,0 +1,38 @@
+// src/bin/audit-verify.rs
+// Checks the audit_events hash chain: `DATABASE_URL=... cargo run --bin audit-verify`
+// Exits 1 when a link is broken, so it can run from cron or CI.
+use anyhow::Context;
+use axum_server_shuttle::audit::AuditLog;
+use sqlx::postgres::PgPoolOptions;
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<ExitCode> {
+    let url = std::env::var("DATABASE_URL").context("DATABASE_URL must be set")?;
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&url)
+        .await
+        .context("failed to connect to DATABASE_URL")?;
+
+    let report = AuditLog::new(pool)
+        .verify()
+        .await
+        .context("failed to read audit_events")?;
+
+    for link in &report.broken {
+        println!("event {}: {}", link.id, link.error);
+    }
+    println!(
+        "{} events checked, {} broken links, head {}",
+        report.events,
+        report.broken.len(),
+        report.head.as_deref().unwrap_or("(empty)")
+    );
+
+    Ok(if report.is_intact() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -0,0 +1,73 @@
+// src/bin/standalone.rs
+// Runs the same Server outside Shuttle: `DATABASE_URL=... cargo run --bin standalone`
+use anyhow::Context;
+use axum_server_shuttle::{
+    config::{AppState, ConfigSource, IpFilterConfig, MetricsEndpoint, StandaloneConfig},
+    models::Server,
+    runtime::{
+        MIGRATOR, init_tracing, reload_on_hangup, serve_with_graceful_shutdown, shutdown_signal,
+        spawn_trash_purge,
+    },
+};
+
+use sqlx::postgres::PgPoolOptions;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    init_tracing();
+
+    let src = ConfigSource::load()?;
+    let cfg = StandaloneConfig::load(&src)?;
+
+    let pool = PgPoolOptions::new()
+        .max_connections(cfg.db_max_connections)
+        .connect(&cfg.database_url)
+        .await
+        .context("failed to connect to DATABASE_URL")?;
+
+    // Run database migrations at startup
+    MIGRATOR
+        .run(&pool)
+        .await
+        .context("failed to run migrations")?;
+
+    spawn_trash_purge(pool.clone(), &cfg.server.trash);
+    let state = AppState::new(pool, cfg.server.clone());
+
+    // SIGHUP re-reads CONFIG_FILE (and the environment) for the IP lists
+    let ip_filter = state.ip_filter.clone();
+    reload_on_hangup(move || {
+        match ConfigSource::load().and_then(|src| IpFilterConfig::load_from(|key| src.get(key))) {
+            Ok(rules) => ip_filter.reload(rules),
+            Err(e) => {
+                tracing::error!(error = %e, "invalid ip filter config; keeping current rules")
+            }
+        }
+    });
+
+    let server = Server::new(state);
+    let app = server.router();
+
+    if let MetricsEndpoint::Separate(addr) = cfg.server.metrics {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("failed to bind metrics listener {}", addr))?;
+        tracing::info!(%addr, "serving /metrics");
+        let metrics_app = server.metrics_router();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, metrics_app).await {
+                tracing::error!(error = %e, "metrics listener failed");
+            }
+        });
+    }
+
+    let listener = TcpListener::bind(cfg.bind)
+        .await
+        .with_context(|| format!("failed to bind {}", cfg.bind))?;
+    tracing::info!(addr = %cfg.bind, "listening");
+
+    serve_with_graceful_shutdown(listener, app, shutdown_signal(), cfg.shutdown_grace).await?;
+    tracing::info!("server stopped");
+    Ok(())
+}
@@ -1,5 +1,10 @@
 // src/app_state.rs
+use crate::audit::AuditLog;
+use crate::auth::{Authenticator, oidc::OidcProvider};
 use crate::config::ServerConfig;
+use crate::idempotency::IdempotencyStore;
+use crate::middleware::{IpFilter, Metrics};
+use crate::ratelimit::RateLimiter;
 use sqlx::PgPool;
 use std::time::Instant;
 
@@ -7,16 +12,38 @@ use std::time::Instant;
 pub struct AppState {
     pub pool: PgPool,
     pub cfg: ServerConfig,
-    #[allow(dead_code)]
     pub started_at: Instant,
+    pub metrics: Metrics,
+    pub auth: Authenticator,
+    pub oidc: Option<OidcProvider>,
+    pub audit: AuditLog,
+    pub rate_limiter: Option<RateLimiter>,
+    pub ip_filter: IpFilter,
+    pub idempotency: Option<IdempotencyStore>,
 }
 
 impl AppState {
     pub fn new(pool: PgPool, cfg: ServerConfig) -> Self {
+        let auth = Authenticator::new(&cfg.auth, pool.clone());
+        let oidc = cfg
+            .oidc
+            .as_ref()
+            .map(|oidc| OidcProvider::new(oidc, cfg.auth.leeway));
+        let audit = AuditLog::new(pool.clone());
+        let rate_limiter = RateLimiter::from_config(&cfg.rate_limit, &pool);
+        let ip_filter = IpFilter::new(cfg.ip_filter.clone());
+        let idempotency = IdempotencyStore::from_config(&cfg.idempotency, &pool, cfg.timeout);
         Self {
             pool,
             cfg,
             started_at: Instant::now(),
+            metrics: Metrics::new(),
+            auth,
+            oidc,
+            audit,
+            rate_limiter,
+            ip_filter,
+            idempotency,
         }
     }
 }
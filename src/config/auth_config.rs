@@ -0,0 +1,222 @@
+// src/config/auth_config.rs
+use crate::auth::{JwtKey, RbacPolicy};
+use crate::config::{Secret, SessionConfig};
+use anyhow::{Context, Result, bail};
+use std::time::Duration;
+
+/// Reachable without a token even under ADMIN_TOKEN lockdown, so load
+/// balancer probes keep working. `/metrics` is left out: scrapers send the
+/// token or use METRICS_ADDR.
+pub const DEFAULT_PUBLIC_ROUTES: &[&str] = &["/health", "/health/live", "/health/ready"];
+
+#[derive(Clone, Debug)]
+pub struct AuthConfig {
+    pub admin_tokens: Vec<Secret>, // static full-access bearers; any configured locks the API down
+    pub public_routes: Vec<String>, // paths exempt from lockdown; `/prefix/*` matches a subtree
+    pub signing_key: Option<JwtKey>, // signs new access tokens; random HS256 when unset
+    pub verification_keys: Vec<JwtKey>, // retired keys still accepted, looked up by kid
+    pub leeway: Duration,          // clock skew tolerated when checking exp
+    pub access_token_ttl: Duration, // lifetime of issued access tokens
+    pub refresh_token_ttl: Duration, // lifetime of each refresh token in a rotation chain
+    pub policy: RbacPolicy,        // role -> scope matrix
+    pub session: SessionConfig,    // browser cookie sessions
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            admin_tokens: Vec::new(),
+            public_routes: DEFAULT_PUBLIC_ROUTES
+                .iter()
+                .map(|&p| p.to_owned())
+                .collect(),
+            signing_key: None,
+            verification_keys: Vec::new(),
+            leeway: Duration::from_secs(30),
+            access_token_ttl: Duration::from_secs(15 * 60),
+            refresh_token_ttl: Duration::from_secs(30 * 24 * 60 * 60),
+            policy: RbacPolicy::default(),
+            session: SessionConfig::default(),
+        }
+    }
+}
+
+impl AuthConfig {
+    /// - ADMIN_TOKEN             (optional)
+    /// - ADMIN_TOKEN_NEXT        (optional) also accepted, for zero-downtime rotation
+    /// - PUBLIC_ROUTES           (comma-separated paths, `/prefix/*` allowed;
+    ///   default: /health, /health/live, /health/ready)
+    /// - JWT_ALGORITHM           (HS256 | EdDSA, default: HS256)
+    /// - JWT_KID                 (default: default) key id stamped on new tokens
+    /// - JWT_SECRET              (HS256; random per process when unset)
+    /// - JWT_PRIVATE_KEY         (EdDSA, PKCS#8 PEM; `\n` escapes allowed)
+    /// - JWT_PUBLIC_KEY          (EdDSA, SPKI PEM)
+    /// - JWT_PREVIOUS_KIDS       (comma-separated retired kids, each backed by
+    ///   JWT_SECRET_<kid> or JWT_PUBLIC_KEY_<kid>)
+    /// - JWT_LEEWAY_SECS         (default: 30)
+    /// - ACCESS_TOKEN_TTL_SECS   (default: 900)
+    /// - REFRESH_TOKEN_TTL_SECS  (default: 2592000, 30 days)
+    /// - RBAC_POLICY             (e.g. `member=todos:read;read_only=`; overrides
+    ///   the listed roles, see [`RbacPolicy::parse`])
+    /// - plus the session keys read by [`SessionConfig::load_from`]
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let mut cfg = AuthConfig {
+            admin_tokens: ["ADMIN_TOKEN", "ADMIN_TOKEN_NEXT"]
+                .into_iter()
+                .filter_map(|key| var(key).map(Secret::from))
+                .collect(),
+            ..Default::default()
+        };
+        if let Some(routes) = var("PUBLIC_ROUTES") {
+            cfg.public_routes = routes
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+                .collect();
+            if let Some(bad) = cfg.public_routes.iter().find(|p| !p.starts_with('/')) {
+                bail!("PUBLIC_ROUTES entries must start with '/', got {bad}");
+            }
+        }
+
+        let kid = var("JWT_KID").unwrap_or_else(|| "default".to_owned());
+        cfg.signing_key = match var("JWT_ALGORITHM").as_deref() {
+            None | Some("HS256") => var("JWT_SECRET")
+                .map(|s| JwtKey::hs256(kid, &Secret::from(s)))
+                .transpose()?,
+            Some("EdDSA") => {
+                let private = var("JWT_PRIVATE_KEY")
+                    .context("JWT_PRIVATE_KEY must be set when JWT_ALGORITHM=EdDSA")?;
+                let public = var("JWT_PUBLIC_KEY")
+                    .context("JWT_PUBLIC_KEY must be set when JWT_ALGORITHM=EdDSA")?;
+                Some(JwtKey::ed25519(
+                    kid,
+                    Some(&Secret::from(unescape_pem(&private))),
+                    &unescape_pem(&public),
+                )?)
+            }
+            Some(other) => bail!("JWT_ALGORITHM must be HS256 or EdDSA, got {other}"),
+        };
+
+        for kid in var("JWT_PREVIOUS_KIDS")
+            .iter()
+            .flat_map(|s| s.split(','))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            let key = if let Some(secret) = var(&format!("JWT_SECRET_{kid}")) {
+                JwtKey::hs256(kid, &Secret::from(secret))?
+            } else if let Some(public) = var(&format!("JWT_PUBLIC_KEY_{kid}")) {
+                JwtKey::ed25519(kid, None, &unescape_pem(&public))?
+            } else {
+                bail!(
+                    "JWT_PREVIOUS_KIDS lists {kid} but neither JWT_SECRET_{kid} nor JWT_PUBLIC_KEY_{kid} is set"
+                );
+            };
+            cfg.verification_keys.push(key);
+        }
+
+        if let Some(secs) = var("JWT_LEEWAY_SECS") {
+            let s: u64 = secs.parse().context("JWT_LEEWAY_SECS must be u64")?;
+            cfg.leeway = Duration::from_secs(s);
+        }
+        if let Some(secs) = var("ACCESS_TOKEN_TTL_SECS") {
+            let s: u64 = secs.parse().context("ACCESS_TOKEN_TTL_SECS must be u64")?;
+            cfg.access_token_ttl = Duration::from_secs(s);
+        }
+        if let Some(secs) = var("REFRESH_TOKEN_TTL_SECS") {
+            let s: u64 = secs.parse().context("REFRESH_TOKEN_TTL_SECS must be u64")?;
+            cfg.refresh_token_ttl = Duration::from_secs(s);
+        }
+        if let Some(spec) = var("RBAC_POLICY") {
+            cfg.policy = RbacPolicy::parse(&spec)?;
+        }
+        cfg.session = SessionConfig::load_from(var)?;
+
+        Ok(cfg)
+    }
+}
+
+/// PEM is multi-line; single-line sources (`.env` files, some secret stores)
+/// can spell the newlines as `\n`.
+fn unescape_pem(s: &str) -> String {
+    s.replace("\\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::keys::tests::{ED25519_PRIVATE, ED25519_PUBLIC};
+    use jsonwebtoken::Algorithm;
+    use std::collections::HashMap;
+
+    fn load(vars: &[(&str, &str)]) -> Result<AuthConfig> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        AuthConfig::load_from(|k| vars.get(k).cloned())
+    }
+
+    #[test]
+    fn loads_eddsa_signing_key_and_retired_kids() {
+        let cfg = load(&[
+            ("JWT_ALGORITHM", "EdDSA"),
+            ("JWT_KID", "2025_06"),
+            ("JWT_PRIVATE_KEY", &ED25519_PRIVATE.replace('\n', "\\n")),
+            ("JWT_PUBLIC_KEY", ED25519_PUBLIC),
+            ("JWT_PREVIOUS_KIDS", "2025_01, legacy"),
+            ("JWT_PUBLIC_KEY_2025_01", ED25519_PUBLIC),
+            ("JWT_SECRET_legacy", "old-hmac-secret"),
+            ("JWT_LEEWAY_SECS", "5"),
+        ])
+        .unwrap();
+
+        let signing = cfg.signing_key.unwrap();
+        assert_eq!(signing.kid(), "2025_06");
+        assert_eq!(signing.algorithm(), Algorithm::EdDSA);
+        assert!(signing.can_sign());
+
+        let retired: Vec<_> = cfg
+            .verification_keys
+            .iter()
+            .map(|k| (k.kid(), k.algorithm(), k.can_sign()))
+            .collect();
+        assert_eq!(
+            retired,
+            [
+                ("2025_01", Algorithm::EdDSA, false),
+                ("legacy", Algorithm::HS256, true)
+            ]
+        );
+        assert_eq!(cfg.leeway, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn rejects_incomplete_key_config() {
+        assert!(load(&[("JWT_ALGORITHM", "EdDSA")]).is_err());
+        assert!(load(&[("JWT_ALGORITHM", "RS256")]).is_err());
+        assert!(load(&[("JWT_PREVIOUS_KIDS", "gone")]).is_err());
+        assert!(load(&[]).unwrap().signing_key.is_none());
+        assert!(load(&[("RBAC_POLICY", "member=todos:delete")]).is_err());
+        assert!(load(&[("PUBLIC_ROUTES", "health")]).is_err());
+    }
+
+    #[test]
+    fn loads_rotation_tokens_and_public_routes() {
+        let cfg = load(&[
+            ("ADMIN_TOKEN", "current"),
+            ("ADMIN_TOKEN_NEXT", "next"),
+            ("PUBLIC_ROUTES", "/health, /docs/*"),
+        ])
+        .unwrap();
+        let tokens: Vec<_> = cfg.admin_tokens.iter().map(Secret::expose).collect();
+        assert_eq!(tokens, ["current", "next"]);
+        assert_eq!(cfg.public_routes, ["/health", "/docs/*"]);
+
+        let defaults = load(&[]).unwrap();
+        assert!(defaults.admin_tokens.is_empty());
+        assert!(!defaults.public_routes.iter().any(|p| p == "/metrics"));
+    }
+}
@@ -0,0 +1,35 @@
+// src/config/idempotency_config.rs
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdempotencyConfig {
+    pub enabled: bool, // off -> Idempotency-Key headers are ignored
+    pub ttl: Duration, // how long a stored response can be replayed
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl IdempotencyConfig {
+    /// - IDEMPOTENCY_DISABLED: any value -> Idempotency-Key is not honoured
+    /// - IDEMPOTENCY_TTL_SECS  (default: 86400, 24 hours)
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let mut cfg = IdempotencyConfig {
+            enabled: var("IDEMPOTENCY_DISABLED").is_none(),
+            ..Default::default()
+        };
+        if let Some(secs) = var("IDEMPOTENCY_TTL_SECS") {
+            let secs: u64 = secs.parse().context("IDEMPOTENCY_TTL_SECS must be u64")?;
+            cfg.ttl = Duration::from_secs(secs);
+        }
+        Ok(cfg)
+    }
+}
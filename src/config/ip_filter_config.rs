@@ -0,0 +1,96 @@
+// src/config/ip_filter_config.rs
+use crate::config::RouteGroup;
+use anyhow::{Context, Result};
+use ipnet::IpNet;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// A CIDR, or a single address meaning just that host.
+pub(crate) fn parse_ip_net(s: &str) -> Result<IpNet> {
+    s.parse::<IpNet>()
+        .or_else(|_| s.parse::<IpAddr>().map(IpNet::from))
+        .with_context(|| format!("invalid network {s:?}"))
+}
+
+/// Networks one route group accepts requests from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IpRules {
+    pub allow: Vec<IpNet>, // empty -> any address not denied
+    pub deny: Vec<IpNet>,  // wins over allow, for carving holes into it
+}
+
+impl IpRules {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IpFilterConfig {
+    pub rules: HashMap<RouteGroup, IpRules>, // groups without an entry are open to all
+}
+
+impl IpFilterConfig {
+    /// Both lists use `group=cidr,cidr;group=...` with groups auth, admin,
+    /// ops and api; bare addresses stand for a single host.
+    /// - IP_ALLOWLIST: only these networks may reach the group (default: any)
+    /// - IP_DENYLIST:  these networks may not, even if allowlisted (default: none)
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let mut cfg = IpFilterConfig::default();
+
+        for (key, deny) in [("IP_ALLOWLIST", false), ("IP_DENYLIST", true)] {
+            let Some(spec) = var(key) else { continue };
+            for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+                let (group, nets) = entry
+                    .split_once('=')
+                    .with_context(|| format!("{key} entry {entry:?} must be group=cidr,..."))?;
+                let group: RouteGroup = group.trim().parse().map_err(anyhow::Error::msg)?;
+                let rules = cfg.rules.entry(group).or_default();
+                let list = if deny {
+                    &mut rules.deny
+                } else {
+                    &mut rules.allow
+                };
+                for net in nets.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    list.push(parse_ip_net(net).with_context(|| format!("in {key} for {group}"))?);
+                }
+            }
+        }
+        cfg.rules.retain(|_, rules| !rules.is_empty());
+
+        Ok(cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_per_group_lists() {
+        let cfg = IpFilterConfig::load_from(|key| match key {
+            "IP_ALLOWLIST" => Some("admin=10.0.0.0/8, 192.0.2.7;ops=fd00::/8".to_owned()),
+            "IP_DENYLIST" => Some("admin=10.9.0.0/16".to_owned()),
+            _ => None,
+        })
+        .unwrap();
+
+        let admin = &cfg.rules[&RouteGroup::Admin];
+        assert_eq!(
+            admin.allow,
+            vec![
+                "10.0.0.0/8".parse::<IpNet>().unwrap(),
+                "192.0.2.7/32".parse().unwrap()
+            ]
+        );
+        assert_eq!(admin.deny, vec!["10.9.0.0/16".parse::<IpNet>().unwrap()]);
+        assert_eq!(cfg.rules[&RouteGroup::Ops].allow.len(), 1);
+        assert!(!cfg.rules.contains_key(&RouteGroup::Api));
+
+        let err = IpFilterConfig::load_from(|key| {
+            (key == "IP_DENYLIST").then(|| "admin=not-a-network".to_owned())
+        });
+        assert!(err.is_err());
+    }
+}
@@ -1,4 +1,25 @@
 mod app_state;
+mod auth_config;
+mod idempotency_config;
+mod ip_filter_config;
+mod oidc_config;
+mod rate_limit_config;
+mod route_group;
+mod secret;
 mod server_config;
+mod session_config;
+mod standalone_config;
+mod trash_config;
 pub use app_state::AppState;
-pub use server_config::{CorsPolicy, ServerConfig};
+pub use auth_config::{AuthConfig, DEFAULT_PUBLIC_ROUTES};
+pub use idempotency_config::IdempotencyConfig;
+pub(crate) use ip_filter_config::parse_ip_net;
+pub use ip_filter_config::{IpFilterConfig, IpRules};
+pub use oidc_config::OidcConfig;
+pub use rate_limit_config::{RateLimitBackend, RateLimitConfig};
+pub use route_group::RouteGroup;
+pub use secret::Secret;
+pub use server_config::{CorsPolicy, MetricsEndpoint, ProxyHeader, ServerConfig};
+pub use session_config::{SameSite, SessionConfig};
+pub use standalone_config::{ConfigSource, StandaloneConfig};
+pub use trash_config::TrashConfig;
@@ -0,0 +1,102 @@
+// src/config/oidc_config.rs
+use crate::config::Secret;
+use anyhow::{Context, Result, bail};
+use std::time::Duration;
+
+/// OpenID Connect relying-party settings. Present only when OIDC_ISSUER is set.
+#[derive(Clone, Debug)]
+pub struct OidcConfig {
+    pub issuer: String,                // discovery base, without a trailing slash
+    pub client_id: String,             // also the required `aud`
+    pub client_secret: Option<Secret>, // sent with HTTP Basic; public clients rely on PKCE alone
+    pub redirect_url: String,          // registered callback, i.e. <base>/auth/oidc/callback
+    pub scopes: Vec<String>,           // requested scopes; always includes openid
+    pub jwks_ttl: Duration,            // how long fetched signing keys are trusted before refetch
+    pub login_ttl: Duration,           // how long a started login may take to come back
+}
+
+impl OidcConfig {
+    /// `None` unless OIDC_ISSUER is set.
+    /// - OIDC_ISSUER          (e.g. https://accounts.example.com)
+    /// - OIDC_CLIENT_ID       (required with OIDC_ISSUER)
+    /// - OIDC_CLIENT_SECRET   (optional)
+    /// - OIDC_REDIRECT_URL    (required with OIDC_ISSUER)
+    /// - OIDC_SCOPES          (space-separated, default: openid profile email)
+    /// - OIDC_JWKS_TTL_SECS   (default: 3600)
+    /// - OIDC_LOGIN_TTL_SECS  (default: 600)
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Option<Self>> {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let Some(issuer) = var("OIDC_ISSUER") else {
+            return Ok(None);
+        };
+        if !issuer.starts_with("https://") && !issuer.starts_with("http://") {
+            bail!("OIDC_ISSUER must be an http(s) URL, got {issuer}");
+        }
+
+        let mut scopes: Vec<String> = var("OIDC_SCOPES")
+            .unwrap_or_else(|| "openid profile email".to_owned())
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect();
+        if !scopes.iter().any(|s| s == "openid") {
+            scopes.insert(0, "openid".to_owned());
+        }
+
+        let secs = |key: &str, default: u64| -> Result<Duration> {
+            let s = match var(key) {
+                Some(v) => v.parse().with_context(|| format!("{key} must be u64"))?,
+                None => default,
+            };
+            Ok(Duration::from_secs(s))
+        };
+
+        Ok(Some(OidcConfig {
+            // Issuer identifiers are compared verbatim; a trailing slash is a classic mismatch
+            issuer: issuer.trim_end_matches('/').to_owned(),
+            client_id: var("OIDC_CLIENT_ID")
+                .context("OIDC_CLIENT_ID must be set with OIDC_ISSUER")?,
+            client_secret: var("OIDC_CLIENT_SECRET").map(Secret::from),
+            redirect_url: var("OIDC_REDIRECT_URL")
+                .context("OIDC_REDIRECT_URL must be set with OIDC_ISSUER")?,
+            scopes,
+            jwks_ttl: secs("OIDC_JWKS_TTL_SECS", 3600)?,
+            login_ttl: secs("OIDC_LOGIN_TTL_SECS", 600)?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(vars: &[(&str, &str)]) -> Result<Option<OidcConfig>> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        OidcConfig::load_from(|k| vars.get(k).cloned())
+    }
+
+    #[test]
+    fn loads_only_when_issuer_is_set() {
+        assert!(load(&[("OIDC_CLIENT_ID", "app")]).unwrap().is_none());
+        assert!(load(&[("OIDC_ISSUER", "https://idp.example.com")]).is_err());
+
+        let cfg = load(&[
+            ("OIDC_ISSUER", "https://idp.example.com/"),
+            ("OIDC_CLIENT_ID", "app"),
+            (
+                "OIDC_REDIRECT_URL",
+                "https://api.example.com/auth/oidc/callback",
+            ),
+            ("OIDC_SCOPES", "email"),
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(cfg.issuer, "https://idp.example.com");
+        assert_eq!(cfg.scopes, ["openid", "email"]);
+        assert!(cfg.client_secret.is_none());
+        assert_eq!(cfg.jwks_ttl, Duration::from_secs(3600));
+    }
+}
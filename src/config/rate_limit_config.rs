@@ -0,0 +1,119 @@
+// src/config/rate_limit_config.rs
+use crate::config::RouteGroup;
+use crate::ratelimit::Quota;
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateLimitBackend {
+    /// Per process; fine for a single instance.
+    #[default]
+    Memory,
+    /// The `rate_limits` table, shared by every instance.
+    Postgres,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub enabled: bool,                      // off -> no limiter at all
+    pub backend: RateLimitBackend,          // where GCRA state is kept
+    pub quotas: HashMap<RouteGroup, Quota>, // groups without an entry are unlimited
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        let minute = Duration::from_secs(60);
+        Self {
+            enabled: true,
+            backend: RateLimitBackend::Memory,
+            quotas: HashMap::from([
+                // Password guessing goes through here, so it gets the tightest budget
+                (RouteGroup::Auth, Quota::new(30, minute)),
+                (RouteGroup::Admin, Quota::new(120, minute)),
+                (RouteGroup::Api, Quota::new(600, minute)),
+            ]),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// - RATE_LIMIT_DISABLED: any value -> no rate limiting
+    /// - RATE_LIMIT_STORE     (memory | postgres, default: memory)
+    /// - RATE_LIMITS          per-group overrides, `group=limit/seconds;...`
+    ///   with groups auth, admin, ops and api, or `group=off` to lift a limit
+    ///   (default: auth=30/60;admin=120/60;api=600/60, ops unlimited)
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let mut cfg = RateLimitConfig {
+            enabled: var("RATE_LIMIT_DISABLED").is_none(),
+            ..Default::default()
+        };
+
+        cfg.backend = match var("RATE_LIMIT_STORE").as_deref() {
+            None | Some("memory") => RateLimitBackend::Memory,
+            Some("postgres") => RateLimitBackend::Postgres,
+            Some(other) => bail!("RATE_LIMIT_STORE must be memory or postgres, got {other}"),
+        };
+
+        if let Some(spec) = var("RATE_LIMITS") {
+            for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+                let (group, quota) = entry
+                    .split_once('=')
+                    .with_context(|| format!("RATE_LIMITS entry {entry:?} must be group=quota"))?;
+                let group: RouteGroup = group.trim().parse().map_err(anyhow::Error::msg)?;
+                match quota.trim() {
+                    "off" => cfg.quotas.remove(&group),
+                    quota => cfg.quotas.insert(
+                        group,
+                        quota
+                            .parse()
+                            .with_context(|| format!("invalid RATE_LIMITS quota for {group}"))?,
+                    ),
+                };
+            }
+        }
+
+        Ok(cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap as Vars;
+
+    fn load(vars: &[(&str, &str)]) -> Result<RateLimitConfig> {
+        let vars: Vars<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        RateLimitConfig::load_from(|k| vars.get(k).cloned())
+    }
+
+    #[test]
+    fn overrides_listed_groups_only() {
+        let cfg = load(&[
+            ("RATE_LIMITS", "auth=5/60; api=off; ops=100/1"),
+            ("RATE_LIMIT_STORE", "postgres"),
+        ])
+        .unwrap();
+        assert!(cfg.enabled);
+        assert_eq!(cfg.backend, RateLimitBackend::Postgres);
+        assert_eq!(
+            cfg.quotas[&RouteGroup::Auth],
+            Quota::new(5, Duration::from_secs(60))
+        );
+        assert_eq!(
+            cfg.quotas[&RouteGroup::Admin],
+            RateLimitConfig::default().quotas[&RouteGroup::Admin]
+        );
+        assert!(!cfg.quotas.contains_key(&RouteGroup::Api));
+        assert!(cfg.quotas.contains_key(&RouteGroup::Ops));
+
+        assert!(!load(&[("RATE_LIMIT_DISABLED", "1")]).unwrap().enabled);
+        assert!(load(&[("RATE_LIMITS", "todos=5/60")]).is_err());
+        assert!(load(&[("RATE_LIMITS", "auth=5")]).is_err());
+        assert!(load(&[("RATE_LIMIT_STORE", "redis")]).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+// src/config/route_group.rs
+use std::{fmt, str::FromStr};
+
+/// Coarse partition of the API that per-group policies (rate limits, ...)
+/// are configured against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RouteGroup {
+    /// `/auth/...`: login, registration, token refresh, sessions.
+    Auth,
+    /// `/admin/...`.
+    Admin,
+    /// Health probes and `/metrics`.
+    Ops,
+    /// Everything else, i.e. the todo API.
+    Api,
+}
+
+impl RouteGroup {
+    pub const ALL: [RouteGroup; 4] = [
+        RouteGroup::Auth,
+        RouteGroup::Admin,
+        RouteGroup::Ops,
+        RouteGroup::Api,
+    ];
+
+    /// The group a request path belongs to.
+    pub fn of(path: &str) -> Self {
+        let under = |prefix: &str| {
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+        if under("/auth") {
+            RouteGroup::Auth
+        } else if under("/admin") {
+            RouteGroup::Admin
+        } else if under("/health") || under("/metrics") {
+            RouteGroup::Ops
+        } else {
+            RouteGroup::Api
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RouteGroup::Auth => "auth",
+            RouteGroup::Admin => "admin",
+            RouteGroup::Ops => "ops",
+            RouteGroup::Api => "api",
+        }
+    }
+}
+
+impl fmt::Display for RouteGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RouteGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RouteGroup::ALL
+            .into_iter()
+            .find(|group| group.as_str() == s)
+            .ok_or_else(|| format!("unknown route group {s:?} (expected auth, admin, ops or api)"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_map_to_groups_on_segment_boundaries() {
+        assert_eq!(RouteGroup::of("/auth/token"), RouteGroup::Auth);
+        assert_eq!(RouteGroup::of("/admin"), RouteGroup::Admin);
+        assert_eq!(RouteGroup::of("/admin/users/1/role"), RouteGroup::Admin);
+        assert_eq!(RouteGroup::of("/health/ready"), RouteGroup::Ops);
+        assert_eq!(RouteGroup::of("/metrics"), RouteGroup::Ops);
+        assert_eq!(RouteGroup::of("/todos/1"), RouteGroup::Api);
+        assert_eq!(RouteGroup::of("/authors"), RouteGroup::Api);
+    }
+}
@@ -1,13 +1,28 @@
 // src/server_config.rs
-use anyhow::{Context, Result};
+use crate::config::{
+    AuthConfig, IdempotencyConfig, IpFilterConfig, OidcConfig, RateLimitConfig, SameSite,
+    TrashConfig, parse_ip_net,
+};
+use anyhow::{Context, Result, bail};
 use axum::http::{HeaderName, HeaderValue};
+use ipnet::IpNet;
+use std::net::SocketAddr;
 use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub struct ServerConfig {
-    pub request_id_header: HeaderName, // e.g., "x-request-id"
-    pub timeout: Duration,             // global handler timeout
-    pub cors: CorsPolicy,              // tiny switch for your use case
+    pub request_id_header: HeaderName,     // e.g., "x-request-id"
+    pub timeout: Duration,                 // global handler timeout
+    pub cors: CorsPolicy,                  // tiny switch for your use case
+    pub metrics: MetricsEndpoint,          // where /metrics is served
+    pub auth: AuthConfig,                  // admin token and user token settings
+    pub oidc: Option<OidcConfig>,          // external identity provider login, if configured
+    pub rate_limit: RateLimitConfig,       // per-client quotas by route group
+    pub trusted_proxies: Vec<IpNet>,       // peers whose forwarding headers are believed
+    pub trusted_proxy_header: ProxyHeader, // the one forwarding header those peers set
+    pub ip_filter: IpFilterConfig,         // per-route-group network allow/deny lists
+    pub idempotency: IdempotencyConfig,    // Idempotency-Key replay window
+    pub trash: TrashConfig,                // soft-deleted todo retention
 }
 
 impl Default for ServerConfig {
@@ -16,6 +31,15 @@ impl Default for ServerConfig {
             request_id_header: HeaderName::from_static("x-request-id"),
             timeout: Duration::from_secs(15),
             cors: CorsPolicy::Permissive,
+            metrics: MetricsEndpoint::Main,
+            auth: AuthConfig::default(),
+            oidc: None,
+            rate_limit: RateLimitConfig::default(),
+            trusted_proxies: Vec::new(),
+            trusted_proxy_header: ProxyHeader::default(),
+            ip_filter: IpFilterConfig::default(),
+            idempotency: IdempotencyConfig::default(),
+            trash: TrashConfig::default(),
         }
     }
 }
@@ -26,25 +50,39 @@ impl ServerConfig {
     /// - TIMEOUT_SECS      (default: 15)
     /// - CORS_ALLOWED_ORIGINS: comma-separated list -> Allow([...])
     /// - CORS_DISABLED: any non-empty value -> Disabled
+    /// - METRICS_DISABLED: any value -> no /metrics endpoint
+    /// - METRICS_ADDR: serve /metrics on its own listener instead (standalone only)
+    /// - TRUSTED_PROXIES: comma-separated CIDRs or addresses whose
+    ///   forwarding header is honoured (default: none)
+    /// - TRUSTED_PROXY_HEADER: which one, `forwarded`, `x-forwarded-for` or
+    ///   `x-real-ip` (default: x-forwarded-for)
+    /// - plus the keys read by [`AuthConfig::load_from`],
+    ///   [`OidcConfig::load_from`], [`RateLimitConfig::load_from`],
+    ///   [`IpFilterConfig::load_from`], [`IdempotencyConfig::load_from`] and
+    ///   [`TrashConfig::load_from`]
     pub fn load_from_env() -> Result<Self> {
-        use std::env;
+        Self::load_from(|key| std::env::var(key).ok())
+    }
 
+    /// Same keys as [`ServerConfig::load_from_env`], read through `var` so
+    /// callers can layer a config file under the environment.
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
         let mut cfg = ServerConfig::default();
 
-        if let Ok(h) = env::var("REQUEST_ID_HEADER") {
+        if let Some(h) = var("REQUEST_ID_HEADER") {
             cfg.request_id_header =
                 HeaderName::from_lowercase(h.as_bytes()).context("invalid REQUEST_ID_HEADER")?;
         }
 
-        if let Ok(secs) = env::var("TIMEOUT_SECS") {
+        if let Some(secs) = var("TIMEOUT_SECS") {
             let s: u64 = secs.parse().context("TIMEOUT_SECS must be u64")?;
             cfg.timeout = Duration::from_secs(s);
         }
 
         // CORS precedence: disabled > allow-list > permissive(default)
-        if env::var("CORS_DISABLED").is_ok() {
+        if var("CORS_DISABLED").is_some() {
             cfg.cors = CorsPolicy::Disabled;
-        } else if let Ok(csv) = env::var("CORS_ALLOWED_ORIGINS") {
+        } else if let Some(csv) = var("CORS_ALLOWED_ORIGINS") {
             let mut origins = Vec::new();
             for o in csv.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
                 origins.push(
@@ -56,6 +94,46 @@ impl ServerConfig {
             }
         }
 
+        // Metrics precedence: disabled > separate listener > main router(default)
+        if var("METRICS_DISABLED").is_some() {
+            cfg.metrics = MetricsEndpoint::Disabled;
+        } else if let Some(addr) = var("METRICS_ADDR") {
+            cfg.metrics =
+                MetricsEndpoint::Separate(addr.parse().context("METRICS_ADDR must be ip:port")?);
+        }
+
+        if let Some(csv) = var("TRUSTED_PROXIES") {
+            for net in csv.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                cfg.trusted_proxies
+                    .push(parse_ip_net(net).context("invalid TRUSTED_PROXIES")?);
+            }
+        }
+        if let Some(name) = var("TRUSTED_PROXY_HEADER") {
+            cfg.trusted_proxy_header = match name.trim().to_ascii_lowercase().as_str() {
+                "forwarded" => ProxyHeader::Forwarded,
+                "x-forwarded-for" => ProxyHeader::XForwardedFor,
+                "x-real-ip" => ProxyHeader::XRealIp,
+                other => bail!(
+                    "TRUSTED_PROXY_HEADER must be forwarded, x-forwarded-for or x-real-ip, got {other}"
+                ),
+            };
+        }
+
+        cfg.auth = AuthConfig::load_from(&var)?;
+        cfg.oidc = OidcConfig::load_from(&var)?;
+        cfg.rate_limit = RateLimitConfig::load_from(&var)?;
+        cfg.ip_filter = IpFilterConfig::load_from(&var)?;
+        cfg.idempotency = IdempotencyConfig::load_from(&var)?;
+        cfg.trash = TrashConfig::load_from(&var)?;
+
+        // A cookie browsers attach cross-site is only useful to origins allowed
+        // credentialed CORS, and Permissive never grants credentials
+        if cfg.auth.session.same_site == SameSite::None
+            && matches!(cfg.cors, CorsPolicy::Permissive)
+        {
+            bail!("SESSION_SAME_SITE=None requires CORS_ALLOWED_ORIGINS or CORS_DISABLED");
+        }
+
         Ok(cfg)
     }
 }
@@ -69,3 +147,26 @@ pub enum CorsPolicy {
     /// No CORS headers at all.
     Disabled,
 }
+
+/// The forwarding header trusted proxies set. Only this one is read: any
+/// other arrives from the client untouched, and believing it would let the
+/// client pick its own address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProxyHeader {
+    /// RFC 7239 `Forwarded`, using the `for=` parameter.
+    Forwarded,
+    #[default]
+    XForwardedFor,
+    /// A single address, as set by e.g. nginx's `proxy_set_header`.
+    XRealIp,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MetricsEndpoint {
+    /// `/metrics` on the main router, behind whatever auth guards the API.
+    Main,
+    /// `/metrics` on a dedicated listener, e.g. a private interface.
+    Separate(SocketAddr),
+    /// Metrics are still recorded but never exposed.
+    Disabled,
+}
@@ -0,0 +1,137 @@
+// src/config/session_config.rs
+use crate::config::Secret;
+use anyhow::{Context, Result, bail};
+use std::{fmt, time::Duration};
+
+/// The cookie's `SameSite` attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SessionConfig {
+    pub secret: Option<Secret>, // HMAC key for cookie signatures; random per process when unset
+    pub cookie_name: String,    // e.g. "session"
+    pub secure: bool,           // only send the cookie over HTTPS
+    pub same_site: SameSite,    // cross-site sending policy
+    pub idle_timeout: Duration, // unused this long -> expired
+    pub absolute_timeout: Duration, // expired this long after login regardless of use
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            cookie_name: "session".to_owned(),
+            secure: true,
+            same_site: SameSite::Lax,
+            idle_timeout: Duration::from_secs(30 * 60),
+            absolute_timeout: Duration::from_secs(12 * 60 * 60),
+        }
+    }
+}
+
+impl SessionConfig {
+    /// - SESSION_SECRET                 (random per process when unset)
+    /// - SESSION_COOKIE_NAME            (default: session)
+    /// - SESSION_COOKIE_SECURE          (default: true; false for plain-HTTP local dev)
+    /// - SESSION_SAME_SITE              (Strict | Lax | None, default: Lax)
+    /// - SESSION_IDLE_TIMEOUT_SECS      (default: 1800)
+    /// - SESSION_ABSOLUTE_TIMEOUT_SECS  (default: 43200, 12 hours)
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let mut cfg = SessionConfig {
+            secret: var("SESSION_SECRET").map(Secret::from),
+            ..Default::default()
+        };
+
+        if let Some(name) = var("SESSION_COOKIE_NAME") {
+            // RFC 6265 token characters only, so the name needs no quoting
+            if !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+            {
+                bail!("SESSION_COOKIE_NAME contains characters not allowed in a cookie name");
+            }
+            cfg.cookie_name = name;
+        }
+        if let Some(secure) = var("SESSION_COOKIE_SECURE") {
+            cfg.secure = secure
+                .parse()
+                .context("SESSION_COOKIE_SECURE must be true or false")?;
+        }
+        cfg.same_site = match var("SESSION_SAME_SITE").as_deref() {
+            None | Some("Lax") => SameSite::Lax,
+            Some("Strict") => SameSite::Strict,
+            Some("None") => SameSite::None,
+            Some(other) => bail!("SESSION_SAME_SITE must be Strict, Lax or None, got {other}"),
+        };
+        if cfg.same_site == SameSite::None && !cfg.secure {
+            bail!("SESSION_SAME_SITE=None requires SESSION_COOKIE_SECURE=true");
+        }
+        if let Some(secs) = var("SESSION_IDLE_TIMEOUT_SECS") {
+            let s: u64 = secs
+                .parse()
+                .context("SESSION_IDLE_TIMEOUT_SECS must be u64")?;
+            cfg.idle_timeout = Duration::from_secs(s);
+        }
+        if let Some(secs) = var("SESSION_ABSOLUTE_TIMEOUT_SECS") {
+            let s: u64 = secs
+                .parse()
+                .context("SESSION_ABSOLUTE_TIMEOUT_SECS must be u64")?;
+            cfg.absolute_timeout = Duration::from_secs(s);
+        }
+
+        Ok(cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(vars: &[(&str, &str)]) -> Result<SessionConfig> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        SessionConfig::load_from(|k| vars.get(k).cloned())
+    }
+
+    #[test]
+    fn validates_cookie_attributes() {
+        let cfg = load(&[
+            ("SESSION_COOKIE_NAME", "__Host-sid"),
+            ("SESSION_SAME_SITE", "Strict"),
+            ("SESSION_IDLE_TIMEOUT_SECS", "60"),
+        ])
+        .unwrap();
+        assert_eq!(cfg.cookie_name, "__Host-sid");
+        assert_eq!(cfg.same_site, SameSite::Strict);
+        assert_eq!(cfg.idle_timeout, Duration::from_secs(60));
+
+        assert!(load(&[("SESSION_COOKIE_NAME", "bad name")]).is_err());
+        assert!(load(&[("SESSION_SAME_SITE", "lax")]).is_err());
+        assert!(
+            load(&[
+                ("SESSION_SAME_SITE", "None"),
+                ("SESSION_COOKIE_SECURE", "false")
+            ])
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+// src/config/standalone_config.rs
+use crate::config::ServerConfig;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::time::Duration;
+
+/// Environment variables layered over an optional `KEY=VALUE` file.
+/// Real environment variables always win over the file.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigSource {
+    file: HashMap<String, String>,
+}
+
+impl ConfigSource {
+    /// Reads the file named by `CONFIG_FILE`, if set.
+    pub fn load() -> Result<Self> {
+        match std::env::var("CONFIG_FILE") {
+            Ok(path) => Self::from_file(path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        Ok(Self {
+            file: parse_env_file(&text),
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key)
+            .ok()
+            .or_else(|| self.file.get(key).cloned())
+    }
+}
+
+/// Parses `.env`-style lines: `KEY=VALUE`, `#` comments, optional surrounding quotes.
+fn parse_env_file(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| {
+            let v = v.trim();
+            let v = v
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(v);
+            (k.trim().to_owned(), v.to_owned())
+        })
+        .collect()
+}
+
+/// Settings for running outside Shuttle, where nothing is injected for us.
+#[derive(Clone, Debug)]
+pub struct StandaloneConfig {
+    pub database_url: String,
+    pub bind: SocketAddr,         // HOST + PORT
+    pub shutdown_grace: Duration, // max time to drain in-flight requests
+    pub db_max_connections: u32,  // PgPool size
+    pub server: ServerConfig,
+}
+
+impl StandaloneConfig {
+    /// - DATABASE_URL         (required)
+    /// - HOST                 (default: 0.0.0.0)
+    /// - PORT                 (default: 8000)
+    /// - SHUTDOWN_GRACE_SECS  (default: 30)
+    /// - DB_MAX_CONNECTIONS   (default: 10)
+    /// - plus every key understood by [`ServerConfig::load_from_env`] (incl. ADMIN_TOKEN)
+    pub fn load(src: &ConfigSource) -> Result<Self> {
+        let database_url = src
+            .get("DATABASE_URL")
+            .context("DATABASE_URL must be set")?;
+
+        let host: IpAddr = match src.get("HOST") {
+            Some(h) => h.parse().context("HOST must be an IP address")?,
+            None => IpAddr::from([0, 0, 0, 0]),
+        };
+        let port: u16 = match src.get("PORT") {
+            Some(p) => p.parse().context("PORT must be u16")?,
+            None => 8000,
+        };
+        let shutdown_grace = match src.get("SHUTDOWN_GRACE_SECS") {
+            Some(s) => Duration::from_secs(s.parse().context("SHUTDOWN_GRACE_SECS must be u64")?),
+            None => Duration::from_secs(30),
+        };
+        let db_max_connections = match src.get("DB_MAX_CONNECTIONS") {
+            Some(n) => n.parse().context("DB_MAX_CONNECTIONS must be u32")?,
+            None => 10,
+        };
+
+        Ok(Self {
+            database_url,
+            bind: SocketAddr::new(host, port),
+            shutdown_grace,
+            db_max_connections,
+            server: ServerConfig::load_from(|key| src.get(key))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_env_file_lines() {
+        let vars = parse_env_file(
+            "# comment\n\nSTANDALONE_TEST_PORT=9000\nSTANDALONE_TEST_URL=\"postgres://x/y\"\nnot a pair\n",
+        );
+        assert_eq!(vars["STANDALONE_TEST_PORT"], "9000");
+        assert_eq!(vars["STANDALONE_TEST_URL"], "postgres://x/y");
+        assert_eq!(vars.len(), 2);
+    }
+}
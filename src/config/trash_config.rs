@@ -0,0 +1,40 @@
+// src/config/trash_config.rs
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrashConfig {
+    pub retention: Option<Duration>, // None -> trashed todos are kept until purged by hand
+    pub purge_interval: Duration,    // how often the background purge runs
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            purge_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl TrashConfig {
+    /// - TRASH_RETENTION_SECS      (default: 2592000, 30 days; 0 keeps trash forever)
+    /// - TRASH_PURGE_INTERVAL_SECS (default: 3600)
+    pub fn load_from(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let mut cfg = TrashConfig::default();
+        if let Some(secs) = var("TRASH_RETENTION_SECS") {
+            let secs: u64 = secs.parse().context("TRASH_RETENTION_SECS must be u64")?;
+            cfg.retention = (secs > 0).then(|| Duration::from_secs(secs));
+        }
+        if let Some(secs) = var("TRASH_PURGE_INTERVAL_SECS") {
+            let secs: u64 = secs
+                .parse()
+                .ok()
+                .filter(|&s| s > 0)
+                .context("TRASH_PURGE_INTERVAL_SECS must be a positive integer")?;
+            cfg.purge_interval = Duration::from_secs(secs);
+        }
+        Ok(cfg)
+    }
+}
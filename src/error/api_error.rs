@@ -0,0 +1,230 @@
+// src/error/api_error.rs
+use crate::auth::RefreshError;
+use crate::auth::oidc::OidcError;
+use crate::error::ProblemDetails;
+use crate::models::ListQueryError;
+use crate::recurrence::RRuleError;
+use crate::validation::ValidationErrors;
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use thiserror::Error;
+
+/// Crate-wide handler error. Every variant renders as `application/problem+json`;
+/// database and internal failures are logged and replaced with a generic detail.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    /// An `If-Match` / `If-None-Match` condition did not hold.
+    #[error("{0}")]
+    PreconditionFailed(String),
+    /// An upstream service (e.g. the identity provider) failed; the detail is
+    /// logged, not returned.
+    #[error("{0}")]
+    BadGateway(String),
+    #[error(transparent)]
+    Validation(#[from] ValidationErrors),
+    #[error(transparent)]
+    Json(#[from] JsonRejection),
+    #[error(transparent)]
+    Query(#[from] QueryRejection),
+    #[error(transparent)]
+    Path(#[from] PathRejection),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+impl ApiError {
+    pub fn not_found(resource: &str, id: impl std::fmt::Display) -> Self {
+        Self::NotFound(format!("{} {} not found", resource, id))
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            ApiError::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Json(rejection) => rejection.status(),
+            ApiError::Query(_) | ApiError::Path(_) => StatusCode::BAD_REQUEST,
+            ApiError::Database(e) => database_status(e),
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable machine-readable error code, safe for clients to match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::PreconditionFailed(_) => "precondition_failed",
+            ApiError::BadGateway(_) => "bad_gateway",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::Json(_) => "invalid_json",
+            ApiError::Query(_) => "invalid_query",
+            ApiError::Path(_) => "invalid_path",
+            ApiError::Database(e) => match database_status(e) {
+                StatusCode::NOT_FOUND => "not_found",
+                StatusCode::CONFLICT => "conflict",
+                StatusCode::UNPROCESSABLE_ENTITY => "constraint_violation",
+                StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+                _ => "internal_error",
+            },
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    fn detail(&self) -> String {
+        match self {
+            ApiError::Json(rejection) => rejection.body_text(),
+            ApiError::Query(rejection) => rejection.body_text(),
+            ApiError::Path(rejection) => rejection.body_text(),
+            ApiError::Database(e) => match database_status(e) {
+                StatusCode::NOT_FOUND => "The requested resource was not found".to_owned(),
+                StatusCode::CONFLICT => "The request conflicts with existing data".to_owned(),
+                StatusCode::UNPROCESSABLE_ENTITY => {
+                    "The request violates a data constraint".to_owned()
+                }
+                StatusCode::SERVICE_UNAVAILABLE => {
+                    "The service is temporarily unavailable".to_owned()
+                }
+                _ => "An internal error occurred".to_owned(),
+            },
+            ApiError::Internal(_) => "An internal error occurred".to_owned(),
+            ApiError::BadGateway(_) => "An upstream service failed".to_owned(),
+            other => other.to_string(),
+        }
+    }
+
+    pub fn to_problem(&self) -> ProblemDetails {
+        let mut problem = ProblemDetails::new(self.status(), self.code(), self.detail());
+        if let ApiError::Validation(errors) = self {
+            problem.errors = errors.0.clone();
+        }
+        problem
+    }
+}
+
+fn database_status(e: &sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        sqlx::Error::Database(db) if db.is_unique_violation() => StatusCode::CONFLICT,
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => StatusCode::CONFLICT,
+        sqlx::Error::Database(db) if db.is_check_violation() => StatusCode::UNPROCESSABLE_ENTITY,
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<ListQueryError> for ApiError {
+    fn from(e: ListQueryError) -> Self {
+        ApiError::BadRequest(e.to_string())
+    }
+}
+
+impl From<RRuleError> for ApiError {
+    fn from(e: RRuleError) -> Self {
+        ApiError::BadRequest(format!("Invalid rrule: {e}"))
+    }
+}
+
+impl From<OidcError> for ApiError {
+    fn from(e: OidcError) -> Self {
+        match e {
+            OidcError::InvalidState => ApiError::BadRequest(e.to_string()),
+            OidcError::Rejected => ApiError::Unauthorized("Login was rejected".to_owned()),
+            OidcError::InvalidIdToken(ref reason) => {
+                tracing::warn!(%reason, "rejected OIDC ID token");
+                ApiError::Unauthorized("Login was rejected".to_owned())
+            }
+            OidcError::Provider(_) => ApiError::BadGateway(e.to_string()),
+            OidcError::Database(e) => ApiError::Database(e),
+        }
+    }
+}
+
+impl From<RefreshError> for ApiError {
+    fn from(e: RefreshError) -> Self {
+        match e {
+            // Reuse is logged where it is detected; the client just sees a dead token
+            RefreshError::Invalid | RefreshError::Reused => {
+                ApiError::Unauthorized("Invalid or expired refresh token".to_owned())
+            }
+            RefreshError::Database(e) => ApiError::Database(e),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            tracing::error!(error = %self, code = self.code(), "request failed");
+        } else {
+            tracing::debug!(error = %self, code = self.code(), "request rejected");
+        }
+        let mut res = self.to_problem().into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            res.headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_not_found_maps_to_404() {
+        let err = ApiError::from(sqlx::Error::RowNotFound);
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+        assert_eq!(err.code(), "not_found");
+    }
+
+    #[test]
+    fn internal_details_are_not_leaked() {
+        let err = ApiError::from(sqlx::Error::Protocol("secret connection string".into()));
+        let problem = err.to_problem();
+        assert_eq!(problem.status, 500);
+        assert_eq!(problem.code, "internal_error");
+        assert!(!problem.detail.contains("secret"));
+
+        let res = err.into_response();
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+    }
+
+    #[test]
+    fn unauthorized_carries_bearer_challenge() {
+        let res = ApiError::Unauthorized("Authentication required".into()).into_response();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            "Bearer"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod api_error;
+mod problem;
+
+pub use api_error::ApiError;
+pub use problem::ProblemDetails;
@@ -0,0 +1,268 @@
+// src/extract/conditional.rs
+// Conditional requests (RFC 9110 §13): entity tags, `If-Match` and
+// `If-None-Match`. Resources opt in by implementing `Versioned`.
+use crate::error::ApiError;
+use crate::extract::Json;
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::fmt;
+
+/// An entity tag. Tags built by this crate are strong; weak ones only come
+/// from clients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ETag {
+    weak: bool,
+    opaque: String,
+}
+
+impl ETag {
+    /// A strong tag; `"` and anything outside visible ASCII are dropped.
+    pub fn strong(tag: impl fmt::Display) -> Self {
+        let opaque = tag
+            .to_string()
+            .chars()
+            .filter(|c| c.is_ascii_graphic() && *c != '"')
+            .collect();
+        Self {
+            weak: false,
+            opaque,
+        }
+    }
+
+    /// Same representation, byte for byte; required by `If-Match`.
+    pub fn strong_eq(&self, other: &ETag) -> bool {
+        !self.weak && !other.weak && self.opaque == other.opaque
+    }
+
+    /// Same opaque tag whatever the weakness; used by `If-None-Match`.
+    pub fn weak_eq(&self, other: &ETag) -> bool {
+        self.opaque == other.opaque
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let (weak, quoted) = match s.strip_prefix("W/") {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let opaque = quoted.strip_prefix('"')?.strip_suffix('"')?;
+        opaque
+            .chars()
+            .all(|c| c.is_ascii_graphic() && c != '"')
+            .then(|| Self {
+                weak,
+                opaque: opaque.to_owned(),
+            })
+    }
+}
+
+impl fmt::Display for ETag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+        write!(f, "\"{}\"", self.opaque)
+    }
+}
+
+impl From<&ETag> for HeaderValue {
+    fn from(tag: &ETag) -> Self {
+        HeaderValue::from_str(&tag.to_string()).expect("entity tags are visible ASCII")
+    }
+}
+
+/// A resource whose current representation has an entity tag.
+pub trait Versioned {
+    fn etag(&self) -> ETag;
+}
+
+/// JSON body with the resource's `ETag` header.
+#[derive(Debug)]
+pub struct Tagged<T>(pub T);
+
+impl<T: Versioned + Serialize> IntoResponse for Tagged<T> {
+    fn into_response(self) -> Response {
+        let etag = self.0.etag();
+        ([(header::ETAG, HeaderValue::from(&etag))], Json(self.0)).into_response()
+    }
+}
+
+/// Value of `If-Match` or `If-None-Match`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TagList {
+    Any,
+    Tags(Vec<ETag>),
+}
+
+impl TagList {
+    /// Every line of the header, comma-separated. Entries that do not parse
+    /// are dropped, so they never match.
+    fn from_headers(headers: &HeaderMap, name: &HeaderName) -> Option<Self> {
+        let mut values = headers.get_all(name).iter().peekable();
+        values.peek()?;
+        let mut tags = Vec::new();
+        for entry in values
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+        {
+            if entry == "*" {
+                return Some(TagList::Any);
+            }
+            tags.extend(ETag::parse(entry));
+        }
+        Some(TagList::Tags(tags))
+    }
+
+    fn matches(&self, current: &ETag, eq: fn(&ETag, &ETag) -> bool) -> bool {
+        match self {
+            TagList::Any => true,
+            TagList::Tags(tags) => tags.iter().any(|tag| eq(tag, current)),
+        }
+    }
+}
+
+/// The request's `If-Match` and `If-None-Match` headers. Handlers evaluate
+/// them against the resource's current tag once it is loaded; writes must do
+/// so while holding the row lock so the check and the change are atomic.
+#[derive(Clone, Debug, Default)]
+pub struct Preconditions {
+    if_match: Option<TagList>,
+    if_none_match: Option<TagList>,
+}
+
+impl Preconditions {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            if_match: TagList::from_headers(headers, &header::IF_MATCH),
+            if_none_match: TagList::from_headers(headers, &header::IF_NONE_MATCH),
+        }
+    }
+
+    /// For GET: a failed `If-Match` is 412, and a matching `If-None-Match`
+    /// yields the 304 to send instead of the body.
+    pub fn check_read(&self, current: &ETag) -> Result<Option<Response>, ApiError> {
+        self.check_if_match(current)?;
+        match &self.if_none_match {
+            Some(list) if list.matches(current, ETag::weak_eq) => Ok(Some(
+                (
+                    StatusCode::NOT_MODIFIED,
+                    [(header::ETAG, HeaderValue::from(current))],
+                )
+                    .into_response(),
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    /// For PUT, PATCH and DELETE: 412 unless `If-Match` (when sent) matches
+    /// and `If-None-Match` (when sent) does not.
+    pub fn check_write(&self, current: &ETag) -> Result<(), ApiError> {
+        self.check_if_match(current)?;
+        match &self.if_none_match {
+            Some(list) if list.matches(current, ETag::weak_eq) => Err(
+                ApiError::PreconditionFailed("The resource matches If-None-Match".to_owned()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_if_match(&self, current: &ETag) -> Result<(), ApiError> {
+        match &self.if_match {
+            Some(list) if !list.matches(current, ETag::strong_eq) => {
+                Err(ApiError::PreconditionFailed(format!(
+                    "The resource has changed; its current ETag is {current}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Preconditions {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preconditions(pairs: &[(HeaderName, &str)]) -> Preconditions {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name, HeaderValue::from_str(value).unwrap());
+        }
+        Preconditions::from_headers(&headers)
+    }
+
+    #[test]
+    fn parses_tags_and_wildcards() {
+        assert_eq!(ETag::parse(r#""3""#), Some(ETag::strong(3)));
+        assert!(ETag::parse(r#"W/"3""#).unwrap().weak);
+        assert_eq!(ETag::parse("3"), None);
+        assert_eq!(ETag::strong(7).to_string(), r#""7""#);
+
+        let mut headers = HeaderMap::new();
+        headers.append(header::IF_MATCH, HeaderValue::from_static(r#""1", junk"#));
+        headers.append(header::IF_MATCH, HeaderValue::from_static(r#"W/"2""#));
+        assert_eq!(
+            TagList::from_headers(&headers, &header::IF_MATCH),
+            Some(TagList::Tags(vec![
+                ETag::strong(1),
+                ETag::parse(r#"W/"2""#).unwrap()
+            ]))
+        );
+        headers.append(header::IF_MATCH, HeaderValue::from_static("*"));
+        assert_eq!(
+            TagList::from_headers(&headers, &header::IF_MATCH),
+            Some(TagList::Any)
+        );
+        assert_eq!(
+            TagList::from_headers(&headers, &header::IF_NONE_MATCH),
+            None
+        );
+    }
+
+    #[test]
+    fn if_match_uses_strong_comparison() {
+        let current = ETag::strong(2);
+        assert!(Preconditions::default().check_write(&current).is_ok());
+        assert!(
+            preconditions(&[(header::IF_MATCH, r#""1", "2""#)])
+                .check_write(&current)
+                .is_ok()
+        );
+        assert!(
+            preconditions(&[(header::IF_MATCH, "*")])
+                .check_write(&current)
+                .is_ok()
+        );
+        let err = preconditions(&[(header::IF_MATCH, r#"W/"2""#)])
+            .check_write(&current)
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(err.code(), "precondition_failed");
+    }
+
+    #[test]
+    fn if_none_match_is_304_on_reads_and_412_on_writes() {
+        let current = ETag::strong(2);
+        let pre = preconditions(&[(header::IF_NONE_MATCH, r#"W/"2""#)]);
+        let res = pre.check_read(&current).unwrap().unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers()[header::ETAG], r#""2""#);
+        assert!(pre.check_write(&current).is_err());
+
+        let stale = preconditions(&[(header::IF_NONE_MATCH, r#""1""#)]);
+        assert!(stale.check_read(&current).unwrap().is_none());
+        assert!(stale.check_write(&current).is_ok());
+    }
+}
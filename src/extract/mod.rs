@@ -0,0 +1,7 @@
+mod conditional;
+mod rejection;
+mod validated_json;
+
+pub use conditional::{ETag, Preconditions, Tagged, Versioned};
+pub use rejection::{Json, Path, Query};
+pub use validated_json::ValidatedJson;
@@ -0,0 +1,263 @@
+// src/idempotency/store.rs
+use crate::config::IdempotencyConfig;
+use axum::{
+    body::Body,
+    http::{HeaderName, HeaderValue, StatusCode, header, response::Parts},
+    response::Response,
+};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, types::Json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Every this many claims, expired keys are deleted.
+const SWEEP_EVERY: u64 = 1000;
+
+/// Extra time an in-flight claim is held beyond the request timeout, after
+/// which the claim of a crashed instance can be taken over.
+const LEASE_MARGIN: Duration = Duration::from_secs(30);
+
+/// Identifies what a key was first used for: a retry must repeat the same
+/// method, path, query and body byte for byte.
+pub fn fingerprint(method: &str, path_and_query: &str, body: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for part in [method.as_bytes(), path_and_query.as_bytes(), body] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+/// A finished response as kept for replay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StoredResponse {
+    /// Copies everything a retry should see. Cookies are left out: a replay
+    /// must not hand out a second copy of a session.
+    pub fn capture(parts: &Parts, body: &[u8]) -> Self {
+        let headers = parts
+            .headers
+            .iter()
+            .filter(|(name, _)| ![header::SET_COOKIE, header::CONTENT_LENGTH].contains(name))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        Self {
+            status: parts.status.as_u16(),
+            headers,
+            body: body.to_vec(),
+        }
+    }
+
+    pub fn into_response(self) -> Response {
+        let mut res = Response::new(Body::from(self.body));
+        *res.status_mut() =
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) =
+                (HeaderName::try_from(name), HeaderValue::try_from(value))
+            {
+                res.headers_mut().append(name, value);
+            }
+        }
+        res
+    }
+}
+
+/// Outcome of [`IdempotencyStore::claim`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Claim {
+    /// The key is new (or expired) and now belongs to this request.
+    Acquired,
+    /// The key was first used for a different request.
+    Mismatch,
+    /// The first request with this key has not finished yet.
+    InFlight,
+    /// The first request finished; send its response again.
+    Replay(StoredResponse),
+}
+
+/// Idempotency keys and their responses in the `idempotency_keys` table, so
+/// a retry is recognised whichever instance it reaches.
+#[derive(Clone, Debug)]
+pub struct IdempotencyStore {
+    pool: PgPool,
+    ttl: Duration,
+    lease: Duration,
+    claims: Arc<AtomicU64>,
+}
+
+impl IdempotencyStore {
+    pub fn new(pool: PgPool, ttl: Duration, lease: Duration) -> Self {
+        Self {
+            pool,
+            ttl,
+            lease,
+            claims: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// `None` when idempotency keys are disabled. The timeout layer bounds
+    /// how long a handler can run, so in-flight claims are held that long
+    /// plus a margin.
+    pub fn from_config(
+        cfg: &IdempotencyConfig,
+        pool: &PgPool,
+        request_timeout: Duration,
+    ) -> Option<Self> {
+        cfg.enabled
+            .then(|| Self::new(pool.clone(), cfg.ttl, request_timeout + LEASE_MARGIN))
+    }
+
+    /// Atomically takes `key` for this request, unless it is already taken.
+    pub async fn claim(
+        &self,
+        client: &str,
+        key: &str,
+        fingerprint: &[u8],
+    ) -> Result<Claim, sqlx::Error> {
+        if self.claims.fetch_add(1, Ordering::Relaxed) % SWEEP_EVERY == SWEEP_EVERY - 1 {
+            self.sweep().await?;
+        }
+
+        // Expired rows are reused in place; live ones make the upsert a no-op
+        let acquired = sqlx::query(
+            r#"
+            INSERT INTO idempotency_keys AS k (client, key, fingerprint, expires_at)
+            VALUES ($1, $2, $3, now() + make_interval(secs => $4))
+            ON CONFLICT (client, key) DO UPDATE
+            SET fingerprint = EXCLUDED.fingerprint, status = NULL, headers = NULL,
+                body = NULL, created_at = now(), expires_at = EXCLUDED.expires_at
+            WHERE k.expires_at <= now()
+            "#,
+        )
+        .bind(client)
+        .bind(key)
+        .bind(fingerprint)
+        .bind(self.lease.as_secs_f64())
+        .execute(&self.pool)
+        .await?
+        .rows_affected()
+            == 1;
+        if acquired {
+            return Ok(Claim::Acquired);
+        }
+
+        type Row = (
+            Vec<u8>,
+            Option<i32>,
+            Option<Json<Vec<(String, String)>>>,
+            Option<Vec<u8>>,
+        );
+        let row: Option<Row> = sqlx::query_as(
+            "SELECT fingerprint, status, headers, body FROM idempotency_keys \
+             WHERE client = $1 AND key = $2",
+        )
+        .bind(client)
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(match row {
+            // Released by a failed first attempt in between; let the client retry
+            None => Claim::InFlight,
+            Some((stored, ..)) if stored != fingerprint => Claim::Mismatch,
+            Some((_, Some(status), headers, body)) => Claim::Replay(StoredResponse {
+                status: u16::try_from(status).unwrap_or(500),
+                headers: headers.map(|Json(h)| h).unwrap_or_default(),
+                body: body.unwrap_or_default(),
+            }),
+            Some((_, None, ..)) => Claim::InFlight,
+        })
+    }
+
+    /// Stores the response for replay and starts the key's TTL.
+    pub async fn complete(
+        &self,
+        client: &str,
+        key: &str,
+        fingerprint: &[u8],
+        res: &StoredResponse,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys
+            SET status = $4, headers = $5, body = $6,
+                expires_at = now() + make_interval(secs => $7)
+            WHERE client = $1 AND key = $2 AND fingerprint = $3 AND status IS NULL
+            "#,
+        )
+        .bind(client)
+        .bind(key)
+        .bind(fingerprint)
+        .bind(i32::from(res.status))
+        .bind(Json(&res.headers))
+        .bind(&res.body)
+        .bind(self.ttl.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gives up an in-flight claim so the next retry runs the request again.
+    pub async fn release(&self, client: &str, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE client = $1 AND key = $2 AND status IS NULL",
+        )
+        .bind(client)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn sweep(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE expires_at < now()")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_separates_fields() {
+        let a = fingerprint("POST", "/todos", b"{}");
+        assert_eq!(a, fingerprint("POST", "/todos", b"{}"));
+        assert_ne!(a, fingerprint("PUT", "/todos", b"{}"));
+        assert_ne!(a, fingerprint("POST", "/todos?x=1", b"{}"));
+        // Length prefixes keep field boundaries from shifting
+        assert_ne!(
+            fingerprint("POST", "/a", b"b"),
+            fingerprint("POST", "/ab", b"")
+        );
+    }
+
+    #[test]
+    fn capture_drops_cookies_and_round_trips() {
+        let res = Response::builder()
+            .status(StatusCode::CREATED)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::LOCATION, "/todos/7")
+            .header(header::SET_COOKIE, "session=abc")
+            .body(())
+            .unwrap();
+        let (parts, ()) = res.into_parts();
+        let stored = StoredResponse::capture(&parts, br#"{"id":7}"#);
+        assert_eq!(stored.status, 201);
+        assert_eq!(stored.headers.len(), 2);
+
+        let replay = stored.into_response();
+        assert_eq!(replay.status(), StatusCode::CREATED);
+        assert_eq!(replay.headers()[header::LOCATION], "/todos/7");
+        assert!(replay.headers().get(header::SET_COOKIE).is_none());
+    }
+}
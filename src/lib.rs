@@ -1,4 +1,13 @@
+pub mod audit;
+pub mod auth;
 pub mod config;
+pub mod error;
+pub mod extract;
+pub mod idempotency;
 pub mod middleware;
 pub mod models;
+pub mod ratelimit;
+pub mod recurrence;
 pub mod routes;
+pub mod runtime;
+pub mod validation;
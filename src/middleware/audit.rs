@@ -0,0 +1,102 @@
+// src/middleware/audit.rs
+use crate::audit::{AuditLog, NewAuditEvent, body_digest};
+use crate::auth::Principal;
+use crate::error::ProblemDetails;
+use crate::middleware::ClientIp;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{MatchedPath, Request, State},
+    http::{HeaderName, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{SubsecRound, Utc};
+
+/// Audited bodies are buffered to digest them; same bound as axum's default
+/// body limit, so no request a handler would accept is refused here.
+pub const AUDIT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Mutating requests, and every request to the admin API.
+fn is_audited(method: &Method, path: &str) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || path == "/admin"
+        || path.starts_with("/admin/")
+}
+
+/// Appends an [`audit_events`](AuditLog) row once the response is known.
+/// Runs outside `authenticate`, which copies the resolved [`Principal`] onto
+/// the response, so rejected credentials are recorded too (as anonymous).
+pub async fn record_audit(
+    State((audit, request_id_header)): State<(AuditLog, HeaderName)>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !is_audited(req.method(), req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let mut event = NewAuditEvent {
+        occurred_at: Utc::now().trunc_subsecs(6),
+        principal: None,
+        method: parts.method.to_string(),
+        route: parts
+            .extensions
+            .get::<MatchedPath>()
+            .map(|p| p.as_str().to_owned()),
+        path: parts.uri.path().to_owned(),
+        status: 0,
+        source_ip: ClientIp::from_extensions(&parts.extensions).map(|ip| ip.to_string()),
+        request_id: parts
+            .headers
+            .get(&request_id_header)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned),
+        body_digest: None,
+    };
+
+    let res = match to_bytes(body, AUDIT_BODY_LIMIT).await {
+        Ok(bytes) => {
+            let content_type = parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok());
+            event.body_digest = body_digest(content_type, &bytes);
+            next.run(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        }
+        Err(_) => ProblemDetails::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            format!("Request body exceeds {AUDIT_BODY_LIMIT} bytes"),
+        )
+        .into_response(),
+    };
+
+    event.status = i32::from(res.status().as_u16());
+    event.principal = res.extensions().get::<Principal>().map(ToString::to_string);
+    if let Err(e) = audit.append(&event).await {
+        tracing::error!(
+            error = %e,
+            method = %event.method,
+            path = %event.path,
+            "failed to write audit event"
+        );
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audits_mutations_and_admin_reads() {
+        assert!(is_audited(&Method::POST, "/todos"));
+        assert!(is_audited(&Method::DELETE, "/todos/1"));
+        assert!(is_audited(&Method::GET, "/admin/audit-events"));
+        assert!(!is_audited(&Method::GET, "/todos"));
+        assert!(!is_audited(&Method::GET, "/administrators"));
+        assert!(!is_audited(&Method::OPTIONS, "/todos"));
+    }
+}
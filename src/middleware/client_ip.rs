@@ -0,0 +1,301 @@
+// src/middleware/client_ip.rs
+use crate::auth::Principal;
+use crate::config::ProxyHeader;
+use crate::error::ApiError;
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, OptionalFromRequestParts, Request, State},
+    http::{Extensions, HeaderMap, HeaderName, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+pub const FORWARDED: HeaderName = HeaderName::from_static("forwarded");
+pub const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+pub const X_REAL_IP: HeaderName = HeaderName::from_static("x-real-ip");
+
+/// The address of the client that sent the request, as resolved by
+/// [`resolve_client_ip`]: the TCP peer, or the originating address a trusted
+/// proxy reported in front of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl std::fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl ClientIp {
+    /// The resolved address if the middleware ran, otherwise the TCP peer.
+    pub fn from_extensions(extensions: &Extensions) -> Option<Self> {
+        extensions.get::<ClientIp>().copied().or_else(|| {
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| ClientIp(addr.ip().to_canonical()))
+        })
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        ClientIp::from_extensions(&parts.extensions).ok_or_else(|| {
+            ApiError::Internal(anyhow::anyhow!(
+                "client address unavailable; serve the router with connect info"
+            ))
+        })
+    }
+}
+
+impl<S: Send + Sync> OptionalFromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(ClientIp::from_extensions(&parts.extensions))
+    }
+}
+
+/// Who per-client state (quotas, idempotency keys) belongs to: the
+/// principal when `authenticate` found one, otherwise `ip:<ClientIp>`.
+pub(crate) fn client_key(extensions: &Extensions) -> String {
+    match extensions.get::<Principal>() {
+        Some(principal) => principal.to_string(),
+        None => match ClientIp::from_extensions(extensions) {
+            Some(ip) => format!("ip:{ip}"),
+            None => "ip:unknown".to_owned(),
+        },
+    }
+}
+
+/// Proxies whose forwarding header is believed. Requests from any other
+/// peer are attributed to the peer itself, whatever headers they carry.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies {
+    nets: Arc<[IpNet]>,
+    header: ProxyHeader,
+}
+
+impl TrustedProxies {
+    /// Trusts `X-Forwarded-For` from `nets`; see [`with_header`](Self::with_header).
+    pub fn new(nets: impl IntoIterator<Item = IpNet>) -> Self {
+        Self {
+            nets: nets.into_iter().collect(),
+            header: ProxyHeader::default(),
+        }
+    }
+
+    /// The header the proxies set. The others are never read, even from a
+    /// trusted peer, since the proxy passes them on from the client as sent.
+    pub fn with_header(mut self, header: ProxyHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.nets.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Walks the forwarding chain from the nearest hop outwards and returns
+    /// the first address not in a trusted network. If every hop is trusted
+    /// the leftmost one wins; an unparseable or obfuscated hop ends the walk
+    /// at the last address that was vouched for.
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = peer.to_canonical();
+        if !self.contains(peer) {
+            return peer;
+        }
+        let Some(chain) = forwarded_chain(headers, self.header) else {
+            return peer;
+        };
+
+        let mut client = peer;
+        for hop in chain.into_iter().rev() {
+            let Some(ip) = hop else { break };
+            client = ip;
+            if !self.contains(ip) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Resolves the [`ClientIp`] once per request, stores it in the request
+/// extensions and records it on the surrounding `TraceLayer` span. Requests
+/// served without connect info are left unresolved.
+pub async fn resolve_client_ip(
+    State(proxies): State<TrustedProxies>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    if let Some(peer) = peer {
+        let ip = proxies.resolve(peer, req.headers());
+        tracing::Span::current().record("client_ip", tracing::field::display(ip));
+        req.extensions_mut().insert(ClientIp(ip));
+    }
+    next.run(req).await
+}
+
+/// Hops listed by `header`, client first. Entries that are not an address
+/// come back as `None`.
+fn forwarded_chain(headers: &HeaderMap, header: ProxyHeader) -> Option<Vec<Option<IpAddr>>> {
+    let joined = |name: &HeaderName| {
+        let values: Vec<&str> = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        (!values.is_empty()).then(|| values.join(","))
+    };
+
+    match header {
+        ProxyHeader::Forwarded => joined(&FORWARDED).map(|forwarded| {
+            forwarded
+                .split(',')
+                .map(|element| {
+                    element
+                        .split(';')
+                        .filter_map(|pair| pair.split_once('='))
+                        .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                        .and_then(|(_, node)| parse_node(node))
+                })
+                .collect()
+        }),
+        ProxyHeader::XForwardedFor => {
+            joined(&X_FORWARDED_FOR).map(|xff| xff.split(',').map(parse_node).collect())
+        }
+        ProxyHeader::XRealIp => joined(&X_REAL_IP).map(|ip| vec![parse_node(&ip)]),
+    }
+}
+
+/// `1.2.3.4`, `1.2.3.4:80`, `2001:db8::1` or `[2001:db8::1]:80`, optionally
+/// quoted. Obfuscated identifiers (`unknown`, `_hidden`) yield `None`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    let ip = if let Some(rest) = node.strip_prefix('[') {
+        rest.split_once(']')?.0.parse().ok()?
+    } else {
+        match node.parse::<IpAddr>() {
+            Ok(ip) => ip,
+            Err(_) => node.parse::<SocketAddr>().ok()?.ip(),
+        }
+    };
+    Some(ip.to_canonical())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn proxies(nets: &[&str]) -> TrustedProxies {
+        TrustedProxies::new(nets.iter().map(|n| n.parse().unwrap()))
+    }
+
+    fn headers(pairs: &[(HeaderName, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_node_forms() {
+        assert_eq!(parse_node(" 203.0.113.7 "), Some(ip("203.0.113.7")));
+        assert_eq!(parse_node("203.0.113.7:4711"), Some(ip("203.0.113.7")));
+        assert_eq!(parse_node("2001:db8::1"), Some(ip("2001:db8::1")));
+        assert_eq!(
+            parse_node("\"[2001:db8::1]:4711\""),
+            Some(ip("2001:db8::1"))
+        );
+        assert_eq!(parse_node("::ffff:198.51.100.2"), Some(ip("198.51.100.2")));
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+
+    #[test]
+    fn ignores_headers_from_untrusted_peers() {
+        let proxies = proxies(&["10.0.0.0/8"]);
+        let h = headers(&[(X_FORWARDED_FOR, "198.51.100.9")]);
+        assert_eq!(proxies.resolve(ip("192.0.2.1"), &h), ip("192.0.2.1"));
+    }
+
+    #[test]
+    fn walks_forwarded_for_from_the_right() {
+        let proxies = proxies(&["10.0.0.0/8"]);
+        // The leftmost entry is client-supplied and must not win over the
+        // first hop a trusted proxy actually saw
+        let h = headers(&[(X_FORWARDED_FOR, "1.1.1.1, 203.0.113.7, 10.0.0.2")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &h), ip("203.0.113.7"));
+
+        // Repeated header lines are one list
+        let h = headers(&[
+            (X_FORWARDED_FOR, "203.0.113.7"),
+            (X_FORWARDED_FOR, "10.0.0.2"),
+        ]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &h), ip("203.0.113.7"));
+
+        // All hops trusted: the leftmost is the client
+        let h = headers(&[(X_FORWARDED_FOR, "10.1.1.1, 10.0.0.2")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &h), ip("10.1.1.1"));
+
+        // Garbage stops the walk at the last verified hop
+        let h = headers(&[(X_FORWARDED_FOR, "203.0.113.7, bogus, 10.0.0.2")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &h), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn reads_only_the_configured_header() {
+        let h = headers(&[
+            (
+                FORWARDED,
+                "for=\"[2001:db8::7]:4711\";proto=https, For=10.0.0.2;by=10.0.0.1",
+            ),
+            (X_FORWARDED_FOR, "198.51.100.9"),
+            (X_REAL_IP, "198.51.100.10"),
+        ]);
+        let resolve = |header| {
+            proxies(&["10.0.0.0/8"])
+                .with_header(header)
+                .resolve(ip("10.0.0.1"), &h)
+        };
+        assert_eq!(resolve(ProxyHeader::Forwarded), ip("2001:db8::7"));
+        assert_eq!(resolve(ProxyHeader::XForwardedFor), ip("198.51.100.9"));
+        assert_eq!(resolve(ProxyHeader::XRealIp), ip("198.51.100.10"));
+
+        // A client-sent Forwarded can't override what the proxy appended
+        let proxies = proxies(&["10.0.0.0/8"]);
+        let h = headers(&[
+            (FORWARDED, "for=192.0.2.66"),
+            (X_FORWARDED_FOR, "203.0.113.7"),
+        ]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &h), ip("203.0.113.7"));
+
+        // The configured header is missing: the proxy itself is the client
+        let h = headers(&[(X_REAL_IP, "198.51.100.10")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &h), ip("10.0.0.1"));
+
+        // No forwarding headers: the proxy itself is the client
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &HeaderMap::new()),
+            ip("10.0.0.1")
+        );
+    }
+}
@@ -0,0 +1,172 @@
+// src/middleware/idempotency.rs
+use crate::config::RouteGroup;
+use crate::error::{ApiError, ProblemDetails};
+use crate::idempotency::{Claim, IdempotencyStore, StoredResponse, fingerprint};
+use crate::middleware::client_ip::client_key;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+/// Set on responses that were replayed from the store.
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// Request bodies are buffered to fingerprint them; same bound as axum's
+/// default body limit.
+pub const IDEMPOTENCY_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Longest accepted key; UUIDs and similar fit comfortably.
+const MAX_KEY_LEN: usize = 255;
+
+/// Methods that change state. GET, HEAD and OPTIONS are idempotent already.
+fn is_unsafe(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Outcomes worth replaying. Server errors, timeouts and rate limiting are
+/// transient, so a retry should run the request again instead.
+fn is_final(status: StatusCode) -> bool {
+    !status.is_server_error()
+        && status != StatusCode::REQUEST_TIMEOUT
+        && status != StatusCode::TOO_MANY_REQUESTS
+}
+
+fn valid_key(key: &str) -> bool {
+    (1..=MAX_KEY_LEN).contains(&key.len()) && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Honours `Idempotency-Key` on unsafe methods: the first request with a key
+/// runs and its response is stored; retries with the same key and request
+/// get that response back (with `Idempotent-Replayed: true`). Reusing a key
+/// for a different request is 422, and a retry racing the first attempt is
+/// 409. Keys are per client. `/auth` routes are left alone, since their
+/// responses carry credentials that should not sit in a table.
+pub async fn idempotency(
+    State(store): State<IdempotencyStore>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !is_unsafe(req.method()) || RouteGroup::of(req.uri().path()) == RouteGroup::Auth {
+        return next.run(req).await;
+    }
+    let Some(key) = req.headers().get(&IDEMPOTENCY_KEY) else {
+        return next.run(req).await;
+    };
+    let Some(key) = key
+        .to_str()
+        .ok()
+        .filter(|k| valid_key(k))
+        .map(str::to_owned)
+    else {
+        return ProblemDetails::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_idempotency_key",
+            format!("Idempotency-Key must be 1 to {MAX_KEY_LEN} visible ASCII characters"),
+        )
+        .into_response();
+    };
+    let client = client_key(req.extensions());
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = to_bytes(body, IDEMPOTENCY_BODY_LIMIT).await else {
+        return ProblemDetails::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            format!("Request body exceeds {IDEMPOTENCY_BODY_LIMIT} bytes"),
+        )
+        .into_response();
+    };
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| parts.uri.path(), |pq| pq.as_str());
+    let fingerprint = fingerprint(parts.method.as_str(), path_and_query, &bytes);
+
+    match store.claim(&client, &key, &fingerprint).await {
+        Ok(Claim::Acquired) => {}
+        Ok(Claim::Replay(stored)) => {
+            let mut res = stored.into_response();
+            res.headers_mut()
+                .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+            return res;
+        }
+        Ok(Claim::Mismatch) => {
+            return ProblemDetails::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "idempotency_key_reused",
+                "This Idempotency-Key was already used for a different request",
+            )
+            .into_response();
+        }
+        Ok(Claim::InFlight) => {
+            let mut res = ProblemDetails::new(
+                StatusCode::CONFLICT,
+                "idempotency_key_in_flight",
+                "A request with this Idempotency-Key is still being processed",
+            )
+            .into_response();
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(1));
+            return res;
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "failed to claim idempotency key");
+            return ApiError::Database(e).into_response();
+        }
+    }
+
+    let res = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    if !is_final(res.status()) {
+        if let Err(e) = store.release(&client, &key).await {
+            tracing::error!(error = %e, "failed to release idempotency key");
+        }
+        return res;
+    }
+    let (parts, body) = res.into_parts();
+    match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => {
+            let stored = StoredResponse::capture(&parts, &bytes);
+            if let Err(e) = store.complete(&client, &key, &fingerprint, &stored).await {
+                // The request did happen; a retry will see 409 until the lease runs out
+                tracing::error!(error = %e, "failed to store idempotent response");
+            }
+            Response::from_parts(parts, Body::from(bytes))
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "failed to read response body");
+            if let Err(e) = store.release(&client, &key).await {
+                tracing::error!(error = %e, "failed to release idempotency key");
+            }
+            ApiError::Internal(anyhow::anyhow!("response body failed")).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_bounded_visible_ascii() {
+        assert!(valid_key("9f1c2d4e-8a7b-4c3d-9e2f-1a2b3c4d5e6f"));
+        assert!(!valid_key(""));
+        assert!(!valid_key("has space"));
+        assert!(!valid_key(&"k".repeat(MAX_KEY_LEN + 1)));
+    }
+
+    #[test]
+    fn transient_failures_are_not_replayed() {
+        assert!(is_final(StatusCode::CREATED));
+        assert!(is_final(StatusCode::UNPROCESSABLE_ENTITY));
+        assert!(!is_final(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_final(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_final(StatusCode::TOO_MANY_REQUESTS));
+    }
+}
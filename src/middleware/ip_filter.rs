@@ -0,0 +1,194 @@
+// src/middleware/ip_filter.rs
+use crate::config::{IpFilterConfig, RouteGroup};
+use crate::error::ProblemDetails;
+use crate::middleware::{ClientIp, Metrics};
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+
+/// Why [`IpFilter::check`] refused a request; also the `reason` metric label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpRejection {
+    /// The address is in the group's denylist.
+    Denied,
+    /// The group has an allowlist and the address is not in it.
+    NotAllowed,
+    /// The group has rules but the client address could not be resolved.
+    UnknownAddress,
+}
+
+impl IpRejection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IpRejection::Denied => "denied",
+            IpRejection::NotAllowed => "not_allowed",
+            IpRejection::UnknownAddress => "unknown_address",
+        }
+    }
+}
+
+/// Per-route-group allow/deny lists, shared by every clone so that
+/// [`IpFilter::reload`] takes effect on the running router.
+#[derive(Clone, Debug, Default)]
+pub struct IpFilter {
+    rules: Arc<RwLock<Arc<IpFilterConfig>>>,
+}
+
+impl IpFilter {
+    pub fn new(cfg: IpFilterConfig) -> Self {
+        Self {
+            rules: Arc::new(RwLock::new(Arc::new(cfg))),
+        }
+    }
+
+    /// Swaps in new lists; requests already past the filter are unaffected.
+    pub fn reload(&self, cfg: IpFilterConfig) {
+        for group in RouteGroup::ALL {
+            if let Some(rules) = cfg.rules.get(&group) {
+                tracing::info!(
+                    %group,
+                    allow = rules.allow.len(),
+                    deny = rules.deny.len(),
+                    "ip filter rules loaded"
+                );
+            }
+        }
+        *self.rules.write().expect("ip filter poisoned") = Arc::new(cfg);
+    }
+
+    pub fn current(&self) -> Arc<IpFilterConfig> {
+        self.rules.read().expect("ip filter poisoned").clone()
+    }
+
+    /// Deny entries win over allow entries. Groups without rules admit
+    /// everyone, even when the address is unknown.
+    pub fn check(&self, group: RouteGroup, ip: Option<IpAddr>) -> Result<(), IpRejection> {
+        let cfg = self.current();
+        let Some(rules) = cfg.rules.get(&group) else {
+            return Ok(());
+        };
+        let Some(ip) = ip else {
+            return Err(IpRejection::UnknownAddress);
+        };
+        if rules.deny.iter().any(|net| net.contains(&ip)) {
+            Err(IpRejection::Denied)
+        } else if !rules.allow.is_empty() && !rules.allow.iter().any(|net| net.contains(&ip)) {
+            Err(IpRejection::NotAllowed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Refuses requests whose [`ClientIp`] the route group's lists exclude with
+/// a 403 `ip_forbidden` problem. Each rejection is logged and counted in
+/// `http_ip_rejections_total`.
+pub async fn filter_ip(
+    State((filter, metrics)): State<(IpFilter, Option<Metrics>)>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let group = RouteGroup::of(req.uri().path());
+    let ip = ClientIp::from_extensions(req.extensions()).map(|ClientIp(ip)| ip);
+    match filter.check(group, ip) {
+        Ok(()) => next.run(req).await,
+        Err(reason) => {
+            tracing::warn!(
+                %group,
+                client_ip = ?ip,
+                reason = reason.as_str(),
+                path = %req.uri().path(),
+                "request refused by ip filter"
+            );
+            if let Some(metrics) = &metrics {
+                metrics.observe_ip_rejection(group.as_str(), reason.as_str());
+            }
+            ProblemDetails::new(
+                StatusCode::FORBIDDEN,
+                "ip_forbidden",
+                "Requests from this network are not allowed here",
+            )
+            .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IpRules;
+    use axum::{Router, body::Body, middleware::from_fn_with_state, routing::get};
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    fn admin_only_from(allow: &[&str], deny: &[&str]) -> IpFilterConfig {
+        let nets = |list: &[&str]| list.iter().map(|n| n.parse().unwrap()).collect();
+        IpFilterConfig {
+            rules: HashMap::from([(
+                RouteGroup::Admin,
+                IpRules {
+                    allow: nets(allow),
+                    deny: nets(deny),
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let filter = IpFilter::new(admin_only_from(&["10.0.0.0/8"], &["10.9.0.0/16"]));
+        let ip = |s: &str| Some(s.parse().unwrap());
+
+        assert_eq!(filter.check(RouteGroup::Admin, ip("10.1.2.3")), Ok(()));
+        assert_eq!(
+            filter.check(RouteGroup::Admin, ip("10.9.0.1")),
+            Err(IpRejection::Denied)
+        );
+        assert_eq!(
+            filter.check(RouteGroup::Admin, ip("192.0.2.1")),
+            Err(IpRejection::NotAllowed)
+        );
+        assert_eq!(
+            filter.check(RouteGroup::Admin, None),
+            Err(IpRejection::UnknownAddress)
+        );
+        // Other groups are unrestricted
+        assert_eq!(filter.check(RouteGroup::Api, ip("192.0.2.1")), Ok(()));
+        assert_eq!(filter.check(RouteGroup::Api, None), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn rejects_and_counts_then_follows_reloads() {
+        let filter = IpFilter::new(admin_only_from(&["10.0.0.0/8"], &[]));
+        let metrics = Metrics::new();
+        let app = Router::new()
+            .route("/admin/users", get(|| async { "ok" }))
+            .layer(from_fn_with_state(
+                (filter.clone(), Some(metrics.clone())),
+                filter_ip,
+            ));
+        let call = || {
+            let mut req = Request::get("/admin/users").body(Body::empty()).unwrap();
+            req.extensions_mut()
+                .insert(ClientIp("192.0.2.1".parse().unwrap()));
+            app.clone().oneshot(req)
+        };
+
+        let res = call().await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(
+            metrics
+                .render()
+                .contains(r#"http_ip_rejections_total{group="admin",reason="not_allowed"} 1"#)
+        );
+
+        filter.reload(admin_only_from(&["192.0.2.0/24"], &[]));
+        let res = call().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
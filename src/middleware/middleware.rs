@@ -1,10 +1,26 @@
 // src/middleware/middleware.rs
-use crate::config::{CorsPolicy, ServerConfig};
-use crate::middleware::MiddlewareSuite;
+use crate::audit::AuditLog;
+use crate::auth::{Authenticator, CSRF_HEADER, authenticate};
+use crate::config::{AppState, CorsPolicy, ServerConfig};
+use crate::error::ProblemDetails;
+use crate::idempotency::IdempotencyStore;
+use crate::middleware::{
+    IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED, IpFilter, Metrics, MiddlewareSuite, RATELIMIT_LIMIT,
+    RATELIMIT_POLICY, RATELIMIT_REMAINING, RATELIMIT_RESET, TrustedProxies, filter_ip, idempotency,
+    limit_auth_failures, problem_details, rate_limit, record_audit, resolve_client_ip,
+    track_metrics,
+};
+use crate::ratelimit::RateLimiter;
 
-use axum::http::{HeaderName, HeaderValue, Method, header};
+use axum::{
+    BoxError, Router,
+    body::Body,
+    error_handling::HandleErrorLayer,
+    http::{HeaderName, HeaderValue, Method, Request, StatusCode, header},
+    middleware::from_fn_with_state,
+};
 use std::time::Duration;
-use tower::timeout::TimeoutLayer;
+use tower::timeout::{TimeoutLayer, error::Elapsed};
 use tower::{Layer, ServiceBuilder};
 use tower_http::{
     cors::{AllowOrigin, Any, CorsLayer},
@@ -13,13 +29,18 @@ use tower_http::{
     trace::{DefaultOnFailure, DefaultOnRequest, DefaultOnResponse, TraceLayer},
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Middleware {
-    #[allow(dead_code)]
     request_id_header: HeaderName,
-    #[allow(dead_code)]
     timeout: Duration,
     cors: CorsPolicy,
+    trusted_proxies: TrustedProxies,
+    metrics: Option<Metrics>,
+    auth: Option<Authenticator>,
+    audit: Option<AuditLog>,
+    rate_limiter: Option<RateLimiter>,
+    ip_filter: Option<IpFilter>,
+    idempotency: Option<IdempotencyStore>,
 }
 
 impl From<&ServerConfig> for Middleware {
@@ -28,46 +49,173 @@ impl From<&ServerConfig> for Middleware {
             request_id_header: cfg.request_id_header.clone(),
             timeout: cfg.timeout,
             cors: cfg.cors.clone(),
+            trusted_proxies: TrustedProxies::new(cfg.trusted_proxies.iter().copied())
+                .with_header(cfg.trusted_proxy_header),
+            metrics: None,
+            auth: None,
+            audit: None,
+            rate_limiter: None,
+            ip_filter: None,
+            idempotency: None,
+        }
+    }
+}
+
+impl From<&AppState> for Middleware {
+    /// Like `From<&ServerConfig>`, but also records into the state's metrics
+    /// registry, authenticates with the state's keys, writes the audit log and
+    /// enforces the configured rate limits, (reloadable) IP lists and
+    /// idempotency keys.
+    fn from(state: &AppState) -> Self {
+        Self {
+            rate_limiter: state.rate_limiter.clone(),
+            ip_filter: Some(state.ip_filter.clone()),
+            idempotency: state.idempotency.clone(),
+            metrics: Some(state.metrics.clone()),
+            auth: Some(state.auth.clone()),
+            audit: Some(state.audit.clone()),
+            ..Self::from(&state.cfg)
         }
     }
 }
 
 impl MiddlewareSuite for Middleware {
-    fn trace<S>(&self) -> impl Layer<S> + Clone {
-        TraceLayer::new_for_http()
-            .on_request(DefaultOnRequest::new())
-            .on_response(DefaultOnResponse::new())
-            .on_failure(DefaultOnFailure::new())
+    fn trace(&self, router: Router) -> Router {
+        router.layer(
+            TraceLayer::new_for_http()
+                .make_span_with(make_request_span)
+                .on_request(DefaultOnRequest::new())
+                .on_response(DefaultOnResponse::new())
+                .on_failure(DefaultOnFailure::new()),
+        )
     }
-    fn normalize_path<S>(&self) -> impl Layer<S> + Clone {
-        NormalizePathLayer::trim_trailing_slash()
+    fn normalize_path(&self, router: Router) -> Router {
+        // `Router::layer` runs after route matching, so the path has to be
+        // fixed up by a service wrapping the whole router instead.
+        Router::new().fallback_service(NormalizePathLayer::trim_trailing_slash().layer(router))
     }
-    fn request_id_stack<S>(&self) -> impl Layer<S> + Clone {
+    fn request_id_stack(&self, router: Router) -> Router {
         let h = self.request_id_header.clone();
-        ServiceBuilder::new()
-            .layer(SetRequestIdLayer::new(h.clone(), MakeRequestUuid))
-            .layer(PropagateRequestIdLayer::new(h))
-            .into_inner()
+        router.layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::new(h.clone(), MakeRequestUuid))
+                .layer(PropagateRequestIdLayer::new(h)),
+        )
+    }
+    fn timeout(&self, router: Router) -> Router {
+        router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(handle_timeout_error))
+                .layer(TimeoutLayer::new(self.timeout)),
+        )
+    }
+    fn problem_details(&self, router: Router) -> Router {
+        router.layer(from_fn_with_state(
+            self.request_id_header.clone(),
+            problem_details,
+        ))
+    }
+    fn metrics(&self, router: Router) -> Router {
+        match &self.metrics {
+            Some(metrics) => router.layer(from_fn_with_state(metrics.clone(), track_metrics)),
+            None => router,
+        }
+    }
+    fn client_ip(&self, router: Router) -> Router {
+        router.layer(from_fn_with_state(
+            self.trusted_proxies.clone(),
+            resolve_client_ip,
+        ))
+    }
+    fn authenticate(&self, router: Router) -> Router {
+        match &self.auth {
+            Some(auth) => router.layer(from_fn_with_state(auth.clone(), authenticate)),
+            None => router,
+        }
+    }
+    fn rate_limit(&self, router: Router) -> Router {
+        match &self.rate_limiter {
+            Some(limiter) => router.layer(from_fn_with_state(limiter.clone(), rate_limit)),
+            None => router,
+        }
     }
-    fn timeout<S>(&self) -> impl Layer<S> + Clone {
-        TimeoutLayer::new(self.timeout)
+    fn limit_auth_failures(&self, router: Router) -> Router {
+        match &self.rate_limiter {
+            Some(limiter) => router.layer(from_fn_with_state(limiter.clone(), limit_auth_failures)),
+            None => router,
+        }
+    }
+    fn idempotency(&self, router: Router) -> Router {
+        match &self.idempotency {
+            Some(store) => router.layer(from_fn_with_state(store.clone(), idempotency)),
+            None => router,
+        }
+    }
+    fn ip_filter(&self, router: Router) -> Router {
+        match &self.ip_filter {
+            Some(filter) => router.layer(from_fn_with_state(
+                (filter.clone(), self.metrics.clone()),
+                filter_ip,
+            )),
+            None => router,
+        }
+    }
+    fn audit(&self, router: Router) -> Router {
+        match &self.audit {
+            Some(audit) => router.layer(from_fn_with_state(
+                (audit.clone(), self.request_id_header.clone()),
+                record_audit,
+            )),
+            None => router,
+        }
     }
     fn cors_layer(&self) -> Option<CorsLayer> {
         match &self.cors {
             CorsPolicy::Disabled => None,
+            // Any origin, but never with credentials: only an explicit allow-list gets those
             CorsPolicy::Permissive => Some(CorsLayer::new().with_defaults().allow_origin(Any)),
             CorsPolicy::Allow(list) => {
                 let allow_values: Vec<HeaderValue> = list.clone();
                 Some(
                     CorsLayer::new()
                         .with_defaults()
-                        .allow_origin(AllowOrigin::list(allow_values)),
+                        .allow_origin(AllowOrigin::list(allow_values))
+                        .allow_credentials(true),
                 )
             }
         }
     }
 }
 
+/// Like tower-http's default span, at INFO so it shows up under the default
+/// filter, plus a `client_ip` field filled in by `resolve_client_ip`.
+fn make_request_span(req: &Request<Body>) -> tracing::Span {
+    tracing::info_span!(
+        "request",
+        method = %req.method(),
+        uri = %req.uri(),
+        version = ?req.version(),
+        client_ip = tracing::field::Empty,
+    )
+}
+
+async fn handle_timeout_error(err: BoxError) -> ProblemDetails {
+    if err.is::<Elapsed>() {
+        ProblemDetails::new(
+            StatusCode::REQUEST_TIMEOUT,
+            "request_timeout",
+            "The request took too long to process",
+        )
+    } else {
+        tracing::error!(error = %err, "unhandled middleware error");
+        ProblemDetails::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "service_unavailable",
+            "The service is temporarily unavailable",
+        )
+    }
+}
+
 trait CorsDefaultsExt {
     fn with_defaults(self) -> Self;
 }
@@ -75,7 +223,7 @@ impl CorsDefaultsExt for CorsLayer {
     fn with_defaults(self) -> Self {
         self.allow_methods(default_methods())
             .allow_headers(default_headers())
-            .allow_credentials(true)
+            .expose_headers(exposed_headers())
     }
 }
 
@@ -90,6 +238,138 @@ fn default_methods() -> [Method; 5] {
     ]
 }
 #[inline]
-fn default_headers() -> [HeaderName; 2] {
-    [header::CONTENT_TYPE, header::AUTHORIZATION]
+fn default_headers() -> [HeaderName; 6] {
+    [
+        header::CONTENT_TYPE,
+        header::AUTHORIZATION,
+        CSRF_HEADER,
+        IDEMPOTENCY_KEY,
+        header::IF_MATCH,
+        header::IF_NONE_MATCH,
+    ]
+}
+
+/// Response headers browser code may read beyond the CORS-safelisted ones.
+#[inline]
+fn exposed_headers() -> [HeaderName; 7] {
+    [
+        header::ETAG,
+        header::RETRY_AFTER,
+        IDEMPOTENT_REPLAYED,
+        RATELIMIT_LIMIT,
+        RATELIMIT_REMAINING,
+        RATELIMIT_RESET,
+        RATELIMIT_POLICY,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get};
+    use tower::ServiceExt;
+
+    fn middleware(timeout: Duration) -> Middleware {
+        Middleware::from(&ServerConfig {
+            timeout,
+            cors: CorsPolicy::Disabled,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn slow_handlers_time_out_with_408() {
+        let mw = middleware(Duration::from_millis(10));
+        let router = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "done"
+            }),
+        );
+        let app = mw.apply(router);
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/slow")
+                    .header("x-request-id", "slow-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(res.headers().get("x-request-id").unwrap(), "slow-1");
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], "request_timeout");
+        assert_eq!(problem["request_id"], "slow-1");
+    }
+
+    async fn cors_request(config: &ServerConfig) -> axum::response::Response {
+        let mw = Middleware::from(config);
+        let app = mw.apply(Router::new().route("/todos", get(|| async { "ok" })));
+        app.oneshot(
+            Request::builder()
+                .uri("/todos")
+                .header("origin", "https://app.example.com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn permissive_cors_never_allows_credentials() {
+        let res = cors_request(&ServerConfig::default()).await;
+        assert_eq!(
+            res.headers().get("access-control-allow-origin").unwrap(),
+            "*"
+        );
+        assert!(
+            res.headers()
+                .get("access-control-allow-credentials")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn allow_listed_origins_get_credentials() {
+        let config = ServerConfig {
+            cors: CorsPolicy::Allow(vec![HeaderValue::from_static("https://app.example.com")]),
+            ..ServerConfig::default()
+        };
+        let res = cors_request(&config).await;
+        assert_eq!(
+            res.headers().get("access-control-allow-origin").unwrap(),
+            "https://app.example.com"
+        );
+        assert_eq!(
+            res.headers()
+                .get("access-control-allow-credentials")
+                .unwrap(),
+            "true"
+        );
+    }
+
+    #[tokio::test]
+    async fn trailing_slash_is_normalized_before_routing() {
+        let app = middleware(Duration::from_secs(1))
+            .apply(Router::new().route("/todos", get(|| async { "ok" })));
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/todos/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
 }
@@ -1,12 +1,95 @@
 // src/middleware/middleware_suite.rs
-use tower::Layer;
+use axum::Router;
 use tower_http::cors::CorsLayer;
 
-#[allow(dead_code)]
-pub trait MiddlewareSuite {
-    fn trace<S>(&self) -> impl Layer<S> + Clone;
-    fn normalize_path<S>(&self) -> impl Layer<S> + Clone;
-    fn request_id_stack<S>(&self) -> impl Layer<S> + Clone;
-    fn timeout<S>(&self) -> impl Layer<S> + Clone;
+/// The HTTP middleware stack `Server::router` wraps around its routes.
+///
+/// Each hook takes the router built so far and returns it wrapped in that
+/// concern's layers. [`MiddlewareSuite::apply`] composes them, innermost first:
+///
+///  1. `timeout`             – aborts slow handlers with a 408 problem response
+///  2. `idempotency`         – replays stored responses for retried Idempotency-Keys
+///  3. `rate_limit`          – per-client quotas; needs the principal, so inside auth
+///  4. `authenticate`        – resolves the caller's `Principal`, rejects bad credentials
+///  5. `limit_auth_failures` – charges rejected credentials to the client IP's quota
+///  6. `audit`               – records mutating and admin requests, incl. auth failures
+///  7. `ip_filter`           – per-route-group network allow/deny lists
+///  8. `problem_details`     – stamps the request id onto problem+json bodies
+///  9. `request_id_stack`    – sets/propagates the request id header
+/// 10. `metrics`             – RED metrics labelled by matched route template
+/// 11. `client_ip`           – resolves the real client address behind trusted proxies
+/// 12. `trace`               – request span covering everything below it
+/// 13. `cors_layer`          – optional, decorates every response incl. errors
+/// 14. `normalize_path`      – outermost, so routing sees the canonical path
+///
+/// Downstream crates can implement this trait (often by delegating to
+/// [`Middleware`](crate::middleware::Middleware)) and pass it to
+/// `Server::with_middleware`.
+pub trait MiddlewareSuite: Clone + Send + Sync + 'static {
+    fn trace(&self, router: Router) -> Router;
+    fn normalize_path(&self, router: Router) -> Router;
+    fn request_id_stack(&self, router: Router) -> Router;
+    fn timeout(&self, router: Router) -> Router;
+    fn problem_details(&self, router: Router) -> Router;
     fn cors_layer(&self) -> Option<CorsLayer>;
+
+    /// Request metrics. Defaults to no instrumentation.
+    fn metrics(&self, router: Router) -> Router {
+        router
+    }
+
+    /// Credential checks. Defaults to none: every request is anonymous.
+    fn authenticate(&self, router: Router) -> Router {
+        router
+    }
+
+    /// Per-client request quotas. Defaults to unlimited.
+    fn rate_limit(&self, router: Router) -> Router {
+        router
+    }
+
+    /// Quota for requests `authenticate` refused. Defaults to unlimited.
+    fn limit_auth_failures(&self, router: Router) -> Router {
+        router
+    }
+
+    /// Network allow/deny lists. Defaults to none: every address is admitted.
+    fn ip_filter(&self, router: Router) -> Router {
+        router
+    }
+
+    /// Client address resolution. Defaults to none: consumers fall back to
+    /// the TCP peer.
+    fn client_ip(&self, router: Router) -> Router {
+        router
+    }
+
+    /// Idempotency-Key replay. Defaults to none: the header is ignored.
+    fn idempotency(&self, router: Router) -> Router {
+        router
+    }
+
+    /// Audit trail. Defaults to none.
+    fn audit(&self, router: Router) -> Router {
+        router
+    }
+
+    fn apply(&self, router: Router) -> Router {
+        let router = self.timeout(router);
+        let router = self.idempotency(router);
+        let router = self.rate_limit(router);
+        let router = self.authenticate(router);
+        let router = self.limit_auth_failures(router);
+        let router = self.audit(router);
+        let router = self.ip_filter(router);
+        let router = self.problem_details(router);
+        let router = self.request_id_stack(router);
+        let router = self.metrics(router);
+        let router = self.client_ip(router);
+        let mut router = self.trace(router);
+        if let Some(cors) = self.cors_layer() {
+            router = router.layer(cors);
+        }
+        self.normalize_path(router)
+    }
 }
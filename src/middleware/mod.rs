@@ -1,6 +1,25 @@
+mod audit;
+mod client_ip;
+mod idempotency;
+mod ip_filter;
+mod metrics;
 #[allow(clippy::module_inception)]
 mod middleware;
 mod middleware_suite;
+mod problem_details;
+mod rate_limit;
 
+pub use audit::{AUDIT_BODY_LIMIT, record_audit};
+pub use client_ip::{
+    ClientIp, FORWARDED, TrustedProxies, X_FORWARDED_FOR, X_REAL_IP, resolve_client_ip,
+};
+pub use idempotency::{IDEMPOTENCY_BODY_LIMIT, IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED, idempotency};
+pub use ip_filter::{IpFilter, IpRejection, filter_ip};
+pub use metrics::{Metrics, track_metrics};
 pub use middleware::Middleware;
 pub use middleware_suite::MiddlewareSuite;
+pub use problem_details::problem_details;
+pub use rate_limit::{
+    RATELIMIT_LIMIT, RATELIMIT_POLICY, RATELIMIT_REMAINING, RATELIMIT_RESET, limit_auth_failures,
+    rate_limit,
+};
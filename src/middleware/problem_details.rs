@@ -0,0 +1,38 @@
+// src/middleware/problem_details.rs
+use crate::error::ProblemDetails;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderName, header},
+    middleware::Next,
+    response::Response,
+};
+
+/// Stamps the request id onto `application/problem+json` responses. Must run
+/// inside the request-id layer so the header is already set on the request.
+pub async fn problem_details(
+    State(request_id_header): State<HeaderName>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let request_id = req
+        .headers()
+        .get(&request_id_header)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+
+    let res = next.run(req).await;
+
+    let (Some(request_id), Some(problem)) = (request_id, res.extensions().get::<ProblemDetails>())
+    else {
+        return res;
+    };
+    let mut problem = problem.clone();
+    problem.request_id = Some(request_id);
+
+    let (mut parts, _) = res.into_parts();
+    let body = serde_json::to_vec(&problem).unwrap_or_default();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.extensions.insert(problem);
+    Response::from_parts(parts, Body::from(body))
+}
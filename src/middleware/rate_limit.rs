@@ -0,0 +1,169 @@
+// src/middleware/rate_limit.rs
+use crate::auth::CredentialsRejected;
+use crate::config::RouteGroup;
+use crate::error::ProblemDetails;
+use crate::middleware::client_ip::client_key;
+use crate::ratelimit::{Decision, RateLimiter};
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::Duration;
+
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+pub const RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+/// Charges the request to its route group's quota and adds `RateLimit-*`
+/// headers; over quota is 429 with `Retry-After`. Clients are keyed by
+/// principal (API key, user, admin) when `authenticate` found one, otherwise
+/// by [`ClientIp`](crate::middleware::ClientIp). If the store fails the request is let through.
+pub async fn rate_limit(State(limiter): State<RateLimiter>, req: Request, next: Next) -> Response {
+    let group = RouteGroup::of(req.uri().path());
+    let client = client_key(req.extensions());
+
+    let decision = match limiter.check(group, &client).await {
+        Ok(Some(decision)) => decision,
+        Ok(None) => return next.run(req).await,
+        Err(e) => {
+            tracing::error!(error = %e, %group, "rate limit store failed; allowing request");
+            return next.run(req).await;
+        }
+    };
+
+    let mut res = if decision.allowed {
+        next.run(req).await
+    } else {
+        tracing::info!(%group, %client, "rate limit exceeded");
+        too_many_requests(&decision)
+    };
+    insert_headers(res.headers_mut(), &limiter, group, &decision);
+    res
+}
+
+/// Runs outside `authenticate`, which refuses bad credentials before
+/// [`rate_limit`] could count them: each refusal is charged to the client
+/// IP's quota for the route group (the budget anonymous requests from that
+/// address draw on), and an address that has used it up gets 429 before its
+/// credentials are looked at again.
+pub async fn limit_auth_failures(
+    State(limiter): State<RateLimiter>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let group = RouteGroup::of(req.uri().path());
+    // No principal yet this far out, so this is always the `ip:` key
+    let client = client_key(req.extensions());
+
+    match limiter.peek(group, &client).await {
+        Ok(Some(decision)) if !decision.allowed => {
+            tracing::info!(%group, %client, "rate limit exceeded by failed authentication");
+            let mut res = too_many_requests(&decision);
+            insert_headers(res.headers_mut(), &limiter, group, &decision);
+            return res;
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!(error = %e, %group, "rate limit store failed; allowing request"),
+    }
+
+    let res = next.run(req).await;
+    if res.extensions().get::<CredentialsRejected>().is_some()
+        && let Err(e) = limiter.check(group, &client).await
+    {
+        tracing::error!(error = %e, %group, "failed to charge rejected credentials");
+    }
+    res
+}
+
+fn too_many_requests(decision: &Decision) -> Response {
+    let mut res = ProblemDetails::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limited",
+        "Too many requests; retry later",
+    )
+    .into_response();
+    let retry_after = decision.retry_after.unwrap_or_default();
+    res.headers_mut().insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(ceil_secs(retry_after)),
+    );
+    res
+}
+
+fn insert_headers(
+    headers: &mut HeaderMap,
+    limiter: &RateLimiter,
+    group: RouteGroup,
+    decision: &Decision,
+) {
+    if let Some(quota) = limiter.quota(group) {
+        let policy = format!("{};w={}", quota.limit, quota.period.as_secs());
+        if let Ok(policy) = HeaderValue::from_str(&policy) {
+            headers.insert(RATELIMIT_POLICY, policy);
+        }
+    }
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(
+        RATELIMIT_RESET,
+        HeaderValue::from(ceil_secs(decision.reset)),
+    );
+}
+
+/// Whole seconds, rounded up so clients never retry too early.
+fn ceil_secs(d: Duration) -> u64 {
+    d.as_secs() + u64::from(d.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RouteGroup;
+    use crate::ratelimit::{MemoryStore, Quota};
+    use axum::{Router, body::Body, middleware::from_fn_with_state, routing::post};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn rejects_over_quota_with_headers() {
+        let limiter = RateLimiter::with_store(
+            HashMap::from([(RouteGroup::Auth, Quota::new(2, Duration::from_secs(60)))]),
+            Arc::new(MemoryStore::new()),
+        );
+        let app = Router::new()
+            .route("/auth/token", post(|| async { "ok" }))
+            .route("/todos", post(|| async { "ok" }))
+            .layer(from_fn_with_state(limiter, rate_limit));
+        let call = |uri: &'static str| {
+            app.clone()
+                .oneshot(Request::post(uri).body(Body::empty()).unwrap())
+        };
+
+        let res = call("/auth/token").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[&RATELIMIT_LIMIT], "2");
+        assert_eq!(res.headers()[&RATELIMIT_REMAINING], "1");
+        assert_eq!(res.headers()[&RATELIMIT_POLICY], "2;w=60");
+        call("/auth/token").await.unwrap();
+
+        let res = call("/auth/token").await.unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()[header::RETRY_AFTER], "30");
+        assert_eq!(res.headers()[&RATELIMIT_REMAINING], "0");
+
+        // Groups without a quota are untouched
+        let res = call("/todos").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(&RATELIMIT_LIMIT).is_none());
+    }
+
+    #[test]
+    fn rounds_seconds_up() {
+        assert_eq!(ceil_secs(Duration::from_millis(1)), 1);
+        assert_eq!(ceil_secs(Duration::from_secs(2)), 2);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::auth::Scope;
+use crate::validation::{Validate, ValidationErrors, Validator, trim};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const API_KEY_NAME_MAX_CHARS: usize = 100;
+
+/// Body of `POST /admin/api-keys`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKey {
+    #[serde(deserialize_with = "trim")]
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// RFC 3339; the key stops working at this instant.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Restricts the key to this user's todos.
+    #[serde(default)]
+    pub user_id: Option<i64>,
+}
+
+impl Validate for CreateApiKey {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut v = Validator::new();
+        v.field("name", &self.name)
+            .not_blank()
+            .max_chars(API_KEY_NAME_MAX_CHARS);
+        if self.scopes.is_empty() {
+            v.reject("scopes", "empty", "must list at least one scope");
+        }
+        if self.expires_at.is_some_and(|at| at <= Utc::now()) {
+            v.reject("expires_at", "in_past", "must be in the future");
+        }
+        v.finish()
+    }
+}
+
+/// An API key as listed by the admin endpoints. The secret itself is never
+/// stored, so it can't be listed.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub user_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Response of `POST /admin/api-keys`: the listing plus the one-time secret.
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn rejects_empty_scopes_and_past_expiry() {
+        let body = CreateApiKey {
+            name: "ci".into(),
+            scopes: vec![],
+            expires_at: Some(Utc::now() - Duration::hours(1)),
+            user_id: None,
+        };
+        let fields: Vec<_> = body
+            .validate()
+            .unwrap_err()
+            .0
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, ["scopes", "expires_at"]);
+    }
+}
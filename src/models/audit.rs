@@ -0,0 +1,61 @@
+use crate::audit::to_hex;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Query of `GET /admin/audit-events`. Every filter is optional and they
+/// combine with AND; pages go newest first, continued with `before`.
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditEventQuery {
+    /// `admin`, `user:<id>` or `api_key:<id>`.
+    pub principal: Option<String>,
+    pub method: Option<String>,
+    /// Route template, e.g. `/todos/{id}`.
+    pub route: Option<String>,
+    pub status: Option<i32>,
+    pub request_id: Option<String>,
+    /// RFC 3339, inclusive.
+    pub since: Option<DateTime<Utc>>,
+    /// RFC 3339, exclusive.
+    pub until: Option<DateTime<Utc>>,
+    /// Only events with a smaller id; pass the previous page's `next_before`.
+    pub before: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// A stored audit event. Hashes and digests are hex encoded.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub principal: Option<String>,
+    pub method: String,
+    pub route: Option<String>,
+    pub path: String,
+    pub status: i32,
+    pub source_ip: Option<String>,
+    pub request_id: Option<String>,
+    #[serde(serialize_with = "hex_opt")]
+    pub body_digest: Option<Vec<u8>>,
+    #[serde(serialize_with = "hex")]
+    pub prev_hash: Vec<u8>,
+    #[serde(serialize_with = "hex")]
+    pub hash: Vec<u8>,
+}
+
+/// One page of `GET /admin/audit-events`.
+#[derive(Debug, Serialize)]
+pub struct AuditPage {
+    pub items: Vec<AuditEvent>,
+    pub next_before: Option<i64>,
+}
+
+fn hex<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&to_hex(bytes))
+}
+
+fn hex_opt<S: Serializer>(bytes: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+    match bytes {
+        Some(bytes) => hex(bytes, s),
+        None => s.serialize_none(),
+    }
+}
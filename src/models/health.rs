@@ -0,0 +1,71 @@
+// src/models/health.rs
+use serde::Serialize;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_SHA: &str = env!("GIT_SHA");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentStatus {
+    Ok,
+    /// Working, but worth a look (e.g. the pool is exhausted).
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub status: ComponentStatus,
+    pub uptime_secs: u64,
+    pub version: &'static str,
+    pub git_sha: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<ReadinessComponents>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessComponents {
+    pub database: DatabaseCheck,
+    pub migrations: MigrationsCheck,
+    pub pool: PoolCheck,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseCheck {
+    pub status: ComponentStatus,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationsCheck {
+    pub status: ComponentStatus,
+    pub expected: usize,
+    pub applied: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolCheck {
+    pub status: ComponentStatus,
+    pub size: u32,
+    pub idle: usize,
+    pub max: u32,
+    /// Share of `max` connections currently checked out, 0.0..=1.0.
+    pub saturation: f64,
+}
+
+impl ReadinessComponents {
+    /// Only hard dependencies decide readiness; pool pressure just degrades it.
+    pub fn overall(&self) -> ComponentStatus {
+        if self.database.status == ComponentStatus::Down
+            || self.migrations.status == ComponentStatus::Down
+        {
+            ComponentStatus::Down
+        } else if self.pool.status == ComponentStatus::Degraded {
+            ComponentStatus::Degraded
+        } else {
+            ComponentStatus::Ok
+        }
+    }
+}
@@ -1,5 +1,38 @@
+mod api_key;
+mod audit;
+mod health;
+mod series;
 mod server;
+mod session;
+mod tag;
 mod todo;
+mod todo_query;
+mod token;
+mod user;
 
+pub use api_key::{API_KEY_NAME_MAX_CHARS, ApiKey, CreateApiKey, CreatedApiKey};
+pub use audit::{AuditEvent, AuditEventQuery, AuditPage};
+pub use health::{
+    ComponentStatus, DatabaseCheck, GIT_SHA, HealthReport, MigrationsCheck, PoolCheck,
+    ReadinessComponents, VERSION,
+};
+pub(crate) use series::validate_rrule;
+pub use series::{
+    MAX_OCCURRENCES, Occurrence, OccurrenceList, OccurrenceQuery, ScopeQuery, SeriesScope,
+};
 pub use server::Server;
-pub use todo::{CreateTodo, Todo};
+pub use session::{SessionInfo, SessionSummary};
+pub(crate) use tag::validate_tag_list;
+pub use tag::{
+    MAX_TAGS_PER_TODO, MergeTag, TAG_NAME_MAX_CHARS, Tag, TagName, TagRef, dedup_tag_names,
+};
+pub use todo::{CreateTodo, DESCRIPTION_MAX_CHARS, Priority, TITLE_MAX_CHARS, Todo, UpdatedTodo};
+pub use todo_query::{
+    Cursor, DEFAULT_PAGE_LIMIT, DueWindow, ListQueryError, MAX_PAGE_LIMIT, TagMatch, TodoFilter,
+    TodoListQuery, TodoPage, TodoSort, start_of_day,
+};
+pub use token::{OidcCallback, RefreshRequest, TokenResponse};
+pub use user::{
+    LoginRequest, PASSWORD_MAX_CHARS, PASSWORD_MIN_CHARS, RegisterUser, USERNAME_MAX_CHARS,
+    USERNAME_MIN_CHARS, UpdateProfile, UpdateRole, User,
+};
@@ -0,0 +1,111 @@
+// src/models/series.rs
+use super::todo_query::{ListQueryError, parse_due_bound};
+use crate::recurrence::{RRULE_MAX_CHARS, RRule};
+use crate::validation::Validator;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Most occurrences one `GET /todos/{id}/occurrences` returns.
+pub const MAX_OCCURRENCES: usize = 500;
+
+/// Which occurrences of a recurring todo a `PATCH` or `DELETE` applies to.
+/// Ignored for todos that don't repeat.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesScope {
+    /// Just this occurrence.
+    #[default]
+    This,
+    /// This occurrence and every later one; the series is split here.
+    Following,
+    /// The whole series.
+    All,
+}
+
+/// `?scope=` on `PATCH` and `DELETE /todos/{id}`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ScopeQuery {
+    #[serde(default)]
+    pub scope: SeriesScope,
+}
+
+/// Query of `GET /todos/{id}/occurrences`: the half-open range `[from, to)`,
+/// each end an RFC 3339 timestamp or a `YYYY-MM-DD` date, which is read as
+/// midnight in the caller's timezone.
+#[derive(Debug, Deserialize)]
+pub struct OccurrenceQuery {
+    pub from: String,
+    pub to: String,
+}
+
+impl OccurrenceQuery {
+    pub fn range(&self, tz: Tz) -> Result<(DateTime<Utc>, DateTime<Utc>), ListQueryError> {
+        let from = parse_due_bound(&self.from, tz, "from")?;
+        let to = parse_due_bound(&self.to, tz, "to")?;
+        if from >= to {
+            return Err(ListQueryError::EmptyRange);
+        }
+        Ok((from, to))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Occurrence {
+    pub at: DateTime<Utc>,
+    /// The todo standing for this occurrence, once it has been created.
+    pub todo_id: Option<i64>,
+}
+
+/// Body of `GET /todos/{id}/occurrences`.
+#[derive(Debug, Serialize)]
+pub struct OccurrenceList {
+    /// `None` for a todo that doesn't repeat; its due date is the only occurrence.
+    pub series_id: Option<i64>,
+    pub rrule: Option<String>,
+    /// The timezone the series keeps its wall-clock time in.
+    pub timezone: String,
+    pub items: Vec<Occurrence>,
+    /// Whether the range holds more than [`MAX_OCCURRENCES`] occurrences.
+    pub truncated: bool,
+}
+
+/// Checks an `rrule` field: it must be a rule [`RRule`] can expand.
+pub(crate) fn validate_rrule(v: &mut Validator, rrule: &str) {
+    if rrule.chars().count() > RRULE_MAX_CHARS {
+        v.reject(
+            "rrule",
+            "too_long",
+            format!("must be at most {RRULE_MAX_CHARS} characters"),
+        );
+    } else if let Err(e) = rrule.parse::<RRule>() {
+        v.reject("rrule", "invalid_rrule", e.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occurrence_ranges_must_not_be_empty() {
+        let query = |from: &str, to: &str| OccurrenceQuery {
+            from: from.to_owned(),
+            to: to.to_owned(),
+        };
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+        let (from, to) = query("2026-03-01", "2026-04-01T00:00:00Z")
+            .range(tz)
+            .unwrap();
+        assert_eq!(from.to_rfc3339(), "2026-02-28T23:00:00+00:00");
+        assert_eq!(to.to_rfc3339(), "2026-04-01T00:00:00+00:00");
+        assert_eq!(
+            query("2026-04-01", "2026-03-01").range(tz),
+            Err(ListQueryError::EmptyRange)
+        );
+        assert_eq!(
+            query("soon", "2026-03-01").range(tz),
+            Err(ListQueryError::InvalidDate("from"))
+        );
+    }
+}
@@ -1,55 +1,156 @@
 // src/models/server.rs (composition point)
 use crate::middleware::MiddlewareSuite;
 use crate::{
-    config::AppState,
+    auth::{Scope, require_scope},
+    config::{AppState, MetricsEndpoint},
     middleware::Middleware,
-    routes::{create_todo, get_all_todos, health},
+    routes::{
+        create_api_key, create_session, create_tag, create_todo, current_session, delete_session,
+        delete_tag, delete_todo, empty_trash, get_all_todos, get_tag, get_todo, health,
+        list_api_keys, list_audit_events, list_occurrences, list_sessions, list_tags, list_trash,
+        list_users, live, logout, me, merge_tag, metrics, oidc_callback, oidc_login, purge_todo,
+        ready, refresh, register, rename_tag, replace_todo, restore_todo, revoke_api_key,
+        revoke_session, set_user_role, token, update_me, update_todo,
+    },
 };
 use axum::{
     Router,
-    routing::{get, post},
+    handler::Handler,
+    routing::{delete, get, post, put},
 };
-use tower::ServiceBuilder;
-use tower_http::normalize_path::NormalizePathLayer;
-use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
-use tower_http::trace::TraceLayer;
 
 #[derive(Clone)]
-pub struct Server {
+pub struct Server<M = Middleware> {
     pub state: AppState,
-    pub mw: Middleware,
+    pub mw: M,
 }
 
 impl Server {
     pub fn new(state: AppState) -> Self {
-        let mw = Middleware::from(&state.cfg);
+        let mw = Middleware::from(&state);
         Self { state, mw }
     }
+}
 
+impl<M: MiddlewareSuite> Server<M> {
+    /// Swap in a custom middleware stack, e.g. one that wraps [`Middleware`]
+    /// and adds extra layers.
+    pub fn with_middleware<N: MiddlewareSuite>(self, mw: N) -> Server<N> {
+        Server {
+            state: self.state,
+            mw,
+        }
+    }
+
+    /// Protected handlers carry a [`require_scope`] guard; which roles hold
+    /// each scope is decided by the configured `RbacPolicy`.
     pub fn router(&self) -> Router {
-        // Build concrete middleware layers directly from config
-        let request_id_header = self.state.cfg.request_id_header.clone();
-        let request_id_stack = ServiceBuilder::new()
-            .layer(SetRequestIdLayer::new(
-                request_id_header.clone(),
-                MakeRequestUuid,
-            ))
-            .layer(PropagateRequestIdLayer::new(request_id_header))
-            .into_inner();
-
-        let mut router = Router::new()
+        use Scope::*;
+        let mut routes = Router::new()
             .route("/health", get(health))
-            .route("/todos", post(create_todo).get(get_all_todos))
-            .with_state(self.state.clone())
-            // innermost of these
-            .layer(NormalizePathLayer::trim_trailing_slash())
-            .layer(request_id_stack)
-            .layer(TraceLayer::new_for_http()); // outermost of these
+            .route("/health/live", get(live))
+            .route("/health/ready", get(ready))
+            .route("/auth/register", post(register))
+            .route("/auth/token", post(token))
+            .route("/auth/refresh", post(refresh))
+            .route("/auth/logout", post(logout))
+            .route("/auth/me", get(me).patch(update_me))
+            .route(
+                "/auth/session",
+                post(create_session)
+                    .get(current_session)
+                    .delete(delete_session),
+            )
+            .route("/auth/sessions", get(list_sessions))
+            .route("/auth/sessions/{id}", delete(revoke_session))
+            .route(
+                "/admin/api-keys",
+                post(create_api_key.layer(require_scope(ApiKeysManage)))
+                    .get(list_api_keys.layer(require_scope(ApiKeysManage))),
+            )
+            .route(
+                "/admin/api-keys/{id}",
+                delete(revoke_api_key.layer(require_scope(ApiKeysManage))),
+            )
+            .route(
+                "/admin/users",
+                get(list_users.layer(require_scope(UsersManage))),
+            )
+            .route(
+                "/admin/users/{id}/role",
+                put(set_user_role.layer(require_scope(UsersManage))),
+            )
+            .route(
+                "/admin/audit-events",
+                get(list_audit_events.layer(require_scope(AuditRead))),
+            )
+            .route(
+                "/todos",
+                post(create_todo.layer(require_scope(TodosWrite)))
+                    .get(get_all_todos.layer(require_scope(TodosRead))),
+            )
+            .route(
+                "/todos/{id}",
+                get(get_todo.layer(require_scope(TodosRead)))
+                    .patch(update_todo.layer(require_scope(TodosWrite)))
+                    .put(replace_todo.layer(require_scope(TodosWrite)))
+                    .delete(delete_todo.layer(require_scope(TodosWrite))),
+            )
+            .route(
+                "/todos/{id}/occurrences",
+                get(list_occurrences.layer(require_scope(TodosRead))),
+            )
+            .route(
+                "/todos/trash",
+                get(list_trash.layer(require_scope(TodosRead)))
+                    .delete(empty_trash.layer(require_scope(TodosWrite))),
+            )
+            .route(
+                "/todos/trash/{id}",
+                delete(purge_todo.layer(require_scope(TodosWrite))),
+            )
+            .route(
+                "/todos/trash/{id}/restore",
+                post(restore_todo.layer(require_scope(TodosWrite))),
+            )
+            .route(
+                "/tags",
+                post(create_tag.layer(require_scope(TodosWrite)))
+                    .get(list_tags.layer(require_scope(TodosRead))),
+            )
+            .route(
+                "/tags/{id}",
+                get(get_tag.layer(require_scope(TodosRead)))
+                    .patch(rename_tag.layer(require_scope(TodosWrite)))
+                    .delete(delete_tag.layer(require_scope(TodosWrite))),
+            )
+            .route(
+                "/tags/{id}/merge",
+                post(merge_tag.layer(require_scope(TodosWrite))),
+            )
+            .with_state(self.state.clone());
 
-        if let Some(cors) = self.mw.cors_layer() {
-            router = router.layer(cors);
+        if self.state.oidc.is_some() {
+            routes = routes.merge(
+                Router::new()
+                    .route("/auth/oidc/login", get(oidc_login))
+                    .route("/auth/oidc/callback", get(oidc_callback))
+                    .with_state(self.state.clone()),
+            );
         }
 
-        router
+        if self.state.cfg.metrics == MetricsEndpoint::Main {
+            routes = routes.merge(self.metrics_router());
+        }
+
+        // Layer order is defined by MiddlewareSuite::apply
+        self.mw.apply(routes)
+    }
+
+    /// Just `/metrics`, for serving on a separate listener.
+    pub fn metrics_router(&self) -> Router {
+        Router::new()
+            .route("/metrics", get(metrics))
+            .with_state(self.state.clone())
     }
 }
@@ -0,0 +1,25 @@
+use crate::models::User;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Returned by `POST /auth/session` and `GET /auth/session`. Browser code
+/// reads `csrf_token` from here and echoes it in `X-CSRF-Token`.
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    pub id: i64,
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: User,
+}
+
+/// One of the caller's live sessions, as listed by `GET /auth/sessions`.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SessionSummary {
+    pub id: i64,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
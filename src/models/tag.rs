@@ -0,0 +1,92 @@
+// src/models/tag.rs
+use crate::validate_fields;
+use crate::validation::{Validator, trim};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const TAG_NAME_MAX_CHARS: usize = 50;
+/// Most tags one todo can carry.
+pub const MAX_TAGS_PER_TODO: usize = 20;
+
+/// Body of `POST /tags` and `PATCH /tags/{id}` (a rename).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagName {
+    #[serde(deserialize_with = "trim")]
+    pub name: String,
+}
+
+validate_fields!(TagName {
+    name: [not_blank, max_chars(TAG_NAME_MAX_CHARS)],
+});
+
+/// Body of `POST /tags/{id}/merge`: the tag in the path is folded into `into`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MergeTag {
+    pub into: i64,
+}
+
+/// A tag with how many live todos carry it. Names are unique per owner,
+/// ignoring case.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    /// Owning user; `None` for tags created with the admin token.
+    pub owner_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub todo_count: i64,
+}
+
+/// A tag as embedded in a [`Todo`](crate::models::Todo).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagRef {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Checks the `tags` list of a todo body: each name must be a valid tag
+/// name, and there may be at most [`MAX_TAGS_PER_TODO`].
+pub(crate) fn validate_tag_list(v: &mut Validator, tags: &[String]) {
+    for tag in tags {
+        v.field("tags", tag)
+            .not_blank()
+            .max_chars(TAG_NAME_MAX_CHARS);
+    }
+    if tags.len() > MAX_TAGS_PER_TODO {
+        v.reject(
+            "tags",
+            "too_many",
+            format!("must list at most {MAX_TAGS_PER_TODO} tags"),
+        );
+    }
+}
+
+/// Drops repeats, comparing case-insensitively; the first spelling wins.
+pub fn dedup_tag_names(tags: &[String]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    tags.iter()
+        .filter(|t| seen.insert(t.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_lists_are_checked_and_deduplicated() {
+        let mut v = Validator::new();
+        let mut tags = vec!["".to_owned(), "x".repeat(TAG_NAME_MAX_CHARS + 1)];
+        tags.extend((0..MAX_TAGS_PER_TODO).map(|i| i.to_string()));
+        validate_tag_list(&mut v, &tags);
+        let errs = v.finish().unwrap_err();
+        let codes: Vec<_> = errs.0.iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["blank", "too_long", "too_many"]);
+
+        let names = ["Work", "home", "work", "HOME"].map(String::from);
+        assert_eq!(dedup_tag_names(&names), ["Work", "home"]);
+    }
+}
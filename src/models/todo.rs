@@ -1,20 +1,145 @@
+use crate::extract::{ETag, Versioned};
+use crate::models::{TagRef, validate_rrule, validate_tag_list};
+use crate::validation::{
+    Validate, ValidationErrors, Validator, nullable, trim, trim_all, trim_all_opt, trim_opt,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::{fmt, str::FromStr};
+
+pub const TITLE_MAX_CHARS: usize = 200;
+pub const DESCRIPTION_MAX_CHARS: usize = 10_000;
+
+/// How urgent a todo is, stored on `todos.priority`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    /// Most urgent first, the order `sort=priority` lists them in.
+    pub const ALL: [Priority; 4] = [
+        Priority::Urgent,
+        Priority::High,
+        Priority::Normal,
+        Priority::Low,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Priority::ALL
+            .into_iter()
+            .find(|priority| priority.as_str() == s)
+            .ok_or_else(|| format!("unknown priority {s:?}"))
+    }
+}
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CreateTodo {
+    #[serde(deserialize_with = "trim")]
     pub title: String,
     pub description: String,
     #[serde(default)]
     pub done: bool,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub priority: Priority,
+    /// Tag names; missing tags are created for the todo's owner.
+    #[serde(default, deserialize_with = "trim_all")]
+    pub tags: Vec<String>,
+    /// RFC 5545 recurrence rule; makes the todo the first occurrence of a
+    /// series starting at `due_at`.
+    #[serde(default, deserialize_with = "trim_opt")]
+    pub rrule: Option<String>,
 }
 
-#[allow(dead_code)]
+/// Partial update for `PATCH /todos/{id}`; absent fields are left untouched.
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct UpdatedTodo {
-    pub id: i64,
+    #[serde(default, deserialize_with = "trim_opt")]
     pub title: Option<String>,
     pub description: Option<String>,
     pub done: Option<bool>,
+    /// `null` clears the due date; absent leaves it unchanged.
+    #[serde(default, deserialize_with = "nullable")]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub priority: Option<Priority>,
+    /// Replaces the todo's tags when present.
+    #[serde(default, deserialize_with = "trim_all_opt")]
+    pub tags: Option<Vec<String>>,
+    /// Makes a todo recurring; with `scope=following` or `scope=all`, changes
+    /// the series' rule, and `null` ends the series.
+    #[serde(default, deserialize_with = "nullable")]
+    pub rrule: Option<Option<String>>,
+}
+
+impl Validate for CreateTodo {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut v = Validator::new();
+        v.field("title", &self.title)
+            .not_blank()
+            .max_chars(TITLE_MAX_CHARS);
+        v.field("description", &self.description)
+            .max_chars(DESCRIPTION_MAX_CHARS);
+        validate_tag_list(&mut v, &self.tags);
+        if let Some(rrule) = &self.rrule {
+            validate_rrule(&mut v, rrule);
+            if self.due_at.is_none() {
+                v.reject(
+                    "due_at",
+                    "required",
+                    "a recurring todo needs a due date for its first occurrence",
+                );
+            }
+        }
+        v.finish()
+    }
+}
+
+impl Validate for UpdatedTodo {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut v = Validator::new();
+        v.field("title", &self.title)
+            .not_blank()
+            .max_chars(TITLE_MAX_CHARS);
+        v.field("description", &self.description)
+            .max_chars(DESCRIPTION_MAX_CHARS);
+        if let Some(tags) = &self.tags {
+            validate_tag_list(&mut v, tags);
+        }
+        if let Some(Some(rrule)) = &self.rrule {
+            validate_rrule(&mut v, rrule);
+        }
+        v.finish()
+    }
 }
 
 #[derive(Debug, Serialize, sqlx::FromRow)]
@@ -23,6 +148,34 @@ pub struct Todo {
     pub title: String,
     pub description: String,
     pub done: bool,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+    /// Set by the database when `done` becomes true, cleared when it becomes false.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Owning user; `None` for rows created with the admin token.
+    pub owner_id: Option<i64>,
+    /// Bumped by the database on every change; sent as the `ETag`.
+    pub version: i64,
+    /// Set by database triggers; serialized as RFC 3339.
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// When the todo was moved to the trash; `None` for live todos.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Sorted by name.
+    pub tags: Json<Vec<TagRef>>,
+    /// The series this todo is an occurrence of, if it repeats.
+    pub series_id: Option<i64>,
+    /// The occurrence this todo stands for; unlike `due_at` it doesn't move
+    /// when the todo is rescheduled.
+    pub occurrence_at: Option<DateTime<Utc>>,
+    /// The series' rule, in canonical form.
+    pub rrule: Option<String>,
+}
+
+impl Versioned for Todo {
+    fn etag(&self) -> ETag {
+        ETag::strong(self.version)
+    }
 }
 
 impl Todo {
@@ -33,6 +186,18 @@ impl Todo {
             title,
             description,
             done,
+            due_at: None,
+            priority: Priority::default(),
+            completed_at: None,
+            owner_id: None,
+            version: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+            tags: Json(Vec::new()),
+            series_id: None,
+            occurrence_at: None,
+            rrule: None,
         }
     }
 }
@@ -0,0 +1,449 @@
+// src/models/todo_query.rs
+use crate::models::{MAX_TAGS_PER_TODO, Priority, Todo, dedup_tag_names};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub const DEFAULT_PAGE_LIMIT: u32 = 20;
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+/// Raw query string for `GET /todos`, e.g. `?done=true&q=milk&tag=work&due=today&sort=priority&limit=50&cursor=...`.
+#[derive(Debug, Default, Deserialize)]
+pub struct TodoListQuery {
+    pub done: Option<bool>,
+    pub q: Option<String>,
+    pub overdue: Option<bool>,
+    pub due: Option<DueWindow>,
+    /// RFC 3339 timestamp, or a `YYYY-MM-DD` date meaning that day's start
+    /// in the caller's timezone.
+    pub due_before: Option<String>,
+    pub due_after: Option<String>,
+    /// The caller's timezone for date-based filters; handlers fill it with
+    /// [`TodoListQuery::with_timezone`] when [`TodoListQuery::needs_timezone`].
+    #[serde(skip)]
+    pub timezone: Option<Tz>,
+    /// Every `tag=` value; `Query` can't collect repeated keys, so handlers
+    /// fill this from the URI with [`TodoListQuery::with_tags`].
+    #[serde(skip)]
+    pub tag: Vec<String>,
+    pub tag_match: Option<TagMatch>,
+    pub sort: Option<TodoSort>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl TodoListQuery {
+    pub fn with_tags(mut self, query: Option<&str>) -> Self {
+        self.tag = form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .filter(|(key, _)| key == "tag")
+            .map(|(_, value)| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+            .collect();
+        self
+    }
+
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Whether any filter depends on what "today" is for the caller.
+    pub fn needs_timezone(&self) -> bool {
+        self.due.is_some()
+            || [&self.due_before, &self.due_after]
+                .into_iter()
+                .flatten()
+                .any(|raw| DateTime::parse_from_rfc3339(raw).is_err())
+    }
+}
+
+/// `due=` shorthand for a whole day in the caller's timezone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DueWindow {
+    Today,
+    Tomorrow,
+}
+
+/// How several `tag=` filters combine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Todos carrying at least one of the tags.
+    #[default]
+    Any,
+    /// Todos carrying every one of the tags.
+    All,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum TodoSort {
+    #[default]
+    #[serde(rename = "id")]
+    IdAsc,
+    #[serde(rename = "-id")]
+    IdDesc,
+    /// Most urgent first, then earliest due (undated last), then id.
+    #[serde(rename = "priority")]
+    Priority,
+    /// Earliest due first (undated last), then id.
+    #[serde(rename = "due")]
+    Due,
+}
+
+impl TodoSort {
+    fn as_str(self) -> &'static str {
+        match self {
+            TodoSort::IdAsc => "id",
+            TodoSort::IdDesc => "-id",
+            TodoSort::Priority => "priority",
+            TodoSort::Due => "due",
+        }
+    }
+}
+
+/// Opaque keyset position: the sort it was issued for plus the sort key of
+/// the last row seen. Sorts that don't order on priority or due date leave
+/// those at their defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cursor {
+    pub sort: TodoSort,
+    pub last_id: i64,
+    pub last_priority: Priority,
+    pub last_due_at: Option<DateTime<Utc>>,
+}
+
+impl Cursor {
+    /// Position just past `todo` in `sort` order.
+    pub fn after(sort: TodoSort, todo: &Todo) -> Self {
+        let mut cursor = Self::id(sort, todo.id);
+        if matches!(sort, TodoSort::Priority | TodoSort::Due) {
+            cursor.last_due_at = todo.due_at;
+        }
+        if sort == TodoSort::Priority {
+            cursor.last_priority = todo.priority;
+        }
+        cursor
+    }
+
+    fn id(sort: TodoSort, last_id: i64) -> Self {
+        Self {
+            sort,
+            last_id,
+            last_priority: Priority::default(),
+            last_due_at: None,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        // Due dates travel as microseconds since the epoch, the database's precision
+        let due = self
+            .last_due_at
+            .map(|at| at.timestamp_micros().to_string())
+            .unwrap_or_default();
+        let text = match self.sort {
+            TodoSort::IdAsc | TodoSort::IdDesc => {
+                format!("{}:{}", self.sort.as_str(), self.last_id)
+            }
+            TodoSort::Priority => format!("priority:{}:{due}:{}", self.last_priority, self.last_id),
+            TodoSort::Due => format!("due:{due}:{}", self.last_id),
+        };
+        URL_SAFE_NO_PAD.encode(text)
+    }
+
+    pub fn decode(raw: &str) -> Result<Self, ListQueryError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(raw)
+            .map_err(|_| ListQueryError::InvalidCursor)?;
+        let text = String::from_utf8(bytes).map_err(|_| ListQueryError::InvalidCursor)?;
+        let parts: Vec<&str> = text.split(':').collect();
+        let id = |raw: &str| raw.parse().map_err(|_| ListQueryError::InvalidCursor);
+        let due = |raw: &str| match raw {
+            "" => Ok(None),
+            micros => micros
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_micros)
+                .map(Some)
+                .ok_or(ListQueryError::InvalidCursor),
+        };
+        match parts.as_slice() {
+            ["id", last_id] => Ok(Self::id(TodoSort::IdAsc, id(last_id)?)),
+            ["-id", last_id] => Ok(Self::id(TodoSort::IdDesc, id(last_id)?)),
+            ["priority", priority, due_at, last_id] => Ok(Self {
+                last_priority: priority
+                    .parse()
+                    .map_err(|_| ListQueryError::InvalidCursor)?,
+                last_due_at: due(due_at)?,
+                ..Self::id(TodoSort::Priority, id(last_id)?)
+            }),
+            ["due", due_at, last_id] => Ok(Self {
+                last_due_at: due(due_at)?,
+                ..Self::id(TodoSort::Due, id(last_id)?)
+            }),
+            _ => Err(ListQueryError::InvalidCursor),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ListQueryError {
+    #[error("limit must be between 1 and {MAX_PAGE_LIMIT}")]
+    LimitOutOfRange,
+    #[error("cursor is malformed")]
+    InvalidCursor,
+    #[error("cursor was issued for a different sort order")]
+    CursorSortMismatch,
+    #[error("at most {MAX_TAGS_PER_TODO} tag filters are allowed")]
+    TooManyTags,
+    #[error("{0} must be an RFC 3339 timestamp or a YYYY-MM-DD date")]
+    InvalidDate(&'static str),
+    #[error("from must be before to")]
+    EmptyRange,
+}
+
+/// Validated form of [`TodoListQuery`], ready to be turned into SQL.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TodoFilter {
+    pub done: Option<bool>,
+    /// `ILIKE` pattern with `%`/`_` in the user input escaped.
+    pub pattern: Option<String>,
+    /// Lowercased tag names; empty means no tag filter.
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    pub overdue: Option<bool>,
+    /// Half-open due range `[due_after, due_before)`, already resolved
+    /// against the caller's timezone.
+    pub due_after: Option<DateTime<Utc>>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub sort: TodoSort,
+    pub limit: u32,
+    pub after: Option<Cursor>,
+}
+
+impl TryFrom<TodoListQuery> for TodoFilter {
+    type Error = ListQueryError;
+
+    fn try_from(query: TodoListQuery) -> Result<Self, Self::Error> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+            return Err(ListQueryError::LimitOutOfRange);
+        }
+
+        let tags: Vec<String> = dedup_tag_names(&query.tag)
+            .iter()
+            .map(|t| t.to_lowercase())
+            .collect();
+        if tags.len() > MAX_TAGS_PER_TODO {
+            return Err(ListQueryError::TooManyTags);
+        }
+
+        let sort = query.sort.unwrap_or_default();
+        let after = match query.cursor.as_deref().filter(|c| !c.is_empty()) {
+            Some(raw) => {
+                let cursor = Cursor::decode(raw)?;
+                if cursor.sort != sort {
+                    return Err(ListQueryError::CursorSortMismatch);
+                }
+                Some(cursor)
+            }
+            None => None,
+        };
+
+        let tz = query.timezone.unwrap_or(Tz::UTC);
+        let mut due_after = query
+            .due_after
+            .as_deref()
+            .map(|raw| parse_due_bound(raw, tz, "due_after"))
+            .transpose()?;
+        let mut due_before = query
+            .due_before
+            .as_deref()
+            .map(|raw| parse_due_bound(raw, tz, "due_before"))
+            .transpose()?;
+        if let Some(window) = query.due {
+            let today = Utc::now().with_timezone(&tz).date_naive();
+            let day = match window {
+                DueWindow::Today => today,
+                DueWindow::Tomorrow => today + Days::new(1),
+            };
+            let (start, end) = (start_of_day(tz, day), start_of_day(tz, day + Days::new(1)));
+            due_after = Some(due_after.map_or(start, |t| t.max(start)));
+            due_before = Some(due_before.map_or(end, |t| t.min(end)));
+        }
+
+        let pattern = query
+            .q
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("%{}%", escape_like(s)));
+
+        Ok(Self {
+            done: query.done,
+            pattern,
+            tags,
+            tag_match: query.tag_match.unwrap_or_default(),
+            overdue: query.overdue,
+            due_after,
+            due_before,
+            sort,
+            limit,
+            after,
+        })
+    }
+}
+
+pub(super) fn parse_due_bound(
+    raw: &str,
+    tz: Tz,
+    field: &'static str,
+) -> Result<DateTime<Utc>, ListQueryError> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .map(|date| start_of_day(tz, date))
+        .map_err(|_| ListQueryError::InvalidDate(field))
+}
+
+/// Local midnight of `date` in `tz`, or the first hour that exists when a
+/// DST change skips midnight.
+pub fn start_of_day(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    (0..24)
+        .find_map(|hour| {
+            tz.from_local_datetime(&date.and_hms_opt(hour, 0, 0)?)
+                .earliest()
+        })
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn escape_like(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// One page of `GET /todos`.
+#[derive(Debug, Serialize)]
+pub struct TodoPage {
+    pub items: Vec<Todo>,
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let mut todo = Todo::new(42, "t".into(), String::new(), false);
+        todo.priority = Priority::High;
+        for sort in [TodoSort::IdDesc, TodoSort::Priority, TodoSort::Due] {
+            let c = Cursor::after(sort, &todo);
+            assert_eq!(Cursor::decode(&c.encode()), Ok(c));
+        }
+        todo.due_at = DateTime::from_timestamp_micros(1_760_000_000_123_456);
+        let c = Cursor::after(TodoSort::Priority, &todo);
+        assert_eq!(Cursor::decode(&c.encode()), Ok(c));
+        assert_eq!(c.last_priority, Priority::High);
+        assert_eq!(
+            Cursor::decode("not a cursor"),
+            Err(ListQueryError::InvalidCursor)
+        );
+    }
+
+    #[test]
+    fn filter_enforces_limit_and_cursor_sort() {
+        let over = TodoListQuery {
+            limit: Some(MAX_PAGE_LIMIT + 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            TodoFilter::try_from(over),
+            Err(ListQueryError::LimitOutOfRange)
+        );
+
+        let cursor = Cursor::id(TodoSort::IdAsc, 7).encode();
+        let mismatch = TodoListQuery {
+            sort: Some(TodoSort::IdDesc),
+            cursor: Some(cursor),
+            ..Default::default()
+        };
+        assert_eq!(
+            TodoFilter::try_from(mismatch),
+            Err(ListQueryError::CursorSortMismatch)
+        );
+    }
+
+    #[test]
+    fn search_term_is_escaped() {
+        let f = TodoFilter::try_from(TodoListQuery {
+            q: Some(" 100%_done ".into()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(f.pattern.as_deref(), Some("%100\\%\\_done%"));
+        assert_eq!(f.limit, DEFAULT_PAGE_LIMIT);
+    }
+
+    #[test]
+    fn repeated_tags_are_collected() {
+        let query = TodoListQuery::default()
+            .with_tags(Some("tag=Work&done=true&tag=%20home%20&tag=work&tag="));
+        assert_eq!(query.tag, ["Work", "home", "work"]);
+        let f = TodoFilter::try_from(query).unwrap();
+        assert_eq!(f.tags, ["work", "home"]);
+        assert_eq!(f.tag_match, TagMatch::Any);
+
+        let too_many = TodoListQuery {
+            tag: (0..=MAX_TAGS_PER_TODO).map(|i| i.to_string()).collect(),
+            ..Default::default()
+        };
+        assert_eq!(
+            TodoFilter::try_from(too_many),
+            Err(ListQueryError::TooManyTags)
+        );
+    }
+
+    #[test]
+    fn due_dates_resolve_in_the_callers_timezone() {
+        let query = TodoListQuery {
+            due_after: Some("2026-03-29".into()),
+            due_before: Some("2026-03-30T00:00:00Z".into()),
+            ..Default::default()
+        };
+        assert!(query.needs_timezone());
+        let f = TodoFilter::try_from(query.with_timezone(chrono_tz::Europe::Berlin)).unwrap();
+        assert_eq!(
+            f.due_after,
+            DateTime::parse_from_rfc3339("2026-03-28T23:00:00Z")
+                .map(|t| t.with_timezone(&Utc))
+                .ok()
+        );
+
+        // Midnight doesn't exist in Santiago on the day clocks spring forward
+        let date = NaiveDate::from_ymd_opt(2026, 9, 6).unwrap();
+        assert_eq!(
+            start_of_day(chrono_tz::America::Santiago, date).to_rfc3339(),
+            "2026-09-06T04:00:00+00:00"
+        );
+
+        let bad = TodoListQuery {
+            due_before: Some("tomorrow".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            TodoFilter::try_from(bad),
+            Err(ListQueryError::InvalidDate("due_before"))
+        );
+    }
+}
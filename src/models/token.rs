@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// Body of `POST /auth/refresh` and `POST /auth/logout`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Query of `GET /auth/oidc/callback`, as sent by the identity provider.
+/// Either `code` or `error` is present (RFC 6749 4.1.2).
+#[derive(Debug, Deserialize)]
+pub struct OidcCallback {
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// Returned by `POST /auth/token`, `POST /auth/refresh` and the OIDC callback.
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    /// Seconds until `access_token` expires.
+    pub expires_in: u64,
+    pub refresh_token: String,
+}
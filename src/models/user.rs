@@ -0,0 +1,78 @@
+use crate::auth::Role;
+use crate::validate_fields;
+use crate::validation::{Validate, ValidationErrors, Validator, trim, trim_opt};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+pub const USERNAME_MIN_CHARS: usize = 3;
+pub const USERNAME_MAX_CHARS: usize = 32;
+pub const PASSWORD_MIN_CHARS: usize = 8;
+pub const PASSWORD_MAX_CHARS: usize = 128;
+
+/// Body of `POST /auth/register`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterUser {
+    #[serde(deserialize_with = "trim")]
+    pub username: String,
+    pub password: String,
+}
+
+validate_fields!(RegisterUser {
+    username: [
+        not_blank,
+        min_chars(USERNAME_MIN_CHARS),
+        max_chars(USERNAME_MAX_CHARS)
+    ],
+    password: [min_chars(PASSWORD_MIN_CHARS), max_chars(PASSWORD_MAX_CHARS)],
+});
+
+/// Body of `POST /auth/token`. Not validated beyond shape: a bad password is
+/// just a failed login.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoginRequest {
+    #[serde(deserialize_with = "trim")]
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub role: Role,
+    /// IANA time zone name; date-based todo filters use it to find "today".
+    pub timezone: String,
+}
+
+/// Body of `PATCH /auth/me`; absent fields are left untouched.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateProfile {
+    #[serde(default, deserialize_with = "trim_opt")]
+    pub timezone: Option<String>,
+}
+
+impl Validate for UpdateProfile {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut v = Validator::new();
+        if let Some(timezone) = &self.timezone
+            && timezone.parse::<Tz>().is_err()
+        {
+            v.reject(
+                "timezone",
+                "unknown_timezone",
+                "must be an IANA time zone name such as Europe/Berlin",
+            );
+        }
+        v.finish()
+    }
+}
+
+/// Body of `PUT /admin/users/{id}/role`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateRole {
+    pub role: Role,
+}
@@ -0,0 +1,81 @@
+// src/ratelimit/limiter.rs
+use crate::config::{RateLimitBackend, RateLimitConfig, RouteGroup};
+use crate::ratelimit::{Decision, MemoryStore, PostgresStore, Quota};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type CheckFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<Decision>> + Send + 'a>>;
+
+/// Where GCRA state lives. Implementations must apply [`Quota::admit`]
+/// atomically per key; [`MemoryStore`] and [`PostgresStore`] ship with the
+/// crate, and a downstream crate can plug in its own through
+/// [`RateLimiter::with_store`].
+pub trait RateLimitStore: Send + Sync + 'static {
+    fn check<'a>(&'a self, key: &'a str, quota: Quota) -> CheckFuture<'a>;
+
+    /// Whether a request for `key` would be admitted now, without charging it.
+    fn peek<'a>(&'a self, key: &'a str, quota: Quota) -> CheckFuture<'a>;
+}
+
+/// Per-route-group quotas over a shared store.
+#[derive(Clone)]
+pub struct RateLimiter {
+    quotas: Arc<HashMap<RouteGroup, Quota>>,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    /// `None` when rate limiting is disabled.
+    pub fn from_config(cfg: &RateLimitConfig, pool: &PgPool) -> Option<Self> {
+        if !cfg.enabled {
+            return None;
+        }
+        let store: Arc<dyn RateLimitStore> = match cfg.backend {
+            RateLimitBackend::Memory => Arc::new(MemoryStore::new()),
+            RateLimitBackend::Postgres => Arc::new(PostgresStore::new(pool.clone())),
+        };
+        Some(Self::with_store(cfg.quotas.clone(), store))
+    }
+
+    pub fn with_store(quotas: HashMap<RouteGroup, Quota>, store: Arc<dyn RateLimitStore>) -> Self {
+        Self {
+            quotas: Arc::new(quotas),
+            store,
+        }
+    }
+
+    /// The group's quota; `None` means the group is not limited.
+    pub fn quota(&self, group: RouteGroup) -> Option<Quota> {
+        self.quotas.get(&group).copied()
+    }
+
+    /// Counts one request by `client` against `group`'s quota. Each group
+    /// has its own budget, so a burst of logins can't starve the API.
+    pub async fn check(&self, group: RouteGroup, client: &str) -> anyhow::Result<Option<Decision>> {
+        let Some(quota) = self.quota(group) else {
+            return Ok(None);
+        };
+        let key = format!("{group}:{client}");
+        self.store.check(&key, quota).await.map(Some)
+    }
+
+    /// Like [`check`](Self::check), but only looks: nothing is charged.
+    pub async fn peek(&self, group: RouteGroup, client: &str) -> anyhow::Result<Option<Decision>> {
+        let Some(quota) = self.quota(group) else {
+            return Ok(None);
+        };
+        let key = format!("{group}:{client}");
+        self.store.peek(&key, quota).await.map(Some)
+    }
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("quotas", &self.quotas)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,72 @@
+// src/ratelimit/memory.rs
+use crate::ratelimit::{CheckFuture, Quota, RateLimitStore};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Above this many tracked clients, checks first drop the ones whose TAT
+/// has passed (they are back to a full quota anyway).
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Process-local store. Each instance counts separately, so with N replicas
+/// a client effectively gets N times the quota.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    tats: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    fn check<'a>(&'a self, key: &'a str, quota: Quota) -> CheckFuture<'a> {
+        Box::pin(async move {
+            let now = Utc::now();
+            let mut tats = self.tats.lock().unwrap_or_else(|e| e.into_inner());
+            if tats.len() > SWEEP_THRESHOLD {
+                tats.retain(|_, tat| *tat > now);
+            }
+            let (decision, next) = quota.admit(tats.get(key).copied(), now);
+            if let Some(next) = next {
+                tats.insert(key.to_owned(), next);
+            }
+            Ok(decision)
+        })
+    }
+
+    fn peek<'a>(&'a self, key: &'a str, quota: Quota) -> CheckFuture<'a> {
+        Box::pin(async move {
+            let tats = self.tats.lock().unwrap_or_else(|e| e.into_inner());
+            Ok(quota.admit(tats.get(key).copied(), Utc::now()).0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn keys_are_limited_independently() {
+        let store = MemoryStore::new();
+        let quota = Quota::new(1, Duration::from_secs(60));
+
+        assert!(store.check("api:ip:a", quota).await.unwrap().allowed);
+        assert!(!store.check("api:ip:a", quota).await.unwrap().allowed);
+        assert!(store.check("api:ip:b", quota).await.unwrap().allowed);
+    }
+
+    #[tokio::test]
+    async fn peeking_does_not_charge() {
+        let store = MemoryStore::new();
+        let quota = Quota::new(1, Duration::from_secs(60));
+
+        assert!(store.peek("api:ip:a", quota).await.unwrap().allowed);
+        assert!(store.check("api:ip:a", quota).await.unwrap().allowed);
+        assert!(!store.peek("api:ip:a", quota).await.unwrap().allowed);
+    }
+}
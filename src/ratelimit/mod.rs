@@ -0,0 +1,10 @@
+// src/ratelimit/mod.rs
+mod limiter;
+mod memory;
+mod postgres;
+mod quota;
+
+pub use limiter::{CheckFuture, RateLimitStore, RateLimiter};
+pub use memory::MemoryStore;
+pub use postgres::PostgresStore;
+pub use quota::{Decision, Quota};
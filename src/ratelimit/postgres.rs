@@ -0,0 +1,90 @@
+// src/ratelimit/postgres.rs
+use crate::ratelimit::{CheckFuture, Quota, RateLimitStore};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Every this many checks, rows whose TAT has passed are deleted.
+const SWEEP_EVERY: u64 = 1000;
+
+/// Shares quotas between instances through the `rate_limits` table. GCRA
+/// runs inside one upsert, against the database clock, so concurrent
+/// requests from any instance can't both take the last slot.
+#[derive(Debug)]
+pub struct PostgresStore {
+    pool: PgPool,
+    checks: AtomicU64,
+}
+
+impl PostgresStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            checks: AtomicU64::new(0),
+        }
+    }
+
+    async fn sweep(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM rate_limits WHERE tat < now()")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RateLimitStore for PostgresStore {
+    fn check<'a>(&'a self, key: &'a str, quota: Quota) -> CheckFuture<'a> {
+        Box::pin(async move {
+            if self.checks.fetch_add(1, Ordering::Relaxed) % SWEEP_EVERY == SWEEP_EVERY - 1 {
+                self.sweep().await?;
+            }
+
+            let emission = quota
+                .emission_interval()
+                .num_microseconds()
+                .unwrap_or(i64::MAX) as f64
+                / 1_000_000.0;
+            let period = quota.period.as_secs_f64();
+
+            // The conditional update leaves denied requests' TAT untouched and
+            // returns no row for them
+            let admitted: Option<(DateTime<Utc>, DateTime<Utc>)> = sqlx::query_as(
+                r#"
+                INSERT INTO rate_limits AS r (key, tat)
+                VALUES ($1, now() + make_interval(secs => $2))
+                ON CONFLICT (key) DO UPDATE
+                SET tat = GREATEST(r.tat, now()) + make_interval(secs => $2)
+                WHERE GREATEST(r.tat, now()) + make_interval(secs => $2)
+                      - make_interval(secs => $3) <= now()
+                RETURNING tat, now()
+                "#,
+            )
+            .bind(key)
+            .bind(emission)
+            .bind(period)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            if let Some((tat, now)) = admitted {
+                return Ok(quota.decide(tat, now, true));
+            }
+            let (tat, now): (DateTime<Utc>, DateTime<Utc>) =
+                sqlx::query_as("SELECT tat, now() FROM rate_limits WHERE key = $1")
+                    .bind(key)
+                    .fetch_one(&self.pool)
+                    .await?;
+            Ok(quota.decide(tat, now, false))
+        })
+    }
+
+    fn peek<'a>(&'a self, key: &'a str, quota: Quota) -> CheckFuture<'a> {
+        Box::pin(async move {
+            let (tat, now): (Option<DateTime<Utc>>, DateTime<Utc>) =
+                sqlx::query_as("SELECT (SELECT tat FROM rate_limits WHERE key = $1), now()")
+                    .bind(key)
+                    .fetch_one(&self.pool)
+                    .await?;
+            Ok(quota.admit(tat, now).0)
+        })
+    }
+}
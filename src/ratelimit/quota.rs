@@ -0,0 +1,170 @@
+// src/ratelimit/quota.rs
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::{fmt, str::FromStr, time::Duration};
+
+/// Largest `limit`: the period is divided by it as an `i32`.
+const MAX_LIMIT: u32 = i32::MAX as u32;
+
+/// `limit` requests per `period`, enforced with GCRA: each client has a
+/// theoretical arrival time (TAT) that every admitted request pushes back by
+/// `period / limit`. A request is admitted while the TAT stays within one
+/// period of now, so a full burst of `limit` is allowed after idling and the
+/// quota then refills smoothly rather than all at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quota {
+    pub limit: u32,
+    pub period: Duration,
+}
+
+/// Outcome of one check, with what the `RateLimit-*` headers report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Until the full quota is available again.
+    pub reset: Duration,
+    /// Until the next request would be admitted; only set when denied.
+    pub retry_after: Option<Duration>,
+}
+
+impl Quota {
+    pub fn new(limit: u32, period: Duration) -> Self {
+        assert!(limit > 0, "a quota must admit at least one request");
+        assert!(
+            limit <= MAX_LIMIT,
+            "a quota admits at most {MAX_LIMIT} requests"
+        );
+        Self { limit, period }
+    }
+
+    fn period_delta(&self) -> TimeDelta {
+        TimeDelta::from_std(self.period).unwrap_or(TimeDelta::MAX)
+    }
+
+    /// Time each request adds to the TAT.
+    pub fn emission_interval(&self) -> TimeDelta {
+        let limit = i32::try_from(self.limit).expect("Quota::new caps the limit");
+        self.period_delta() / limit
+    }
+
+    /// Applies one request to the stored TAT (`None` for a new client) and
+    /// returns the decision plus the TAT to store when admitted.
+    pub fn admit(
+        &self,
+        tat: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> (Decision, Option<DateTime<Utc>>) {
+        let tat = tat.map_or(now, |tat| tat.max(now));
+        let next = tat + self.emission_interval();
+        if next - self.period_delta() > now {
+            (self.decide(tat, now, false), None)
+        } else {
+            (self.decide(next, now, true), Some(next))
+        }
+    }
+
+    /// Decision for a TAT already updated (`allowed`) or left alone (denied)
+    /// by a store that applies GCRA itself.
+    pub fn decide(&self, tat: DateTime<Utc>, now: DateTime<Utc>, allowed: bool) -> Decision {
+        let backlog = (tat - now).max(TimeDelta::zero());
+        let headroom = self.period_delta() - backlog;
+        let remaining = if allowed && headroom > TimeDelta::zero() {
+            (headroom.num_microseconds().unwrap_or(i64::MAX)
+                / self
+                    .emission_interval()
+                    .num_microseconds()
+                    .unwrap_or(i64::MAX)
+                    .max(1)) as u32
+        } else {
+            0
+        };
+        let retry_after = (!allowed).then(|| {
+            (tat + self.emission_interval() - self.period_delta() - now)
+                .to_std()
+                .unwrap_or_default()
+        });
+        Decision {
+            allowed,
+            limit: self.limit,
+            remaining: remaining.min(self.limit),
+            reset: backlog.to_std().unwrap_or_default(),
+            retry_after,
+        }
+    }
+}
+
+/// `limit/seconds`, e.g. `10/60`.
+impl FromStr for Quota {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (limit, secs) = s
+            .split_once('/')
+            .with_context(|| format!("quota {s:?} must be limit/seconds"))?;
+        let limit: u32 = limit.trim().parse().context("quota limit must be u32")?;
+        let secs: u64 = secs
+            .trim()
+            .parse()
+            .context("quota period must be u64 seconds")?;
+        if limit == 0 || secs == 0 {
+            bail!("quota {s:?} must have a positive limit and period");
+        }
+        if limit > MAX_LIMIT {
+            bail!("quota {s:?} allows at most {MAX_LIMIT} requests");
+        }
+        Ok(Self::new(limit, Duration::from_secs(secs)))
+    }
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.limit, self.period.as_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_a_burst_then_refills_gradually() {
+        let quota = Quota::new(3, Duration::from_secs(3));
+        let t0 = Utc::now();
+        let mut tat = None;
+
+        for remaining in [2, 1, 0] {
+            let (decision, next) = quota.admit(tat, t0);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+            tat = next;
+        }
+
+        let (denied, next) = quota.admit(tat, t0);
+        assert!(!denied.allowed);
+        assert_eq!(next, None);
+        assert_eq!(denied.retry_after, Some(Duration::from_secs(1)));
+        assert_eq!(denied.reset, Duration::from_secs(3));
+
+        // One emission interval later exactly one more request fits
+        let t1 = t0 + TimeDelta::seconds(1);
+        let (decision, next) = quota.admit(tat, t1);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert!(!quota.admit(next, t1).0.allowed);
+    }
+
+    #[test]
+    fn parses_limit_per_seconds() {
+        assert_eq!(
+            "10/60".parse::<Quota>().unwrap(),
+            Quota::new(10, Duration::from_secs(60))
+        );
+        assert!("0/60".parse::<Quota>().is_err());
+        // Would wrap to a negative emission interval and admit everything
+        assert!("3000000000/60".parse::<Quota>().is_err());
+        assert!("2147483647/60".parse::<Quota>().is_ok());
+        assert!("10".parse::<Quota>().is_err());
+    }
+}
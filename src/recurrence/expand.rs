@@ -0,0 +1,538 @@
+// src/recurrence/expand.rs
+use super::rule::{Frequency, RRule, Until, WeekdayNum};
+use chrono::{
+    DateTime, Datelike, Days, Duration, LocalResult, Months, NaiveDate, NaiveDateTime, Offset,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+const MAX_YEAR: i32 = 9999;
+
+/// Consecutive periods without a match after which expansion gives up, so a
+/// rule like `FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30` ends instead of spinning.
+/// 400 years of periods: the Gregorian calendar repeats after that.
+fn max_empty_periods(freq: Frequency) -> u32 {
+    match freq {
+        Frequency::Daily => 146_097,
+        Frequency::Weekly => 20_872,
+        Frequency::Monthly => 4_800,
+        Frequency::Yearly => 400,
+    }
+}
+
+/// UTC instant of a wall-clock time in `tz`. An ambiguous time (clocks going
+/// back) is the first of the two; a time skipped by clocks going forward is
+/// read with the offset from before the gap, which moves it later by the
+/// gap's length (RFC 5545 §3.3.5).
+pub fn resolve_local(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => at.with_timezone(&Utc),
+        LocalResult::None => {
+            let before = tz
+                .offset_from_utc_datetime(&(local - Duration::days(1)))
+                .fix();
+            (local - Duration::seconds(before.local_minus_utc().into())).and_utc()
+        }
+    }
+}
+
+/// The occurrences of a series that starts at `start` and repeats by `rule`,
+/// in order. The start is always the first occurrence (RFC 5545 §3.8.5.3);
+/// later ones keep its wall-clock time in `tz`, so a 09:00 daily todo stays
+/// at 09:00 local across DST changes.
+pub fn occurrences(rule: &RRule, start: DateTime<Utc>, tz: Tz) -> Occurrences<'_> {
+    Occurrences {
+        rule,
+        tz,
+        start,
+        local_start: start.with_timezone(&tz).naive_local(),
+        period: 0,
+        pending: Vec::new(),
+        emitted: 0,
+        empty_periods: 0,
+        finished: false,
+    }
+}
+
+pub struct Occurrences<'a> {
+    rule: &'a RRule,
+    tz: Tz,
+    start: DateTime<Utc>,
+    local_start: NaiveDateTime,
+    period: u32,
+    /// Matching dates of the current period, last first.
+    pending: Vec<NaiveDate>,
+    emitted: u32,
+    empty_periods: u32,
+    finished: bool,
+}
+
+impl Occurrences<'_> {
+    /// Jumps to the period before the one holding `from`, instead of walking
+    /// every period since the start. What follows may still begin a little
+    /// before `from`. A rule with `COUNT` is left as is: its count runs from
+    /// the start, which also bounds the walk.
+    pub fn seek(mut self, from: DateTime<Utc>) -> Self {
+        if self.rule.count.is_some() || self.emitted > 0 || from <= self.start {
+            return self;
+        }
+        let date = from.with_timezone(&self.tz).date_naive();
+        if let Some(period) = self.period_of(date).and_then(|p| p.checked_sub(1))
+            && period > self.period
+        {
+            self.period = period;
+            // The start is before `from`
+            self.emitted = 1;
+        }
+        self
+    }
+
+    /// Queues the matching dates of the next period; `false` once there can
+    /// be no more.
+    fn fill(&mut self) -> bool {
+        while self.pending.is_empty() {
+            if self.empty_periods >= max_empty_periods(self.rule.freq) {
+                return false;
+            }
+            let Some(days) = self.period_days(self.period) else {
+                return false;
+            };
+            self.period += 1;
+            let start = self.local_start.date();
+            self.pending = days
+                .filter(|date| *date > start && self.matches(*date))
+                .collect();
+            self.pending.reverse();
+            self.empty_periods = if self.pending.is_empty() {
+                self.empty_periods + 1
+            } else {
+                0
+            };
+        }
+        true
+    }
+
+    /// Every day of the `period`th daily, weekly, monthly or yearly period.
+    fn period_days(&self, period: u32) -> Option<impl Iterator<Item = NaiveDate> + use<>> {
+        let step = period.checked_mul(self.rule.interval)?;
+        let start = self.local_start.date();
+        let (first, len) = match self.rule.freq {
+            Frequency::Daily => (start.checked_add_days(Days::new(step.into()))?, 1),
+            Frequency::Weekly => {
+                let back = start.weekday().days_since(self.rule.week_start);
+                let first = start
+                    .checked_sub_days(Days::new(back.into()))?
+                    .checked_add_days(Days::new(u64::from(step) * 7))?;
+                (first, 7)
+            }
+            Frequency::Monthly => {
+                let first = start.with_day(1)?.checked_add_months(Months::new(step))?;
+                (first, days_in_month(first))
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(step).ok()?)?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                (first, days_in_year(first))
+            }
+        };
+        (first.year() <= MAX_YEAR).then(|| first.iter_days().take(len as usize))
+    }
+
+    /// Index of the period holding `date`, a day on or after the start.
+    fn period_of(&self, date: NaiveDate) -> Option<u32> {
+        let start = self.local_start.date();
+        let elapsed = match self.rule.freq {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => {
+                let back = start.weekday().days_since(self.rule.week_start);
+                ((date - start).num_days() + i64::from(back)) / 7
+            }
+            Frequency::Monthly => {
+                i64::from(date.year() - start.year()) * 12 + i64::from(date.month())
+                    - i64::from(start.month())
+            }
+            Frequency::Yearly => i64::from(date.year() - start.year()),
+        };
+        u32::try_from(elapsed / i64::from(self.rule.interval)).ok()
+    }
+
+    fn matches(&self, date: NaiveDate) -> bool {
+        let rule = self.rule;
+        let start = self.local_start.date();
+        if !rule.by_month.is_empty() && !rule.by_month.contains(&date.month()) {
+            return false;
+        }
+        if !rule.by_month_day.is_empty()
+            && !rule
+                .by_month_day
+                .iter()
+                .any(|day| month_day_matches(date, *day))
+        {
+            return false;
+        }
+        if !rule.by_day.is_empty() {
+            let in_year = rule.freq == Frequency::Yearly && rule.by_month.is_empty();
+            if !rule
+                .by_day
+                .iter()
+                .any(|d| weekday_matches(date, *d, in_year))
+            {
+                return false;
+            }
+        }
+
+        // Parts the rule leaves out are taken from the start (RFC 5545 §3.3.10)
+        let by_date = !rule.by_day.is_empty() || !rule.by_month_day.is_empty();
+        match rule.freq {
+            Frequency::Daily => true,
+            Frequency::Weekly => by_date || date.weekday() == start.weekday(),
+            Frequency::Monthly => by_date || date.day() == start.day(),
+            Frequency::Yearly => {
+                (by_date || date.day() == start.day())
+                    && (by_date || !rule.by_month.is_empty() || date.month() == start.month())
+            }
+        }
+    }
+
+    fn past_until(&self, date: NaiveDate, at: DateTime<Utc>) -> bool {
+        match self.rule.until {
+            Some(Until::Date(until)) => date > until,
+            Some(Until::Time(until)) => at > until,
+            None => false,
+        }
+    }
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = DateTime<Utc>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.rule.count.is_some_and(|count| self.emitted >= count) {
+            return None;
+        }
+        let at = if self.emitted == 0 {
+            self.start
+        } else {
+            if !self.fill() {
+                self.finished = true;
+                return None;
+            }
+            let date = self.pending.pop()?;
+            let at = resolve_local(self.tz, date.and_time(self.local_start.time()));
+            if self.past_until(date, at) {
+                self.finished = true;
+                return None;
+            }
+            at
+        };
+        self.emitted += 1;
+        Some(at)
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).expect("day 1 exists");
+    let next = first + Months::new(1);
+    (next - first).num_days() as u32
+}
+
+fn days_in_year(date: NaiveDate) -> u32 {
+    if date.leap_year() { 366 } else { 365 }
+}
+
+fn month_day_matches(date: NaiveDate, day: i8) -> bool {
+    let day = i64::from(day);
+    let wanted = if day > 0 {
+        day
+    } else {
+        i64::from(days_in_month(date)) + 1 + day
+    };
+    i64::from(date.day()) == wanted
+}
+
+/// Whether `date` is the weekday `day` names, counting a numbered entry
+/// within the month or, for yearly rules without `BYMONTH`, the year.
+fn weekday_matches(date: NaiveDate, day: WeekdayNum, in_year: bool) -> bool {
+    if date.weekday() != day.weekday {
+        return false;
+    }
+    let Some(ordinal) = day.ordinal else {
+        return true;
+    };
+    let (index, len) = if in_year {
+        (date.ordinal0(), days_in_year(date))
+    } else {
+        (date.day0(), days_in_month(date))
+    };
+    let from_start = (index / 7 + 1) as i8;
+    let from_end = -(((len - 1 - index) / 7 + 1) as i8);
+    ordinal == from_start || ordinal == from_end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, Weekday};
+    use proptest::prelude::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn expand(rule: &str, start: &str, tz: Tz, n: usize) -> Vec<String> {
+        let rule: RRule = rule.parse().unwrap();
+        occurrences(&rule, utc(start), tz)
+            .take(n)
+            .map(|at| at.with_timezone(&tz).to_rfc3339())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_wall_clock_time_across_dst() {
+        let berlin = chrono_tz::Europe::Berlin;
+        assert_eq!(
+            expand("FREQ=DAILY", "2026-03-28T08:00:00Z", berlin, 3),
+            [
+                "2026-03-28T09:00:00+01:00",
+                "2026-03-29T09:00:00+02:00",
+                "2026-03-30T09:00:00+02:00"
+            ]
+        );
+        // 02:30 doesn't exist on the 29th and is pushed past the gap; on
+        // 25 October it happens twice and the first (summer time) is used
+        assert_eq!(
+            expand("FREQ=DAILY", "2026-03-28T01:30:00Z", berlin, 2)[1],
+            "2026-03-29T03:30:00+02:00"
+        );
+        assert_eq!(
+            expand("FREQ=DAILY", "2026-10-24T00:30:00Z", berlin, 2)[1],
+            "2026-10-25T02:30:00+02:00"
+        );
+    }
+
+    #[test]
+    fn expands_calendar_rules() {
+        let utc_tz = chrono_tz::UTC;
+        assert_eq!(
+            expand(
+                "FREQ=MONTHLY;BYDAY=-1FR;COUNT=3",
+                "2026-01-30T12:00:00Z",
+                utc_tz,
+                5
+            ),
+            [
+                "2026-01-30T12:00:00+00:00",
+                "2026-02-27T12:00:00+00:00",
+                "2026-03-27T12:00:00+00:00"
+            ]
+        );
+        // Months without a 31st are skipped, not clamped
+        assert_eq!(
+            expand("FREQ=MONTHLY", "2026-01-31T12:00:00Z", utc_tz, 3),
+            [
+                "2026-01-31T12:00:00+00:00",
+                "2026-03-31T12:00:00+00:00",
+                "2026-05-31T12:00:00+00:00"
+            ]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;UNTIL=20260220",
+                "2026-02-02T07:00:00Z",
+                utc_tz,
+                10
+            ),
+            [
+                "2026-02-02T07:00:00+00:00",
+                "2026-02-06T07:00:00+00:00",
+                "2026-02-16T07:00:00+00:00",
+                "2026-02-20T07:00:00+00:00"
+            ]
+        );
+        assert_eq!(
+            expand("FREQ=YEARLY", "2024-02-29T00:00:00Z", utc_tz, 2)[1],
+            "2028-02-29T00:00:00+00:00"
+        );
+        assert!(
+            expand(
+                "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30",
+                "2026-01-01T00:00:00Z",
+                utc_tz,
+                3
+            )
+            .len()
+                == 1
+        );
+    }
+
+    #[test]
+    fn seeks_far_ahead_without_walking() {
+        let rule: RRule = "FREQ=WEEKLY;BYDAY=MO".parse().unwrap();
+        let from = utc("9000-01-01T00:00:00Z");
+        let next = occurrences(&rule, utc("2026-01-05T09:00:00Z"), chrono_tz::UTC)
+            .seek(from)
+            .find(|at| *at >= from);
+        // 1 January 9000 is a Wednesday
+        assert_eq!(next, Some(utc("9000-01-06T09:00:00Z")));
+    }
+
+    const ZONES: [Tz; 6] = [
+        chrono_tz::UTC,
+        chrono_tz::Europe::Berlin,
+        chrono_tz::America::New_York,
+        chrono_tz::America::Santiago,
+        chrono_tz::Australia::Lord_Howe,
+        chrono_tz::Asia::Kolkata,
+    ];
+
+    const WEEKDAYS: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+
+    fn arb_rule() -> impl Strategy<Value = RRule> {
+        (
+            prop::sample::select(Frequency::ALL.to_vec()),
+            1u32..4,
+            prop::collection::vec(1u32..=12, 0..3),
+            prop::collection::vec(prop_oneof![1i8..=31, -31i8..=-1], 0..3),
+            prop::collection::vec((prop::option::of(-5i8..=5), 0usize..7), 0..3),
+            prop::sample::select(WEEKDAYS.to_vec()),
+        )
+            .prop_map(|(freq, interval, by_month, by_month_day, by_day, wkst)| {
+                let numbered = matches!(freq, Frequency::Monthly | Frequency::Yearly);
+                RRule {
+                    interval,
+                    by_month,
+                    by_month_day: if freq == Frequency::Weekly {
+                        Vec::new()
+                    } else {
+                        by_month_day
+                    },
+                    by_day: by_day
+                        .into_iter()
+                        .map(|(ordinal, day)| WeekdayNum {
+                            ordinal: ordinal.filter(|n| numbered && *n != 0),
+                            weekday: WEEKDAYS[day],
+                        })
+                        .collect(),
+                    week_start: wkst,
+                    ..RRule::new(freq)
+                }
+            })
+    }
+
+    fn arb_start() -> impl Strategy<Value = DateTime<Utc>> {
+        // 2000-01-01 to 2040-01-01, on whole minutes
+        (946_684_800i64 / 60..2_208_988_800 / 60)
+            .prop_map(|minutes| DateTime::from_timestamp(minutes * 60, 0).unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn occurrences_are_ordered_and_keep_local_time(
+            rule in arb_rule(),
+            start in arb_start(),
+            tz in prop::sample::select(ZONES.to_vec()),
+        ) {
+            let all: Vec<_> = occurrences(&rule, start, tz).take(40).collect();
+            prop_assert_eq!(all[0], start);
+            prop_assert!(all.windows(2).all(|w| w[0] < w[1]));
+
+            let time = start.with_timezone(&tz).time();
+            for at in &all[1..] {
+                let local = at.with_timezone(&tz).naive_local();
+                // Either the start's wall-clock time, or that time didn't exist that day
+                prop_assert!(
+                    local.time() == time
+                        || tz.from_local_datetime(&local.date().and_time(time)) == LocalResult::None,
+                    "{} lost its time of day", local
+                );
+                let date = local.date();
+                prop_assert!(rule.by_month.is_empty() || rule.by_month.contains(&date.month()));
+                prop_assert!(
+                    rule.by_day.is_empty()
+                        || rule.by_day.iter().any(|d| d.weekday == date.weekday())
+                );
+                prop_assert!(
+                    rule.by_month_day.is_empty()
+                        || rule.by_month_day.iter().any(|d| month_day_matches(date, *d))
+                );
+            }
+        }
+
+        #[test]
+        fn count_and_until_bound_the_series(
+            rule in arb_rule(),
+            start in arb_start(),
+            tz in prop::sample::select(ZONES.to_vec()),
+            count in 1u32..30,
+            days in 0u64..400,
+        ) {
+            let counted = RRule { count: Some(count), ..rule.clone() };
+            prop_assert!(occurrences(&counted, start, tz).count() <= count as usize);
+
+            let until = start + Duration::days(days as i64);
+            let bounded = RRule { until: Some(Until::Time(until)), ..rule };
+            prop_assert!(occurrences(&bounded, start, tz).skip(1).all(|at| at <= until));
+        }
+
+        #[test]
+        fn seeking_skips_only_earlier_occurrences(
+            rule in arb_rule(),
+            start in arb_start(),
+            tz in prop::sample::select(ZONES.to_vec()),
+            minutes in 0i64..10_000_000,
+        ) {
+            let from = start + Duration::minutes(minutes);
+            let walked: Vec<_> = occurrences(&rule, start, tz)
+                .skip_while(|at| *at < from)
+                .take(10)
+                .collect();
+            let sought: Vec<_> = occurrences(&rule, start, tz)
+                .seek(from)
+                .skip_while(|at| *at < from)
+                .take(10)
+                .collect();
+            prop_assert_eq!(walked, sought);
+        }
+
+        #[test]
+        fn plain_daily_rules_step_by_their_interval(
+            interval in 1u32..10,
+            start in arb_start(),
+            tz in prop::sample::select(ZONES.to_vec()),
+        ) {
+            let rule = RRule { interval, count: Some(20), ..RRule::new(Frequency::Daily) };
+            let dates: Vec<_> = occurrences(&rule, start, tz)
+                .map(|at| at.with_timezone(&tz).date_naive())
+                .collect();
+            prop_assert_eq!(dates.len(), 20);
+            for pair in dates.windows(2) {
+                prop_assert_eq!((pair[1] - pair[0]).num_days(), i64::from(interval));
+            }
+        }
+
+        #[test]
+        fn rules_round_trip_through_text(rule in arb_rule(), count in prop::option::of(1u32..100)) {
+            let rule = RRule { count, ..rule };
+            prop_assert_eq!(rule.to_string().parse::<RRule>(), Ok(rule));
+        }
+    }
+
+    #[test]
+    fn gap_resolution_moves_forward() {
+        let local = NaiveDate::from_ymd_opt(2026, 3, 8)
+            .unwrap()
+            .and_time(NaiveTime::from_hms_opt(2, 30, 0).unwrap());
+        assert_eq!(
+            resolve_local(chrono_tz::America::New_York, local).to_rfc3339(),
+            "2026-03-08T07:30:00+00:00"
+        );
+    }
+}
@@ -0,0 +1,7 @@
+mod expand;
+mod rule;
+
+pub use expand::{Occurrences, occurrences, resolve_local};
+pub use rule::{
+    Frequency, MAX_COUNT, MAX_INTERVAL, RRULE_MAX_CHARS, RRule, RRuleError, Until, WeekdayNum,
+};
@@ -0,0 +1,357 @@
+// src/recurrence/rule.rs
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc, Weekday};
+use std::{collections::HashSet, fmt, str::FromStr};
+use thiserror::Error;
+
+pub const RRULE_MAX_CHARS: usize = 500;
+pub const MAX_INTERVAL: u32 = 1000;
+/// A counted series can't be expanded from the middle, so `COUNT` bounds
+/// how many occurrences reaching a far-off date takes.
+pub const MAX_COUNT: u32 = 10_000;
+
+/// `FREQ`; sub-daily frequencies make no sense for todos and are rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub const ALL: [Frequency; 4] = [
+        Frequency::Daily,
+        Frequency::Weekly,
+        Frequency::Monthly,
+        Frequency::Yearly,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// One `BYDAY` entry, e.g. `MO`, `1MO` (first Monday) or `-1FR` (last Friday).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i8>,
+    pub weekday: Weekday,
+}
+
+/// `UNTIL`: a date (inclusive, in the series' timezone) or a UTC instant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Until {
+    Date(NaiveDate),
+    Time(DateTime<Utc>),
+}
+
+/// The subset of an RFC 5545 `RRULE` that todos support: `FREQ` (daily and
+/// up), `INTERVAL`, `COUNT` or `UNTIL`, `BYMONTH`, `BYMONTHDAY`, `BYDAY` and
+/// `WKST`. Parsed case-insensitively; [`fmt::Display`] gives the canonical form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    pub by_month: Vec<u32>,
+    pub by_month_day: Vec<i8>,
+    pub by_day: Vec<WeekdayNum>,
+    pub week_start: Weekday,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RRuleError {
+    #[error("FREQ is required")]
+    MissingFreq,
+    #[error("{0:?} is not a NAME=VALUE rule part")]
+    Malformed(String),
+    #[error("{0} appears more than once")]
+    Duplicate(String),
+    #[error("{0} is not supported")]
+    Unsupported(String),
+    #[error("invalid {0} value")]
+    InvalidValue(&'static str),
+    #[error("{0}")]
+    Conflict(&'static str),
+}
+
+impl RRule {
+    pub fn new(freq: Frequency) -> Self {
+        Self {
+            freq,
+            interval: 1,
+            count: None,
+            until: None,
+            by_month: Vec::new(),
+            by_month_day: Vec::new(),
+            by_day: Vec::new(),
+            week_start: Weekday::Mon,
+        }
+    }
+
+    fn check(self) -> Result<Self, RRuleError> {
+        if self.count.is_some() && self.until.is_some() {
+            return Err(RRuleError::Conflict("COUNT and UNTIL cannot both be set"));
+        }
+        if self.freq == Frequency::Weekly && !self.by_month_day.is_empty() {
+            return Err(RRuleError::Conflict(
+                "BYMONTHDAY cannot be used with FREQ=WEEKLY",
+            ));
+        }
+        let ordinals = self.by_day.iter().any(|d| d.ordinal.is_some());
+        if ordinals && matches!(self.freq, Frequency::Daily | Frequency::Weekly) {
+            return Err(RRuleError::Conflict(
+                "numbered BYDAY values need FREQ=MONTHLY or YEARLY",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .into_iter()
+    .find(|day| weekday_code(*day) == code)
+}
+
+fn parse_list<T>(
+    value: &str,
+    part: &'static str,
+    item: impl Fn(&str) -> Option<T>,
+) -> Result<Vec<T>, RRuleError> {
+    value
+        .split(',')
+        .map(|v| item(v).ok_or(RRuleError::InvalidValue(part)))
+        .collect()
+}
+
+fn parse_signed(v: &str, max: i8) -> Option<i8> {
+    let n: i8 = v.strip_prefix('+').unwrap_or(v).parse().ok()?;
+    (n != 0 && n.abs() <= max).then_some(n)
+}
+
+fn parse_until(v: &str) -> Option<Until> {
+    if let Some(time) = v.strip_suffix('Z') {
+        let at = NaiveDateTime::parse_from_str(time, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Until::Time(at.and_utc()));
+    }
+    // A floating date-time has no meaning for a zoned series (RFC 5545 §3.3.10)
+    NaiveDate::parse_from_str(v, "%Y%m%d")
+        .ok()
+        .filter(|_| v.len() == 8)
+        .map(Until::Date)
+}
+
+impl FromStr for RRule {
+    type Err = RRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        let body = upper.strip_prefix("RRULE:").unwrap_or(&upper);
+        let mut seen = HashSet::new();
+        let mut freq = None;
+        let mut rule = RRule::new(Frequency::Daily);
+
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| RRuleError::Malformed(part.to_owned()))?;
+            if !seen.insert(name) {
+                return Err(RRuleError::Duplicate(name.to_owned()));
+            }
+            match name {
+                "FREQ" => {
+                    freq = Some(
+                        Frequency::ALL
+                            .into_iter()
+                            .find(|f| f.as_str() == value)
+                            .ok_or_else(|| match value {
+                                "HOURLY" | "MINUTELY" | "SECONDLY" => {
+                                    RRuleError::Unsupported(format!("FREQ={value}"))
+                                }
+                                _ => RRuleError::InvalidValue("FREQ"),
+                            })?,
+                    )
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|n| (1..=MAX_INTERVAL).contains(n))
+                        .ok_or(RRuleError::InvalidValue("INTERVAL"))?
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|n| (1..=MAX_COUNT).contains(n))
+                            .ok_or(RRuleError::InvalidValue("COUNT"))?,
+                    )
+                }
+                "UNTIL" => {
+                    rule.until = Some(parse_until(value).ok_or(RRuleError::InvalidValue("UNTIL"))?)
+                }
+                "BYMONTH" => {
+                    rule.by_month = parse_list(value, "BYMONTH", |v| {
+                        v.parse().ok().filter(|m| (1..=12).contains(m))
+                    })?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = parse_list(value, "BYMONTHDAY", |v| parse_signed(v, 31))?
+                }
+                "BYDAY" => {
+                    rule.by_day = parse_list(value, "BYDAY", |v| {
+                        let split = v.len().checked_sub(2)?;
+                        let (ordinal, code) = (v.get(..split)?, v.get(split..)?);
+                        let ordinal = match ordinal {
+                            "" => None,
+                            n => Some(parse_signed(n, 53)?),
+                        };
+                        Some(WeekdayNum {
+                            ordinal,
+                            weekday: parse_weekday(code)?,
+                        })
+                    })?
+                }
+                "WKST" => {
+                    rule.week_start =
+                        parse_weekday(value).ok_or(RRuleError::InvalidValue("WKST"))?
+                }
+                _ => return Err(RRuleError::Unsupported(name.to_owned())),
+            }
+        }
+
+        rule.freq = freq.ok_or(RRuleError::MissingFreq)?;
+        rule.check()
+    }
+}
+
+impl fmt::Display for RRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.freq.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        match self.until {
+            Some(Until::Date(date)) => write!(f, ";UNTIL={}", date.format("%Y%m%d"))?,
+            Some(Until::Time(at)) => write!(f, ";UNTIL={}", at.format("%Y%m%dT%H%M%SZ"))?,
+            None => {}
+        }
+        let join = |items: Vec<String>| items.join(",");
+        if !self.by_month.is_empty() {
+            let months = self.by_month.iter().map(u32::to_string).collect();
+            write!(f, ";BYMONTH={}", join(months))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days = self.by_month_day.iter().map(i8::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", join(days))?;
+        }
+        if !self.by_day.is_empty() {
+            let days = self
+                .by_day
+                .iter()
+                .map(|d| match d.ordinal {
+                    Some(n) => format!("{n}{}", weekday_code(d.weekday)),
+                    None => weekday_code(d.weekday).to_owned(),
+                })
+                .collect();
+            write!(f, ";BYDAY={}", join(days))?;
+        }
+        if self.week_start != Weekday::Mon {
+            write!(f, ";WKST={}", weekday_code(self.week_start))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_normalizes() {
+        let rule: RRule = "rrule:freq=monthly;byday=-1fr,+2MO;interval=2;until=20261231"
+            .parse()
+            .unwrap();
+        assert_eq!(rule.freq, Frequency::Monthly);
+        assert_eq!(
+            rule.by_day[0],
+            WeekdayNum {
+                ordinal: Some(-1),
+                weekday: Weekday::Fri
+            }
+        );
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=MONTHLY;INTERVAL=2;UNTIL=20261231;BYDAY=-1FR,2MO"
+        );
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_expand() {
+        let err = |s: &str| s.parse::<RRule>().unwrap_err();
+        assert_eq!(err("INTERVAL=2"), RRuleError::MissingFreq);
+        assert_eq!(
+            err("FREQ=HOURLY"),
+            RRuleError::Unsupported("FREQ=HOURLY".into())
+        );
+        assert_eq!(
+            err("FREQ=DAILY;BYSETPOS=1"),
+            RRuleError::Unsupported("BYSETPOS".into())
+        );
+        assert_eq!(
+            err("FREQ=DAILY;FREQ=WEEKLY"),
+            RRuleError::Duplicate("FREQ".into())
+        );
+        assert_eq!(
+            err("FREQ=DAILY;UNTIL=20260101T090000"),
+            RRuleError::InvalidValue("UNTIL")
+        );
+        assert_eq!(
+            err("FREQ=DAILY;COUNT=10001"),
+            RRuleError::InvalidValue("COUNT")
+        );
+        assert!(matches!(
+            err("FREQ=DAILY;COUNT=3;UNTIL=20260101"),
+            RRuleError::Conflict(_)
+        ));
+        assert!(matches!(
+            err("FREQ=WEEKLY;BYDAY=1MO"),
+            RRuleError::Conflict(_)
+        ));
+        assert_eq!(
+            err("FREQ=MONTHLY;BYMONTHDAY=0"),
+            RRuleError::InvalidValue("BYMONTHDAY")
+        );
+    }
+}
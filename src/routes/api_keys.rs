@@ -0,0 +1,102 @@
+use crate::{
+    auth::{Permissions, Principal, generate_api_key},
+    config::AppState,
+    error::ApiError,
+    extract::{Json, Path, ValidatedJson},
+    models::{ApiKey, CreateApiKey, CreatedApiKey},
+};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+
+const API_KEY_COLUMNS: &str =
+    "id, name, prefix, scopes, user_id, created_at, expires_at, last_used_at, revoked_at";
+
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    principal: Principal,
+    permissions: Permissions,
+    ValidatedJson(json): ValidatedJson<CreateApiKey>,
+) -> Result<impl IntoResponse, ApiError> {
+    // A scoped caller may only mint keys for its own user; `user_id: null`
+    // would be an unscoped key that sees every user's todos
+    if let Some(owner) = principal.owner_scope()
+        && json.user_id != Some(owner)
+    {
+        return Err(ApiError::Forbidden(
+            "Cannot create API keys for another user".to_owned(),
+        ));
+    }
+    // Callers may only hand out scopes they hold themselves
+    if let Some(scope) = json.scopes.iter().find(|s| !permissions.contains(**s)) {
+        return Err(ApiError::Forbidden(format!(
+            "Cannot grant scope {scope} that the caller does not hold"
+        )));
+    }
+
+    let generated = generate_api_key();
+    let scopes: Vec<&str> = json.scopes.iter().map(|s| s.as_str()).collect();
+    let sql = format!(
+        r#"
+        INSERT INTO api_keys (name, prefix, key_hash, scopes, user_id, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {API_KEY_COLUMNS}
+        "#
+    );
+    let api_key = sqlx::query_as::<_, ApiKey>(&sql)
+        .bind(&json.name)
+        .bind(&generated.prefix)
+        .bind(&generated.hash)
+        .bind(&scopes)
+        .bind(json.user_id)
+        .bind(json.expires_at)
+        .fetch_one(&state.pool)
+        .await?;
+
+    tracing::info!(api_key_id = api_key.id, prefix = %api_key.prefix, "API key created");
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKey {
+            api_key,
+            key: generated.key,
+        }),
+    ))
+}
+
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    principal: Principal,
+) -> Result<impl IntoResponse, ApiError> {
+    let sql = format!(
+        "SELECT {API_KEY_COLUMNS} FROM api_keys \
+         WHERE ($1::BIGINT IS NULL OR user_id = $1) ORDER BY id"
+    );
+    let keys = sqlx::query_as::<_, ApiKey>(&sql)
+        .bind(principal.owner_scope())
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(keys))
+}
+
+/// Revocation is permanent; the row is kept so the listing shows when it happened.
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let res = sqlx::query(
+        r#"
+        UPDATE api_keys SET revoked_at = COALESCE(revoked_at, now())
+        WHERE id = $1 AND ($2::BIGINT IS NULL OR user_id = $2)
+        "#,
+    )
+    .bind(id)
+    .bind(principal.owner_scope())
+    .execute(&state.pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(ApiError::not_found("API key", id));
+    }
+    tracing::info!(api_key_id = id, "API key revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,57 @@
+use crate::{
+    config::AppState,
+    error::ApiError,
+    extract::{Json, Query},
+    models::{
+        AuditEvent, AuditEventQuery, AuditPage, DEFAULT_PAGE_LIMIT, ListQueryError, MAX_PAGE_LIMIT,
+    },
+};
+use axum::{extract::State, response::IntoResponse};
+
+pub async fn list_audit_events(
+    State(state): State<AppState>,
+    Query(query): Query<AuditEventQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+        return Err(ListQueryError::LimitOutOfRange.into());
+    }
+
+    let mut items = sqlx::query_as::<_, AuditEvent>(
+        r#"
+        SELECT id, occurred_at, principal, method, route, path, status,
+               source_ip, request_id, body_digest, prev_hash, hash
+        FROM audit_events
+        WHERE ($1::TEXT IS NULL OR principal = $1)
+          AND ($2::TEXT IS NULL OR method = upper($2))
+          AND ($3::TEXT IS NULL OR route = $3)
+          AND ($4::INTEGER IS NULL OR status = $4)
+          AND ($5::TEXT IS NULL OR request_id = $5)
+          AND ($6::TIMESTAMPTZ IS NULL OR occurred_at >= $6)
+          AND ($7::TIMESTAMPTZ IS NULL OR occurred_at < $7)
+          AND ($8::BIGINT IS NULL OR id < $8)
+        ORDER BY id DESC
+        LIMIT $9
+        "#,
+    )
+    .bind(&query.principal)
+    .bind(&query.method)
+    .bind(&query.route)
+    .bind(query.status)
+    .bind(&query.request_id)
+    .bind(query.since)
+    .bind(query.until)
+    .bind(query.before)
+    .bind(i64::from(limit) + 1)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let next_before = if items.len() > limit as usize {
+        items.truncate(limit as usize);
+        items.last().map(|last| last.id)
+    } else {
+        None
+    };
+
+    Ok(Json(AuditPage { items, next_before }))
+}
@@ -0,0 +1,213 @@
+use crate::{
+    auth::{
+        AuthUser, Role, hash_password, issue_refresh_token, revoke_refresh_token,
+        rotate_refresh_token, verify_password,
+    },
+    config::AppState,
+    error::ApiError,
+    extract::{Json, ValidatedJson},
+    models::{LoginRequest, RefreshRequest, RegisterUser, TokenResponse, UpdateProfile, User},
+};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+
+pub async fn register(
+    State(state): State<AppState>,
+    ValidatedJson(json): ValidatedJson<RegisterUser>,
+) -> Result<impl IntoResponse, ApiError> {
+    let password_hash = hash_password(json.password).await?;
+
+    // A taken username (any case) trips the unique index and maps to 409
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (username, password_hash)
+        VALUES ($1, $2)
+        RETURNING id, username, role, timezone
+        "#,
+    )
+    .bind(&json.username)
+    .bind(&password_hash)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+/// Password grant: exchanges credentials for an access token and a fresh
+/// refresh token family.
+pub async fn token(
+    State(state): State<AppState>,
+    Json(json): Json<LoginRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (id, role) = check_credentials(&state, json).await?;
+    let refresh_token =
+        issue_refresh_token(&state.pool, id, state.cfg.auth.refresh_token_ttl).await?;
+    Ok(Json(token_response(&state, id, role, refresh_token)?))
+}
+
+/// Verifies a username and password, returning the user's id and role.
+pub(crate) async fn check_credentials(
+    state: &AppState,
+    json: LoginRequest,
+) -> Result<(i64, Role), ApiError> {
+    let row: Option<(i64, Role, Option<String>)> = sqlx::query_as(
+        "SELECT id, role, password_hash FROM users WHERE lower(username) = lower($1)",
+    )
+    .bind(&json.username)
+    .fetch_optional(&state.pool)
+    .await?;
+
+    // Unknown users (and OIDC-only users, who have no password) still pay for
+    // a hash verification so timing doesn't reveal them
+    let hash = row.as_ref().and_then(|(_, _, hash)| hash.clone());
+    if !verify_password(json.password, hash).await {
+        return Err(ApiError::Unauthorized(
+            "Invalid username or password".to_owned(),
+        ));
+    }
+    let (id, role, _) = row.expect("verified password implies a stored user");
+    Ok((id, role))
+}
+
+/// Rotates a refresh token: the presented one is spent and a successor returned.
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(json): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rotated = rotate_refresh_token(
+        &state.pool,
+        &json.refresh_token,
+        state.cfg.auth.refresh_token_ttl,
+    )
+    .await?;
+
+    // Re-read the role so a changed role reaches the client within one refresh
+    let (role,): (Role,) = sqlx::query_as("SELECT role FROM users WHERE id = $1")
+        .bind(rotated.user_id)
+        .fetch_one(&state.pool)
+        .await?;
+
+    Ok(Json(token_response(
+        &state,
+        rotated.user_id,
+        role,
+        rotated.refresh_token,
+    )?))
+}
+
+/// Revokes the refresh token's whole family. Always 204, so it can't be used to
+/// probe which tokens exist.
+pub async fn logout(
+    State(state): State<AppState>,
+    Json(json): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    revoke_refresh_token(&state.pool, &json.refresh_token).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn me(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let user =
+        sqlx::query_as::<_, User>("SELECT id, username, role, timezone FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or_else(|| ApiError::not_found("User", user.id))?;
+
+    Ok(Json(user))
+}
+
+/// Changes the caller's own preferences; absent fields keep their value.
+pub async fn update_me(
+    State(state): State<AppState>,
+    user: AuthUser,
+    ValidatedJson(json): ValidatedJson<UpdateProfile>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users SET timezone = COALESCE($2, timezone)
+        WHERE id = $1
+        RETURNING id, username, role, timezone
+        "#,
+    )
+    .bind(user.id)
+    .bind(&json.timezone)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found("User", user.id))?;
+
+    Ok(Json(user))
+}
+
+pub(crate) fn token_response(
+    state: &AppState,
+    user_id: i64,
+    role: Role,
+    refresh_token: String,
+) -> Result<TokenResponse, ApiError> {
+    let tokens = state.auth.tokens();
+    Ok(TokenResponse {
+        access_token: tokens.issue(user_id, role)?,
+        token_type: "Bearer",
+        expires_in: tokens.ttl().as_secs(),
+        refresh_token,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::{
+        Router,
+        routing::{get, post},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    fn lazy_state() -> AppState {
+        let pool = PgPool::connect_lazy("postgres://127.0.0.1/postgres").expect("lazy pool");
+        AppState::new(pool, ServerConfig::default())
+    }
+
+    #[tokio::test]
+    async fn register_rejects_short_credentials() {
+        let app = Router::new()
+            .route("/auth/register", post(register))
+            .with_state(lazy_state());
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/auth/register")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"username":"ab","password":"short"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn me_requires_a_principal() {
+        let app = Router::new()
+            .route("/auth/me", get(me))
+            .with_state(lazy_state());
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/auth/me")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
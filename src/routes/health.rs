@@ -0,0 +1,196 @@
+// src/routes/health.rs
+use crate::{
+    config::AppState,
+    extract::Json,
+    models::{
+        ComponentStatus, DatabaseCheck, GIT_SHA, HealthReport, MigrationsCheck, PoolCheck,
+        ReadinessComponents, VERSION,
+    },
+    runtime::MIGRATOR,
+};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use std::time::{Duration, Instant};
+
+/// Upper bound for each dependency check so a hung database can't hang the probe.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Process liveness: no I/O, so it stays cheap under load.
+pub async fn live(State(state): State<AppState>) -> impl IntoResponse {
+    Json(HealthReport {
+        status: ComponentStatus::Ok,
+        uptime_secs: state.started_at.elapsed().as_secs(),
+        version: VERSION,
+        git_sha: GIT_SHA,
+        components: None,
+    })
+}
+
+/// Readiness: 200 while the database is reachable and fully migrated, 503 otherwise.
+pub async fn ready(State(state): State<AppState>) -> impl IntoResponse {
+    let database = check_database(&state).await;
+    let migrations = if database.status == ComponentStatus::Down {
+        MigrationsCheck {
+            status: ComponentStatus::Down,
+            expected: MIGRATOR.iter().count(),
+            applied: 0,
+        }
+    } else {
+        check_migrations(&state).await
+    };
+    let components = ReadinessComponents {
+        database,
+        migrations,
+        pool: check_pool(&state),
+    };
+
+    let status = components.overall();
+    let code = if status == ComponentStatus::Down {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    (
+        code,
+        Json(HealthReport {
+            status,
+            uptime_secs: state.started_at.elapsed().as_secs(),
+            version: VERSION,
+            git_sha: GIT_SHA,
+            components: Some(components),
+        }),
+    )
+}
+
+async fn check_database(state: &AppState) -> DatabaseCheck {
+    let started = Instant::now();
+    let ping = sqlx::query("SELECT 1").execute(&state.pool);
+    let error = match tokio::time::timeout(CHECK_TIMEOUT, ping).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => {
+            tracing::warn!(error = %e, "readiness: database ping failed");
+            Some("database ping failed".to_owned())
+        }
+        Err(_) => {
+            tracing::warn!("readiness: database ping timed out");
+            Some("database ping timed out".to_owned())
+        }
+    };
+
+    DatabaseCheck {
+        status: if error.is_none() {
+            ComponentStatus::Ok
+        } else {
+            ComponentStatus::Down
+        },
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+async fn check_migrations(state: &AppState) -> MigrationsCheck {
+    let expected: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+    let applied = sqlx::query_scalar::<_, i64>(
+        "SELECT version FROM _sqlx_migrations WHERE success ORDER BY version",
+    )
+    .fetch_all(&state.pool);
+
+    let applied = match tokio::time::timeout(CHECK_TIMEOUT, applied).await {
+        Ok(Ok(applied)) => applied,
+        Ok(Err(e)) => {
+            tracing::warn!(error = %e, "readiness: could not read migration history");
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    };
+
+    let complete = expected.iter().all(|v| applied.contains(v));
+    MigrationsCheck {
+        status: if complete {
+            ComponentStatus::Ok
+        } else {
+            ComponentStatus::Down
+        },
+        expected: expected.len(),
+        applied: applied.len(),
+    }
+}
+
+fn check_pool(state: &AppState) -> PoolCheck {
+    let size = state.pool.size();
+    let idle = state.pool.num_idle();
+    let max = state.pool.options().get_max_connections();
+    let in_use = (size as usize).saturating_sub(idle);
+    let saturation = if max == 0 {
+        0.0
+    } else {
+        in_use as f64 / f64::from(max)
+    };
+
+    PoolCheck {
+        status: if saturation >= 1.0 {
+            ComponentStatus::Degraded
+        } else {
+            ComponentStatus::Ok
+        },
+        size,
+        idle,
+        max,
+        saturation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use sqlx::postgres::PgPoolOptions;
+    use tower::ServiceExt;
+
+    fn unreachable_db_state() -> AppState {
+        // Nothing listens on port 1, so every acquire fails fast
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy("postgres://127.0.0.1:1/postgres")
+            .expect("lazy pool");
+        AppState::new(pool, ServerConfig::default())
+    }
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let res = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn live_does_not_touch_the_database() {
+        let app = Router::new()
+            .route("/health/live", get(live))
+            .with_state(unreachable_db_state());
+
+        let (status, body) = get_json(app, "/health/live").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["version"], VERSION);
+    }
+
+    #[tokio::test]
+    async fn ready_is_503_when_database_is_down() {
+        let app = Router::new()
+            .route("/health/ready", get(ready))
+            .with_state(unreachable_db_state());
+
+        let (status, body) = get_json(app, "/health/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "down");
+        assert_eq!(body["components"]["database"]["status"], "down");
+        assert_eq!(body["components"]["migrations"]["status"], "down");
+    }
+}
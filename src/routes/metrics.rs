@@ -0,0 +1,19 @@
+// src/routes/metrics.rs
+use crate::config::AppState;
+use axum::{
+    extract::State,
+    http::{HeaderValue, header},
+    response::IntoResponse,
+};
+
+/// Prometheus scrape endpoint.
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    state.metrics.observe_pool(&state.pool);
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+        )],
+        state.metrics.render(),
+    )
+}
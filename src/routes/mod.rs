@@ -1,3 +1,28 @@
+mod api_keys;
+mod audit;
+mod auth;
+mod health;
+mod metrics;
+mod oidc;
 #[allow(clippy::module_inception)]
 mod routes;
-pub use routes::{create_todo, get_all_todos, health};
+mod series;
+mod sessions;
+mod tags;
+mod users;
+pub use api_keys::{create_api_key, list_api_keys, revoke_api_key};
+pub use audit::list_audit_events;
+pub use auth::{logout, me, refresh, register, token, update_me};
+pub use health::{live, ready};
+pub use metrics::metrics;
+pub use oidc::{oidc_callback, oidc_login};
+pub use routes::{
+    create_todo, delete_todo, empty_trash, get_all_todos, get_todo, health, list_trash, purge_todo,
+    replace_todo, restore_todo, update_todo,
+};
+pub use series::list_occurrences;
+pub use sessions::{
+    create_session, current_session, delete_session, list_sessions, revoke_session,
+};
+pub use tags::{create_tag, delete_tag, get_tag, list_tags, merge_tag, rename_tag};
+pub use users::{list_users, set_user_role};
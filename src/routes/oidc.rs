@@ -0,0 +1,83 @@
+use crate::{
+    auth::{
+        issue_refresh_token,
+        oidc::{OidcProvider, begin_login, browser_state, complete_login, state_cookie},
+    },
+    config::AppState,
+    error::ApiError,
+    extract::{Json, Query},
+    models::OidcCallback,
+    routes::auth::token_response,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, header},
+    response::{IntoResponse, Redirect},
+};
+use std::time::Duration;
+
+/// Sends the browser to the identity provider, remembering the login's
+/// `state` in a cookie.
+pub async fn oidc_login(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let provider = provider(&state)?;
+    let login = begin_login(&state.pool, provider).await?;
+    let cookie = state_cookie(
+        &login.state,
+        provider.config().login_ttl,
+        state.cfg.auth.session.secure,
+    );
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(&login.url)))
+}
+
+/// Where the identity provider sends the browser back. Answers with the same
+/// token pair as `POST /auth/token`.
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(callback): Query<OidcCallback>,
+) -> Result<impl IntoResponse, ApiError> {
+    let provider = provider(&state)?;
+    let Some(code) = callback.code else {
+        tracing::info!(
+            error = callback.error.as_deref().unwrap_or("missing code"),
+            description = callback.error_description.as_deref().unwrap_or(""),
+            "OIDC login did not complete"
+        );
+        return Err(ApiError::Unauthorized("Login was rejected".to_owned()));
+    };
+
+    let login = complete_login(
+        &state.pool,
+        provider,
+        browser_state(&headers),
+        &callback.state,
+        &code,
+    )
+    .await?;
+    tracing::info!(
+        user_id = login.user_id,
+        created = login.created,
+        "OIDC login"
+    );
+
+    let refresh_token =
+        issue_refresh_token(&state.pool, login.user_id, state.cfg.auth.refresh_token_ttl).await?;
+    let clear = state_cookie("", Duration::ZERO, state.cfg.auth.session.secure);
+    Ok((
+        [(header::SET_COOKIE, clear)],
+        Json(token_response(
+            &state,
+            login.user_id,
+            login.role,
+            refresh_token,
+        )?),
+    ))
+}
+
+/// The routes are only mounted with OIDC configured; this is belt and braces.
+fn provider(state: &AppState) -> Result<&OidcProvider, ApiError> {
+    state
+        .oidc
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("OIDC login is not configured".to_owned()))
+}
@@ -1,13 +1,37 @@
 use crate::{
+    auth::Principal,
     config::AppState,
-    models::{CreateTodo, Todo},
+    error::ApiError,
+    extract::{Json, Path, Preconditions, Query, Tagged, ValidatedJson, Versioned},
+    models::{
+        CreateTodo, Cursor, ScopeQuery, SeriesScope, TagMatch, Todo, TodoFilter, TodoListQuery,
+        TodoPage, TodoSort, UpdatedTodo,
+    },
+    recurrence::RRule,
+    routes::{
+        series::{create_next_occurrence, delete_series, start_series, update_series},
+        tags::set_todo_tags,
+    },
 };
 use axum::{
-    Json as JsonData,
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode, Uri, header},
+    response::{IntoResponse, Response},
 };
+use chrono_tz::Tz;
+use sqlx::{Postgres, Transaction};
+
+/// Columns selected into [`Todo`]; tags are aggregated into a JSON array,
+/// and a recurring todo's rule comes from its series.
+const TODO_COLUMNS: &str = r#"
+    id, title, description, done, due_at, priority, completed_at, owner_id, version,
+    created_at, updated_at, deleted_at,
+    (SELECT COALESCE(json_agg(json_build_object('id', t.id, 'name', t.name) ORDER BY lower(t.name), t.id), '[]')
+       FROM todo_tags tt JOIN tags t ON t.id = tt.tag_id
+      WHERE tt.todo_id = todos.id) AS tags,
+    series_id, occurrence_at,
+    (SELECT rrule FROM todo_series s WHERE s.id = todos.series_id) AS rrule
+"#;
 
 pub async fn health() -> impl IntoResponse {
     (StatusCode::OK, "This is a health check")
@@ -15,66 +39,512 @@ pub async fn health() -> impl IntoResponse {
 
 pub async fn get_all_todos(
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    let todos = match sqlx::query_as::<_, Todo>("SELECT * FROM todos")
-        .fetch_all(&state.pool)
-        .await
-    {
-        Ok(todos) => todos,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to fetch all To-Dos: {}", e),
-            ));
+    principal: Principal,
+    uri: Uri,
+    Query(query): Query<TodoListQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    list_todos(&state, &principal, &uri, query, false).await
+}
+
+/// Position of a priority in `sort=priority` order, matching `Priority::ALL`.
+fn priority_rank(expr: &str) -> String {
+    format!("array_position(ARRAY['urgent', 'high', 'normal', 'low'], {expr})")
+}
+
+/// `ORDER BY` list for a sort, and the row comparison that selects rows
+/// strictly after the cursor ($3 id, $11 priority, $12 due_at). Undated
+/// todos sort as if due at infinity, so they come last and stay comparable.
+fn sort_sql(sort: TodoSort) -> (String, String) {
+    let due = "COALESCE(due_at, 'infinity')";
+    let last_due = "COALESCE($12::TIMESTAMPTZ, 'infinity')";
+    match sort {
+        TodoSort::IdAsc => ("id ASC".to_owned(), "id > $3".to_owned()),
+        TodoSort::IdDesc => ("id DESC".to_owned(), "id < $3".to_owned()),
+        TodoSort::Priority => {
+            let (rank, last_rank) = (priority_rank("priority"), priority_rank("$11::TEXT"));
+            (
+                format!("{rank}, {due}, id"),
+                format!("({rank}, {due}, id) > ({last_rank}, {last_due}, $3)"),
+            )
         }
+        TodoSort::Due => (
+            format!("{due}, id"),
+            format!("({due}, id) > ({last_due}, $3)"),
+        ),
+    }
+}
+
+/// The caller's timezone preference; the admin token and unbound API keys
+/// have no user row and get UTC.
+pub(super) async fn caller_timezone(
+    state: &AppState,
+    principal: &Principal,
+) -> Result<Tz, ApiError> {
+    let Some(user_id) = principal.owner_scope() else {
+        return Ok(Tz::UTC);
     };
+    let name: Option<String> = sqlx::query_scalar("SELECT timezone FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&state.pool)
+        .await?;
+    Ok(name.and_then(|n| n.parse().ok()).unwrap_or(Tz::UTC))
+}
 
-    Ok(Json(todos))
+/// `GET /todos/trash`: same paging and filters as `GET /todos`, over
+/// soft-deleted todos.
+pub async fn list_trash(
+    State(state): State<AppState>,
+    principal: Principal,
+    uri: Uri,
+    Query(query): Query<TodoListQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    list_todos(&state, &principal, &uri, query, true).await
+}
+
+async fn list_todos(
+    state: &AppState,
+    principal: &Principal,
+    uri: &Uri,
+    query: TodoListQuery,
+    trashed: bool,
+) -> Result<impl IntoResponse + use<>, ApiError> {
+    let mut query = query.with_tags(uri.query());
+    if query.needs_timezone() {
+        query = query.with_timezone(caller_timezone(state, principal).await?);
+    }
+    let filter = TodoFilter::try_from(query)?;
+    let (order_by, keyset) = sort_sql(filter.sort);
+
+    // Keyset pagination on the sort key, ending in the BIGSERIAL id: rows
+    // inserted while a client pages through never shift earlier pages. One
+    // extra row tells us if there is more.
+    let sql = format!(
+        r#"
+        SELECT {TODO_COLUMNS} FROM todos
+        WHERE ($1::BOOLEAN IS NULL OR done = $1)
+          AND ($2::TEXT IS NULL OR title ILIKE $2 OR description ILIKE $2)
+          AND ($3::BIGINT IS NULL OR {keyset})
+          AND ($5::BIGINT IS NULL OR owner_id = $5)
+          AND (cardinality($6::TEXT[]) = 0 OR (
+                SELECT count(*) FROM todo_tags tt JOIN tags t ON t.id = tt.tag_id
+                 WHERE tt.todo_id = todos.id AND lower(t.name) = ANY($6)
+              ) >= CASE WHEN $7 THEN cardinality($6::TEXT[]) ELSE 1 END)
+          AND ($8::BOOLEAN IS NULL OR COALESCE(NOT done AND due_at < now(), FALSE) = $8)
+          AND ($9::TIMESTAMPTZ IS NULL OR due_at >= $9)
+          AND ($10::TIMESTAMPTZ IS NULL OR due_at < $10)
+          AND {live}
+        ORDER BY {order_by}
+        LIMIT $4
+        "#,
+        live = trash_condition(trashed),
+    );
+    let mut todos = sqlx::query_as::<_, Todo>(&sql)
+        .bind(filter.done)
+        .bind(&filter.pattern)
+        .bind(filter.after.map(|c| c.last_id))
+        .bind(i64::from(filter.limit) + 1)
+        .bind(principal.owner_scope())
+        .bind(&filter.tags)
+        .bind(filter.tag_match == TagMatch::All)
+        .bind(filter.overdue)
+        .bind(filter.due_after)
+        .bind(filter.due_before)
+        .bind(filter.after.map(|c| c.last_priority))
+        .bind(filter.after.and_then(|c| c.last_due_at))
+        .fetch_all(&state.pool)
+        .await?;
+
+    let next_cursor = if todos.len() > filter.limit as usize {
+        todos.truncate(filter.limit as usize);
+        todos
+            .last()
+            .map(|last| Cursor::after(filter.sort, last).encode())
+    } else {
+        None
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Some(cursor) = &next_cursor
+        && let Ok(link) = HeaderValue::from_str(&next_link(uri, cursor))
+    {
+        headers.insert(header::LINK, link);
+    }
+
+    Ok((
+        headers,
+        Json(TodoPage {
+            items: todos,
+            next_cursor,
+        }),
+    ))
+}
+
+fn trash_condition(trashed: bool) -> &'static str {
+    if trashed {
+        "deleted_at IS NOT NULL"
+    } else {
+        "deleted_at IS NULL"
+    }
+}
+
+/// `Link: <...>; rel="next"` pointing at the same query with the cursor swapped in.
+fn next_link(uri: &Uri, cursor: &str) -> String {
+    let mut query: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|kv| !kv.is_empty() && !kv.starts_with("cursor="))
+        .collect();
+    let cursor = format!("cursor={}", cursor);
+    query.push(&cursor);
+    format!("<{}?{}>; rel=\"next\"", uri.path(), query.join("&"))
 }
 
 pub async fn create_todo(
     State(state): State<AppState>,
-    JsonData(json): Json<CreateTodo>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    // Let the database assign BIGSERIAL id and return the inserted row
-    let inserted = match sqlx::query_as::<_, Todo>(
+    principal: Principal,
+    ValidatedJson(json): ValidatedJson<CreateTodo>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+
+    // Let the database assign BIGSERIAL id; tags need it before the row is read back
+    let id: i64 = sqlx::query_scalar(
         r#"
-        INSERT INTO todos (title, description, done)
-        VALUES ($1, $2, $3)
-        RETURNING id, title, description, done
+        INSERT INTO todos (title, description, done, due_at, priority, owner_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
         "#,
     )
     .bind(&json.title)
     .bind(&json.description)
     .bind(json.done)
-    .fetch_one(&state.pool)
-    .await
+    .bind(json.due_at)
+    .bind(json.priority)
+    .bind(principal.owner_scope())
+    .fetch_one(&mut *tx)
+    .await?;
+    set_todo_tags(&mut tx, id, principal.owner_scope(), &json.tags).await?;
+    let mut inserted = fetch_todo(&mut tx, id).await?;
+    if let Some(rule) = &json.rrule {
+        start_recurring(&mut tx, &state, &principal, &inserted, rule).await?;
+        inserted = fetch_todo(&mut tx, id).await?;
+    }
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Tagged(inserted)))
+}
+
+async fn fetch_todo(tx: &mut Transaction<'_, Postgres>, id: i64) -> Result<Todo, ApiError> {
+    let todo =
+        sqlx::query_as::<_, Todo>(&format!("SELECT {TODO_COLUMNS} FROM todos WHERE id = $1"))
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await?;
+    Ok(todo)
+}
+
+pub async fn get_todo(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    preconditions: Preconditions,
+) -> Result<Response, ApiError> {
+    // Other users' todos are reported as missing rather than forbidden
+    let sql = format!(
+        "SELECT {TODO_COLUMNS} FROM todos WHERE id = $1 AND ($2::BIGINT IS NULL OR owner_id = $2) AND deleted_at IS NULL"
+    );
+    let todo = sqlx::query_as::<_, Todo>(&sql)
+        .bind(id)
+        .bind(principal.owner_scope())
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Todo", id))?;
+
+    if let Some(not_modified) = preconditions.check_read(&todo.etag())? {
+        return Ok(not_modified);
+    }
+    Ok(Tagged(todo).into_response())
+}
+
+/// Locks the live (or, with `trashed`, the soft-deleted) todo for the rest
+/// of `tx` and checks the request's preconditions against it, so no other
+/// write can slip in between the check and ours. Returns the locked row.
+async fn lock_todo(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i64,
+    principal: &Principal,
+    preconditions: &Preconditions,
+    trashed: bool,
+) -> Result<Todo, ApiError> {
+    let current = sqlx::query_as::<_, Todo>(&format!(
+        "SELECT {TODO_COLUMNS} FROM todos WHERE id = $1 AND ($2::BIGINT IS NULL OR owner_id = $2) AND {live} FOR UPDATE",
+        live = trash_condition(trashed),
+    ))
+    .bind(id)
+    .bind(principal.owner_scope())
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| ApiError::not_found("Todo", id))?;
+
+    preconditions.check_write(&current.etag())?;
+    Ok(current)
+}
+
+/// After a write to `current`, carries its series forward: an occurrence
+/// that was just completed or trashed while open makes way for the next.
+async fn advance_series(
+    tx: &mut Transaction<'_, Postgres>,
+    current: &Todo,
+    closed: bool,
+) -> Result<(), ApiError> {
+    if let (Some(series_id), Some(slot)) = (current.series_id, current.occurrence_at)
+        && closed
+        && !current.done
     {
-        Ok(todo) => todo,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to create Todo: {}", e),
+        create_next_occurrence(tx, series_id, slot).await?;
+    }
+    Ok(())
+}
+
+/// Turns the just-written one-off todo into the first occurrence of a series
+/// repeating in the caller's timezone.
+async fn start_recurring(
+    tx: &mut Transaction<'_, Postgres>,
+    state: &AppState,
+    principal: &Principal,
+    todo: &Todo,
+    rule: &str,
+) -> Result<(), ApiError> {
+    let rule: RRule = rule.parse()?;
+    let tz = caller_timezone(state, principal).await?;
+    let series_id = start_series(tx, todo.id, &rule, tz).await?;
+    if let (true, Some(due_at)) = (todo.done, todo.due_at) {
+        create_next_occurrence(tx, series_id, due_at).await?;
+    }
+    Ok(())
+}
+
+/// With `?scope=following` or `?scope=all` on a recurring todo, the change
+/// applies to the rest of its series or all of it; see [`update_series`].
+pub async fn update_todo(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    Query(scope): Query<ScopeQuery>,
+    preconditions: Preconditions,
+    ValidatedJson(json): ValidatedJson<UpdatedTodo>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    let current = lock_todo(&mut tx, id, &principal, &preconditions, false).await?;
+    if current.series_id.is_some() {
+        if scope.scope != SeriesScope::This {
+            update_series(&mut tx, &current, scope.scope, &json).await?;
+            let updated = fetch_todo(&mut tx, id).await?;
+            tx.commit().await?;
+            return Ok(Tagged(updated));
+        }
+        if json.rrule.is_some() {
+            return Err(ApiError::BadRequest(
+                "A series' rrule is changed with scope=following or scope=all".to_owned(),
             ));
         }
+    }
+    let retagged = match &json.tags {
+        Some(tags) => set_todo_tags(&mut tx, id, current.owner_id, tags).await?,
+        None => false,
     };
 
-    Ok((StatusCode::CREATED, Json(inserted)))
+    // Only the fields present in the body are changed; absent ones keep their
+    // value, and `"due_at": null` clears the due date. A tag change alone
+    // still counts as a change to the todo.
+    let sql = format!(
+        r#"
+        UPDATE todos
+        SET title = COALESCE($2, title),
+            description = COALESCE($3, description),
+            done = COALESCE($4, done),
+            version = version + $5,
+            due_at = CASE WHEN $6 THEN $7 ELSE due_at END,
+            priority = COALESCE($8, priority)
+        WHERE id = $1
+        RETURNING {TODO_COLUMNS}
+        "#
+    );
+    let mut updated = sqlx::query_as::<_, Todo>(&sql)
+        .bind(id)
+        .bind(&json.title)
+        .bind(&json.description)
+        .bind(json.done)
+        .bind(i64::from(retagged))
+        .bind(json.due_at.is_some())
+        .bind(json.due_at.flatten())
+        .bind(json.priority)
+        .fetch_one(&mut *tx)
+        .await?;
+    if let Some(Some(rule)) = &json.rrule {
+        start_recurring(&mut tx, &state, &principal, &updated, rule).await?;
+        updated = fetch_todo(&mut tx, id).await?;
+    } else {
+        advance_series(&mut tx, &current, updated.done).await?;
+    }
+    tx.commit().await?;
+
+    Ok(Tagged(updated))
+}
+
+pub async fn replace_todo(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    preconditions: Preconditions,
+    ValidatedJson(json): ValidatedJson<CreateTodo>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    let current = lock_todo(&mut tx, id, &principal, &preconditions, false).await?;
+    let rule = json.rrule.as_deref().map(str::parse::<RRule>).transpose()?;
+    if current.series_id.is_some()
+        && let Some(rule) = &rule
+        && current.rrule.as_deref() != Some(rule.to_string().as_str())
+    {
+        return Err(ApiError::BadRequest(
+            "A series' rrule is changed with PATCH and scope=following or scope=all".to_owned(),
+        ));
+    }
+    let retagged = set_todo_tags(&mut tx, id, current.owner_id, &json.tags).await?;
+
+    let sql = format!(
+        r#"
+        UPDATE todos
+        SET title = $2, description = $3, done = $4, version = version + $5,
+            due_at = $6, priority = $7
+        WHERE id = $1
+        RETURNING {TODO_COLUMNS}
+        "#
+    );
+    let mut replaced = sqlx::query_as::<_, Todo>(&sql)
+        .bind(id)
+        .bind(&json.title)
+        .bind(&json.description)
+        .bind(json.done)
+        .bind(i64::from(retagged))
+        .bind(json.due_at)
+        .bind(json.priority)
+        .fetch_one(&mut *tx)
+        .await?;
+    if let Some(rule) = &json.rrule
+        && current.series_id.is_none()
+    {
+        start_recurring(&mut tx, &state, &principal, &replaced, rule).await?;
+        replaced = fetch_todo(&mut tx, id).await?;
+    } else {
+        advance_series(&mut tx, &current, replaced.done).await?;
+    }
+    tx.commit().await?;
+
+    Ok(Tagged(replaced))
+}
+
+/// With `?scope=following` or `?scope=all` on a recurring todo, the rest of
+/// its series (or all of it) is trashed and ended; see [`delete_series`].
+pub async fn delete_todo(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    Query(scope): Query<ScopeQuery>,
+    preconditions: Preconditions,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    let current = lock_todo(&mut tx, id, &principal, &preconditions, false).await?;
+
+    if current.series_id.is_some() && scope.scope != SeriesScope::This {
+        delete_series(&mut tx, &current, scope.scope).await?;
+    } else {
+        // Soft delete: the row moves to the trash until restored or purged
+        sqlx::query("UPDATE todos SET deleted_at = now() WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        // Skipping an occurrence moves the series on, like completing it
+        advance_series(&mut tx, &current, true).await?;
+    }
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn restore_todo(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    preconditions: Preconditions,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    lock_todo(&mut tx, id, &principal, &preconditions, true).await?;
+
+    let sql = format!("UPDATE todos SET deleted_at = NULL WHERE id = $1 RETURNING {TODO_COLUMNS}");
+    let restored = sqlx::query_as::<_, Todo>(&sql)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(Tagged(restored))
+}
+
+/// Permanently deletes one trashed todo. Live todos have to be trashed first.
+pub async fn purge_todo(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    preconditions: Preconditions,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    lock_todo(&mut tx, id, &principal, &preconditions, true).await?;
+
+    sqlx::query("DELETE FROM todos WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Permanently deletes everything in the caller's trash.
+pub async fn empty_trash(
+    State(state): State<AppState>,
+    principal: Principal,
+) -> Result<impl IntoResponse, ApiError> {
+    sqlx::query(
+        "DELETE FROM todos WHERE deleted_at IS NOT NULL AND ($1::BIGINT IS NULL OR owner_id = $1)",
+    )
+    .bind(principal.owner_scope())
+    .execute(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::{Principal, Role};
     use crate::config::{AppState, ServerConfig};
     use axum::body::Body;
     use axum::http::{Request, StatusCode};
     use axum::{
-        Router,
-        routing::{get, post},
+        Extension, Router,
+        routing::{get, patch, post},
     };
     use sqlx::PgPool;
     use tower::ServiceExt;
 
+    fn lazy_state() -> AppState {
+        let cfg = ServerConfig::default();
+        let pool = PgPool::connect_lazy("postgres://127.0.0.1/postgres").expect("lazy pool");
+        AppState::new(pool, cfg)
+    }
+
     #[tokio::test]
     async fn health_unit_ok() {
         let app = Router::new().route("/health", get(health));
@@ -98,6 +568,10 @@ mod tests {
         let state = AppState::new(pool, cfg);
         let app = Router::new()
             .route("/todos", post(create_todo))
+            .layer(Extension(Principal::User {
+                id: 1,
+                role: Role::Member,
+            }))
             .with_state(state);
 
         let res = app
@@ -117,4 +591,151 @@ mod tests {
                 | StatusCode::UNSUPPORTED_MEDIA_TYPE
         ));
     }
+
+    #[tokio::test]
+    async fn todo_by_id_rejects_non_numeric_id() {
+        let app = Router::new()
+            .route("/todos/{id}", get(get_todo))
+            .layer(Extension(Principal::User {
+                id: 1,
+                role: Role::Member,
+            }))
+            .with_state(lazy_state());
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/todos/abc")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn update_todo_malformed_body_returns_400() {
+        let app = Router::new()
+            .route("/todos/{id}", patch(update_todo))
+            .layer(Extension(Principal::User {
+                id: 1,
+                role: Role::Member,
+            }))
+            .with_state(lazy_state());
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/todos/1")
+                    .method("PATCH")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{\"done\": \"yes\""))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            res.status(),
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_todos_rejects_oversized_limit() {
+        let app = Router::new()
+            .route("/todos", get(get_all_todos))
+            .layer(Extension(Principal::User {
+                id: 1,
+                role: Role::Member,
+            }))
+            .with_state(lazy_state());
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/todos?limit=100000")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn next_link_replaces_cursor() {
+        let uri: Uri = "/todos?done=true&cursor=old&limit=2".parse().unwrap();
+        assert_eq!(
+            next_link(&uri, "new"),
+            "</todos?done=true&limit=2&cursor=new>; rel=\"next\""
+        );
+    }
+
+    #[tokio::test]
+    async fn create_todo_reports_every_field_error() {
+        let app = Router::new()
+            .route("/todos", post(create_todo))
+            .layer(Extension(Principal::User {
+                id: 1,
+                role: Role::Member,
+            }))
+            .with_state(lazy_state());
+
+        let body = serde_json::json!({
+            "title": "   ",
+            "description": "x".repeat(crate::models::DESCRIPTION_MAX_CHARS + 1),
+        });
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/todos")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(problem["code"], "validation_failed");
+        let fields: Vec<_> = problem["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, ["title", "description"]);
+    }
+
+    #[tokio::test]
+    async fn create_todo_rejects_unknown_fields() {
+        let app = Router::new()
+            .route("/todos", post(create_todo))
+            .layer(Extension(Principal::User {
+                id: 1,
+                role: Role::Member,
+            }))
+            .with_state(lazy_state());
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/todos")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        r#"{"title":"a","description":"b","assignee":1}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
 }
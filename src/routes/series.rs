@@ -0,0 +1,502 @@
+// src/routes/series.rs
+use crate::{
+    auth::Principal,
+    config::AppState,
+    error::ApiError,
+    extract::{Json, Path, Query},
+    models::{
+        MAX_OCCURRENCES, Occurrence, OccurrenceList, OccurrenceQuery, SeriesScope, Todo,
+        UpdatedTodo,
+    },
+    recurrence::{RRule, occurrences},
+    routes::{
+        routes::caller_timezone,
+        tags::{set_series_tags, set_todo_tags},
+    },
+};
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use sqlx::{Postgres, Transaction};
+use std::collections::{BTreeMap, HashSet};
+
+const SERIES_COLUMNS: &str = "id, owner_id, rrule, dtstart, timezone, ends_before";
+
+/// A row of `todo_series`, minus the template fields new occurrences are
+/// copied from.
+#[derive(sqlx::FromRow)]
+struct Series {
+    id: i64,
+    owner_id: Option<i64>,
+    rrule: String,
+    dtstart: DateTime<Utc>,
+    timezone: String,
+    ends_before: Option<DateTime<Utc>>,
+}
+
+impl Series {
+    fn rule(&self) -> Result<RRule, ApiError> {
+        Ok(self.rrule.parse()?)
+    }
+
+    fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    /// The series' occurrences, stopping at `ends_before`.
+    fn occurrences<'a>(&'a self, rule: &'a RRule) -> impl Iterator<Item = DateTime<Utc>> + 'a {
+        self.occurrences_from(rule, self.dtstart)
+    }
+
+    /// The series' occurrences at or after `from`, skipping ahead to it
+    /// rather than expanding every earlier one.
+    fn occurrences_from<'a>(
+        &'a self,
+        rule: &'a RRule,
+        from: DateTime<Utc>,
+    ) -> impl Iterator<Item = DateTime<Utc>> + 'a {
+        let end = self.ends_before;
+        occurrences(rule, self.dtstart, self.tz())
+            .seek(from)
+            .skip_while(move |at| *at < from)
+            .take_while(move |at| end.is_none_or(|e| *at < e))
+    }
+}
+
+/// Locks the series for the rest of `tx`. Callers reach it through a todo
+/// they already checked ownership of.
+async fn lock_series(tx: &mut Transaction<'_, Postgres>, id: i64) -> Result<Series, ApiError> {
+    let series = sqlx::query_as::<_, Series>(&format!(
+        "SELECT {SERIES_COLUMNS} FROM todo_series WHERE id = $1 FOR UPDATE"
+    ))
+    .bind(id)
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(series)
+}
+
+/// Makes todo `todo_id` the first occurrence of a new series repeating by
+/// `rule` in `tz`, with the todo's current fields and tags as the template.
+pub(super) async fn start_series(
+    tx: &mut Transaction<'_, Postgres>,
+    todo_id: i64,
+    rule: &RRule,
+    tz: Tz,
+) -> Result<i64, ApiError> {
+    let series_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO todo_series (owner_id, rrule, dtstart, timezone, title, description, priority)
+        SELECT owner_id, $2, due_at, $3, title, description, priority
+          FROM todos WHERE id = $1 AND due_at IS NOT NULL
+        RETURNING id
+        "#,
+    )
+    .bind(todo_id)
+    .bind(rule.to_string())
+    .bind(tz.name())
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| {
+        ApiError::BadRequest("A recurring todo needs a due_at for its first occurrence".to_owned())
+    })?;
+
+    sqlx::query(
+        "INSERT INTO todo_series_tags (series_id, tag_id) SELECT $1, tag_id FROM todo_tags WHERE todo_id = $2",
+    )
+    .bind(series_id)
+    .bind(todo_id)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("UPDATE todos SET series_id = $2, occurrence_at = due_at WHERE id = $1")
+        .bind(todo_id)
+        .bind(series_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(series_id)
+}
+
+/// Creates the todo for the series' first occurrence after `after`, from the
+/// template. Nothing happens when the series has ended or that occurrence
+/// already has a todo, trashed ones included, so repeating this is harmless.
+pub(super) async fn create_next_occurrence(
+    tx: &mut Transaction<'_, Postgres>,
+    series_id: i64,
+    after: DateTime<Utc>,
+) -> Result<(), ApiError> {
+    let series = lock_series(tx, series_id).await?;
+    let rule = series.rule()?;
+    let Some(next) = series.occurrences_from(&rule, after).find(|at| *at > after) else {
+        return Ok(());
+    };
+
+    let id: Option<i64> = sqlx::query_scalar(
+        r#"
+        INSERT INTO todos (title, description, priority, due_at, owner_id, series_id, occurrence_at)
+        SELECT title, description, priority, $2, owner_id, id, $2 FROM todo_series WHERE id = $1
+        ON CONFLICT (series_id, occurrence_at) WHERE series_id IS NOT NULL DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(series_id)
+    .bind(next)
+    .fetch_optional(&mut **tx)
+    .await?;
+    if let Some(id) = id {
+        sqlx::query(
+            "INSERT INTO todo_tags (todo_id, tag_id) SELECT $1, tag_id FROM todo_series_tags WHERE series_id = $2",
+        )
+        .bind(id)
+        .bind(series_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Where a `following` or `all` edit takes effect: `None` for the whole
+/// series, or the occurrence to split at. Splitting at the first occurrence
+/// is the same as changing all of them.
+fn split_point(
+    series: &Series,
+    current: DateTime<Utc>,
+    scope: SeriesScope,
+) -> Option<DateTime<Utc>> {
+    (scope == SeriesScope::Following && current > series.dtstart).then_some(current)
+}
+
+/// Applies title, description, priority and tag changes to the open (not
+/// done, not trashed) occurrences of `series`, from `from` on if given.
+/// Completed occurrences are history and keep their fields.
+async fn update_open_occurrences(
+    tx: &mut Transaction<'_, Postgres>,
+    series: &Series,
+    from: Option<DateTime<Utc>>,
+    changes: &UpdatedTodo,
+) -> Result<(), ApiError> {
+    let ids: Vec<i64> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM todos
+         WHERE series_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR occurrence_at >= $2)
+           AND NOT done AND deleted_at IS NULL
+         ORDER BY id
+           FOR UPDATE
+        "#,
+    )
+    .bind(series.id)
+    .bind(from)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    for id in ids {
+        let retagged = match &changes.tags {
+            Some(tags) => set_todo_tags(tx, id, series.owner_id, tags).await?,
+            None => false,
+        };
+        sqlx::query(
+            r#"
+            UPDATE todos
+            SET title = COALESCE($2, title),
+                description = COALESCE($3, description),
+                priority = COALESCE($4, priority),
+                version = version + $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(&changes.title)
+        .bind(&changes.description)
+        .bind(changes.priority)
+        .bind(i64::from(retagged))
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Takes occurrences out of the series: they become ordinary todos.
+async fn detach_occurrences(
+    tx: &mut Transaction<'_, Postgres>,
+    series_id: i64,
+    from: Option<DateTime<Utc>>,
+) -> Result<(), ApiError> {
+    sqlx::query(
+        r#"
+        UPDATE todos SET series_id = NULL, occurrence_at = NULL
+         WHERE series_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR occurrence_at >= $2)
+        "#,
+    )
+    .bind(series_id)
+    .bind(from)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// `PATCH /todos/{id}?scope=following|all` on an occurrence: changes the
+/// template and the open occurrences, and with `rrule` the rule itself.
+/// `following` splits the series at `current`: the earlier occurrences keep
+/// the old series, the rest move to a new one starting at `current`.
+pub(super) async fn update_series(
+    tx: &mut Transaction<'_, Postgres>,
+    current: &Todo,
+    scope: SeriesScope,
+    changes: &UpdatedTodo,
+) -> Result<(), ApiError> {
+    if changes.done.is_some() || changes.due_at.is_some() {
+        return Err(ApiError::BadRequest(
+            "done and due_at can only be changed one occurrence at a time (scope=this)".to_owned(),
+        ));
+    }
+    let (Some(series_id), Some(cutoff)) = (current.series_id, current.occurrence_at) else {
+        return Ok(());
+    };
+    let series = lock_series(tx, series_id).await?;
+    let split = split_point(&series, cutoff, scope);
+    update_open_occurrences(tx, &series, split, changes).await?;
+
+    let Some(split) = split else {
+        let rule = match &changes.rrule {
+            Some(Some(text)) => Some(text.parse::<RRule>()?.to_string()),
+            Some(None) => {
+                // Ending the series keeps its todos, as one-off todos
+                detach_occurrences(tx, series_id, None).await?;
+                sqlx::query("DELETE FROM todo_series WHERE id = $1")
+                    .bind(series_id)
+                    .execute(&mut **tx)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+        sqlx::query(
+            r#"
+            UPDATE todo_series
+            SET title = COALESCE($2, title),
+                description = COALESCE($3, description),
+                priority = COALESCE($4, priority)
+            WHERE id = $1
+            "#,
+        )
+        .bind(series_id)
+        .bind(&changes.title)
+        .bind(&changes.description)
+        .bind(changes.priority)
+        .execute(&mut **tx)
+        .await?;
+        if let Some(tags) = &changes.tags {
+            set_series_tags(tx, series_id, series.owner_id, tags).await?;
+        }
+        if let Some(rule) = rule {
+            let res =
+                sqlx::query("UPDATE todo_series SET rrule = $2 WHERE id = $1 AND rrule <> $2")
+                    .bind(series_id)
+                    .bind(&rule)
+                    .execute(&mut **tx)
+                    .await?;
+            if res.rows_affected() > 0 {
+                // The rule is part of every occurrence's representation
+                sqlx::query("UPDATE todos SET version = version + 1 WHERE series_id = $1")
+                    .bind(series_id)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+        }
+        return Ok(());
+    };
+
+    let rule = match &changes.rrule {
+        Some(Some(text)) => Some(text.parse::<RRule>()?),
+        Some(None) => None,
+        None => {
+            // A counted series keeps its total across the split
+            let mut rule = series.rule()?;
+            if let Some(count) = rule.count {
+                let before = series
+                    .occurrences(&rule)
+                    .take_while(|at| *at < split)
+                    .count();
+                rule.count = Some(count.saturating_sub(before as u32).max(1));
+            }
+            Some(rule)
+        }
+    };
+    match rule {
+        Some(rule) => {
+            let tail: i64 = sqlx::query_scalar(
+                r#"
+                INSERT INTO todo_series
+                    (owner_id, rrule, dtstart, timezone, ends_before, title, description, priority)
+                SELECT owner_id, $2, $3, timezone, ends_before,
+                       COALESCE($4, title), COALESCE($5, description), COALESCE($6, priority)
+                  FROM todo_series WHERE id = $1
+                RETURNING id
+                "#,
+            )
+            .bind(series_id)
+            .bind(rule.to_string())
+            .bind(split)
+            .bind(&changes.title)
+            .bind(&changes.description)
+            .bind(changes.priority)
+            .fetch_one(&mut **tx)
+            .await?;
+            match &changes.tags {
+                Some(tags) => set_series_tags(tx, tail, series.owner_id, tags).await?,
+                None => {
+                    sqlx::query(
+                        "INSERT INTO todo_series_tags (series_id, tag_id) SELECT $2, tag_id FROM todo_series_tags WHERE series_id = $1",
+                    )
+                    .bind(series_id)
+                    .bind(tail)
+                    .execute(&mut **tx)
+                    .await?;
+                }
+            }
+            sqlx::query(
+                "UPDATE todos SET series_id = $3 WHERE series_id = $1 AND occurrence_at >= $2",
+            )
+            .bind(series_id)
+            .bind(split)
+            .bind(tail)
+            .execute(&mut **tx)
+            .await?;
+        }
+        None => detach_occurrences(tx, series_id, Some(split)).await?,
+    }
+    sqlx::query("UPDATE todo_series SET ends_before = $2 WHERE id = $1")
+        .bind(series_id)
+        .bind(split)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// `DELETE /todos/{id}?scope=following|all` on an occurrence: trashes it and
+/// every later occurrence (or all of them) and ends the series there, so no
+/// further occurrences are created.
+pub(super) async fn delete_series(
+    tx: &mut Transaction<'_, Postgres>,
+    current: &Todo,
+    scope: SeriesScope,
+) -> Result<(), ApiError> {
+    let (Some(series_id), Some(cutoff)) = (current.series_id, current.occurrence_at) else {
+        return Ok(());
+    };
+    let series = lock_series(tx, series_id).await?;
+    let end = split_point(&series, cutoff, scope).unwrap_or(series.dtstart);
+
+    sqlx::query(
+        "UPDATE todos SET deleted_at = now() WHERE series_id = $1 AND occurrence_at >= $2 AND deleted_at IS NULL",
+    )
+    .bind(series_id)
+    .bind(end)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("UPDATE todo_series SET ends_before = $2 WHERE id = $1")
+        .bind(series_id)
+        .bind(end)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// `GET /todos/{id}/occurrences`: the todo's series expanded over a range,
+/// each occurrence with the todo standing for it, if one exists yet.
+/// Occurrences whose todo was trashed are left out. A todo that doesn't
+/// repeat has its due date as its only occurrence.
+pub async fn list_occurrences(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    Query(query): Query<OccurrenceQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let caller_tz = caller_timezone(&state, &principal).await?;
+    let (from, to) = query.range(caller_tz)?;
+
+    let (series_id, due_at): (Option<i64>, Option<DateTime<Utc>>) = sqlx::query_as(
+        "SELECT series_id, due_at FROM todos WHERE id = $1 AND ($2::BIGINT IS NULL OR owner_id = $2) AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .bind(principal.owner_scope())
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found("Todo", id))?;
+
+    let Some(series_id) = series_id else {
+        let items = due_at
+            .filter(|at| (from..to).contains(at))
+            .map(|at| Occurrence {
+                at,
+                todo_id: Some(id),
+            })
+            .into_iter()
+            .collect();
+        return Ok(Json(OccurrenceList {
+            series_id: None,
+            rrule: None,
+            timezone: caller_tz.name().to_owned(),
+            items,
+            truncated: false,
+        }));
+    };
+
+    let series = sqlx::query_as::<_, Series>(&format!(
+        "SELECT {SERIES_COLUMNS} FROM todo_series WHERE id = $1"
+    ))
+    .bind(series_id)
+    .fetch_one(&state.pool)
+    .await?;
+    let rule = series.rule()?;
+    let slots: Vec<(DateTime<Utc>, i64, bool)> = sqlx::query_as(
+        r#"
+        SELECT occurrence_at, id, deleted_at IS NOT NULL FROM todos
+         WHERE series_id = $1 AND occurrence_at >= $2 AND occurrence_at < $3
+        "#,
+    )
+    .bind(series_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&state.pool)
+    .await?;
+
+    // Existing todos come first: one may sit on a slot the rule no longer
+    // produces, after the rule was changed
+    let mut found: BTreeMap<DateTime<Utc>, Option<i64>> = BTreeMap::new();
+    let mut trashed = HashSet::new();
+    for (at, todo_id, deleted) in slots {
+        if deleted {
+            trashed.insert(at);
+        } else {
+            found.insert(at, Some(todo_id));
+        }
+    }
+    // Past the (MAX + 1)th expanded occurrence, everything earlier is known
+    let mut expanded = 0;
+    for at in series
+        .occurrences_from(&rule, from)
+        .take_while(|at| *at < to)
+    {
+        if trashed.contains(&at) {
+            continue;
+        }
+        found.entry(at).or_insert(None);
+        expanded += 1;
+        if expanded > MAX_OCCURRENCES {
+            break;
+        }
+    }
+
+    let truncated = found.len() > MAX_OCCURRENCES;
+    let items = found
+        .into_iter()
+        .take(MAX_OCCURRENCES)
+        .map(|(at, todo_id)| Occurrence { at, todo_id })
+        .collect();
+    Ok(Json(OccurrenceList {
+        series_id: Some(series_id),
+        rrule: Some(series.rrule),
+        timezone: series.timezone,
+        items,
+        truncated,
+    }))
+}
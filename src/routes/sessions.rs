@@ -0,0 +1,121 @@
+use crate::{
+    auth::{AuthUser, Session},
+    config::AppState,
+    error::ApiError,
+    extract::{Json, Path},
+    models::{LoginRequest, SessionInfo, SessionSummary, User},
+    routes::auth::check_credentials,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+};
+
+/// Password login for browsers: sets the session cookie instead of returning
+/// tokens.
+pub async fn create_session(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(json): Json<LoginRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (id, role) = check_credentials(&state, json).await?;
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok());
+    let created = state
+        .auth
+        .sessions()
+        .create(&state.pool, id, role, user_agent)
+        .await?;
+
+    let info = session_info(&state, created.session, created.expires_at).await?;
+    Ok((
+        StatusCode::CREATED,
+        [(header::SET_COOKIE, created.set_cookie)],
+        Json(info),
+    ))
+}
+
+pub async fn current_session(
+    State(state): State<AppState>,
+    session: Session,
+) -> Result<impl IntoResponse, ApiError> {
+    let (expires_at,) = sqlx::query_as("SELECT expires_at FROM sessions WHERE id = $1")
+        .bind(session.id)
+        .fetch_one(&state.pool)
+        .await?;
+    Ok(Json(session_info(&state, session, expires_at).await?))
+}
+
+/// Signs out: revokes the current session and clears the cookie.
+pub async fn delete_session(
+    State(state): State<AppState>,
+    session: Session,
+) -> Result<impl IntoResponse, ApiError> {
+    let sessions = state.auth.sessions();
+    sessions
+        .revoke(&state.pool, session.id, session.user_id)
+        .await?;
+    Ok((
+        StatusCode::NO_CONTENT,
+        [(header::SET_COOKIE, sessions.clear_cookie())],
+    ))
+}
+
+/// The caller's live sessions, newest first.
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    session: Option<Session>,
+) -> Result<impl IntoResponse, ApiError> {
+    let sessions = sqlx::query_as::<_, SessionSummary>(
+        r#"
+        SELECT id, user_agent, created_at, last_seen_at, expires_at, id = $2 AS current
+        FROM sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > now()
+        ORDER BY created_at DESC, id DESC
+        "#,
+    )
+    .bind(user.id)
+    .bind(session.map(|s| s.id))
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(sessions))
+}
+
+/// Revokes one of the caller's sessions, e.g. a forgotten one on another device.
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state
+        .auth
+        .sessions()
+        .revoke(&state.pool, id, user.id)
+        .await?
+    {
+        return Err(ApiError::not_found("Session", id));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn session_info(
+    state: &AppState,
+    session: Session,
+    expires_at: chrono::DateTime<chrono::Utc>,
+) -> Result<SessionInfo, ApiError> {
+    let user =
+        sqlx::query_as::<_, User>("SELECT id, username, role, timezone FROM users WHERE id = $1")
+            .bind(session.user_id)
+            .fetch_one(&state.pool)
+            .await?;
+    Ok(SessionInfo {
+        id: session.id,
+        csrf_token: session.csrf_token,
+        expires_at,
+        user,
+    })
+}
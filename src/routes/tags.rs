@@ -0,0 +1,303 @@
+// src/routes/tags.rs
+use crate::{
+    auth::Principal,
+    config::AppState,
+    error::ApiError,
+    extract::{Json, Path, ValidatedJson},
+    models::{MergeTag, Tag, TagName, dedup_tag_names},
+};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use sqlx::{Postgres, Transaction};
+
+/// Columns selected into [`Tag`]; trashed todos are not counted.
+const TAG_COLUMNS: &str = r#"
+    id, name, owner_id, created_at,
+    (SELECT count(*) FROM todo_tags tt JOIN todos td ON td.id = tt.todo_id
+      WHERE tt.tag_id = tags.id AND td.deleted_at IS NULL) AS todo_count
+"#;
+
+/// Creates the tags in `names` that `owner` does not have yet. Returns the
+/// names lowercased, for matching against `lower(tags.name)`.
+async fn ensure_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    owner: Option<i64>,
+    names: &[String],
+) -> Result<Vec<String>, ApiError> {
+    let names = dedup_tag_names(names);
+    sqlx::query(
+        r#"
+        INSERT INTO tags (owner_id, name)
+        SELECT $1, name FROM unnest($2::TEXT[]) AS name
+        ON CONFLICT ((COALESCE(owner_id, 0)), (lower(name))) DO NOTHING
+        "#,
+    )
+    .bind(owner)
+    .bind(&names)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(names.iter().map(|n| n.to_lowercase()).collect())
+}
+
+/// Makes `names` the tags of todo `todo_id`, creating any that `owner` does
+/// not have yet. Returns whether the set changed; the caller bumps the todo's
+/// version when it did, since tags are part of its representation.
+pub(super) async fn set_todo_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    todo_id: i64,
+    owner: Option<i64>,
+    names: &[String],
+) -> Result<bool, ApiError> {
+    let lowered = ensure_tags(tx, owner, names).await?;
+    let changed: i64 = sqlx::query_scalar(
+        r#"
+        WITH wanted AS (
+            SELECT id FROM tags
+             WHERE COALESCE(owner_id, 0) = COALESCE($2, 0) AND lower(name) = ANY($3)
+        ), removed AS (
+            DELETE FROM todo_tags
+             WHERE todo_id = $1 AND tag_id NOT IN (SELECT id FROM wanted)
+            RETURNING 1
+        ), added AS (
+            INSERT INTO todo_tags (todo_id, tag_id)
+            SELECT $1, id FROM wanted
+            ON CONFLICT DO NOTHING
+            RETURNING 1
+        )
+        SELECT (SELECT count(*) FROM removed) + (SELECT count(*) FROM added)
+        "#,
+    )
+    .bind(todo_id)
+    .bind(owner)
+    .bind(&lowered)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(changed > 0)
+}
+
+/// Makes `names` the tags that new occurrences of series `series_id` are
+/// created with.
+pub(super) async fn set_series_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    series_id: i64,
+    owner: Option<i64>,
+    names: &[String],
+) -> Result<(), ApiError> {
+    let lowered = ensure_tags(tx, owner, names).await?;
+    sqlx::query(
+        r#"
+        WITH wanted AS (
+            SELECT id FROM tags
+             WHERE COALESCE(owner_id, 0) = COALESCE($2, 0) AND lower(name) = ANY($3)
+        ), removed AS (
+            DELETE FROM todo_series_tags
+             WHERE series_id = $1 AND tag_id NOT IN (SELECT id FROM wanted)
+        )
+        INSERT INTO todo_series_tags (series_id, tag_id)
+        SELECT $1, id FROM wanted
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(series_id)
+    .bind(owner)
+    .bind(&lowered)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Bumps the version (and so the ETag and updated_at) of every todo carrying
+/// `tag_id`, for changes to the tag itself.
+async fn touch_tagged_todos(
+    tx: &mut Transaction<'_, Postgres>,
+    tag_id: i64,
+) -> Result<(), ApiError> {
+    sqlx::query(
+        "UPDATE todos SET version = version + 1 WHERE id IN (SELECT todo_id FROM todo_tags WHERE tag_id = $1)",
+    )
+    .bind(tag_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Locks the tag for the rest of `tx`; other users' tags are reported as missing.
+async fn lock_tag(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i64,
+    principal: &Principal,
+) -> Result<Option<i64>, ApiError> {
+    let owner: Option<Option<i64>> = sqlx::query_scalar(
+        "SELECT owner_id FROM tags WHERE id = $1 AND ($2::BIGINT IS NULL OR owner_id = $2) FOR UPDATE",
+    )
+    .bind(id)
+    .bind(principal.owner_scope())
+    .fetch_optional(&mut **tx)
+    .await?;
+    owner.ok_or_else(|| ApiError::not_found("Tag", id))
+}
+
+async fn fetch_tag(tx: &mut Transaction<'_, Postgres>, id: i64) -> Result<Tag, ApiError> {
+    let tag = sqlx::query_as::<_, Tag>(&format!("SELECT {TAG_COLUMNS} FROM tags WHERE id = $1"))
+        .bind(id)
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(tag)
+}
+
+pub async fn list_tags(
+    State(state): State<AppState>,
+    principal: Principal,
+) -> Result<impl IntoResponse, ApiError> {
+    let sql = format!(
+        "SELECT {TAG_COLUMNS} FROM tags WHERE ($1::BIGINT IS NULL OR owner_id = $1) ORDER BY lower(name), id"
+    );
+    let tags = sqlx::query_as::<_, Tag>(&sql)
+        .bind(principal.owner_scope())
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(tags))
+}
+
+/// A name the owner already uses (in any case) is 409.
+pub async fn create_tag(
+    State(state): State<AppState>,
+    principal: Principal,
+    ValidatedJson(json): ValidatedJson<TagName>,
+) -> Result<impl IntoResponse, ApiError> {
+    let sql = format!("INSERT INTO tags (owner_id, name) VALUES ($1, $2) RETURNING {TAG_COLUMNS}");
+    let tag = sqlx::query_as::<_, Tag>(&sql)
+        .bind(principal.owner_scope())
+        .bind(&json.name)
+        .fetch_one(&state.pool)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(tag)))
+}
+
+pub async fn get_tag(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let sql = format!(
+        "SELECT {TAG_COLUMNS} FROM tags WHERE id = $1 AND ($2::BIGINT IS NULL OR owner_id = $2)"
+    );
+    let tag = sqlx::query_as::<_, Tag>(&sql)
+        .bind(id)
+        .bind(principal.owner_scope())
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Tag", id))?;
+
+    Ok(Json(tag))
+}
+
+/// Renames the tag; every todo carrying it changes with it, in one
+/// transaction. Renaming onto another existing tag is 409; merge instead.
+pub async fn rename_tag(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    ValidatedJson(json): ValidatedJson<TagName>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    lock_tag(&mut tx, id, &principal).await?;
+
+    let res = sqlx::query("UPDATE tags SET name = $2 WHERE id = $1 AND name <> $2")
+        .bind(id)
+        .bind(&json.name)
+        .execute(&mut *tx)
+        .await?;
+    if res.rows_affected() > 0 {
+        touch_tagged_todos(&mut tx, id).await?;
+    }
+    let tag = fetch_tag(&mut tx, id).await?;
+    tx.commit().await?;
+
+    Ok(Json(tag))
+}
+
+/// Removes the tag from every todo, then deletes it.
+pub async fn delete_tag(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    lock_tag(&mut tx, id, &principal).await?;
+
+    touch_tagged_todos(&mut tx, id).await?;
+    sqlx::query("DELETE FROM tags WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Moves every todo (and recurring series) from the tag in the path to
+/// `into` and deletes the former, atomically. Both tags must belong to the
+/// same owner.
+pub async fn merge_tag(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(id): Path<i64>,
+    Json(json): Json<MergeTag>,
+) -> Result<impl IntoResponse, ApiError> {
+    if json.into == id {
+        return Err(ApiError::BadRequest(
+            "A tag cannot be merged into itself".to_owned(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    // Lock in id order so two opposite merges cannot deadlock
+    let (source_owner, target_owner) = if id < json.into {
+        let source = lock_tag(&mut tx, id, &principal).await?;
+        (source, lock_tag(&mut tx, json.into, &principal).await?)
+    } else {
+        let target = lock_tag(&mut tx, json.into, &principal).await?;
+        (lock_tag(&mut tx, id, &principal).await?, target)
+    };
+    if source_owner != target_owner {
+        return Err(ApiError::Conflict(
+            "Tags belonging to different users cannot be merged".to_owned(),
+        ));
+    }
+
+    touch_tagged_todos(&mut tx, id).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO todo_tags (todo_id, tag_id)
+        SELECT todo_id, $2 FROM todo_tags WHERE tag_id = $1
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(id)
+    .bind(json.into)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO todo_series_tags (series_id, tag_id)
+        SELECT series_id, $2 FROM todo_series_tags WHERE tag_id = $1
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(id)
+    .bind(json.into)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM tags WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let tag = fetch_tag(&mut tx, json.into).await?;
+    tx.commit().await?;
+
+    Ok(Json(tag))
+}
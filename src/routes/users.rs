@@ -0,0 +1,35 @@
+use crate::{
+    config::AppState,
+    error::ApiError,
+    extract::{Json, Path},
+    models::{UpdateRole, User},
+};
+use axum::{extract::State, response::IntoResponse};
+
+pub async fn list_users(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let users =
+        sqlx::query_as::<_, User>("SELECT id, username, role, timezone FROM users ORDER BY id")
+            .fetch_all(&state.pool)
+            .await?;
+
+    Ok(Json(users))
+}
+
+/// Takes effect when the user's next access token is issued (login or refresh).
+pub async fn set_user_role(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(json): Json<UpdateRole>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = sqlx::query_as::<_, User>(
+        "UPDATE users SET role = $2 WHERE id = $1 RETURNING id, username, role, timezone",
+    )
+    .bind(id)
+    .bind(json.role)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found("User", id))?;
+
+    tracing::info!(user_id = id, role = %user.role, "user role changed");
+    Ok(Json(user))
+}
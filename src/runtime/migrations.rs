@@ -0,0 +1,5 @@
+// src/runtime/migrations.rs
+use sqlx::migrate::Migrator;
+
+/// Embedded `migrations/` directory, shared by both binaries and the readiness probe.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
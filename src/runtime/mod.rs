@@ -0,0 +1,13 @@
+mod migrations;
+mod reload;
+mod serve;
+mod shuttle;
+mod telemetry;
+mod trash;
+
+pub use migrations::MIGRATOR;
+pub use reload::reload_on_hangup;
+pub use serve::{serve_with_graceful_shutdown, shutdown_signal};
+pub use shuttle::{ShuttleApp, ShuttleService};
+pub use telemetry::init_tracing;
+pub use trash::{purge_trash, spawn_trash_purge};
@@ -0,0 +1,26 @@
+// src/runtime/reload.rs
+use tokio::task::JoinHandle;
+
+/// Runs `reload` every time the process receives SIGHUP, the conventional
+/// "re-read your config" signal. A no-op task on other platforms.
+pub fn reload_on_hangup(reload: impl Fn() + Send + 'static) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    tracing::error!(error = %e, "failed to listen for SIGHUP; reloads disabled");
+                    return;
+                }
+            };
+            while hangup.recv().await.is_some() {
+                tracing::info!("SIGHUP received; reloading");
+                reload();
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = reload;
+    })
+}
@@ -0,0 +1,141 @@
+// src/runtime/serve.rs
+use axum::Router;
+use std::future::{Future, IntoFuture};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+/// Serves `app` until `signal` resolves, then stops accepting connections and
+/// waits up to `grace` for in-flight requests before giving up on them.
+/// Peer addresses are available to handlers as `ConnectInfo<SocketAddr>`.
+pub async fn serve_with_graceful_shutdown(
+    listener: TcpListener,
+    app: Router,
+    signal: impl Future<Output = ()> + Send + 'static,
+    grace: Duration,
+) -> std::io::Result<()> {
+    let (started_tx, started_rx) = oneshot::channel();
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        signal.await;
+        tracing::info!(?grace, "shutdown signal received; draining connections");
+        let _ = started_tx.send(());
+    })
+    .into_future();
+
+    let deadline = async move {
+        // A dropped sender means the server ended on its own; never fire then
+        if started_rx.await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        tokio::time::sleep(grace).await;
+    };
+
+    tokio::select! {
+        res = server => res,
+        _ = deadline => {
+            tracing::warn!(?grace, "grace period elapsed; dropping remaining connections");
+            Ok(())
+        }
+    }
+}
+
+/// Resolves on SIGINT (Ctrl+C) or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "failed to listen for Ctrl+C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    async fn spawn(
+        app: Router,
+        grace: Duration,
+    ) -> (String, oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = oneshot::channel::<()>();
+        let handle = tokio::spawn(async move {
+            let signal = async {
+                let _ = rx.await;
+            };
+            serve_with_graceful_shutdown(listener, app, signal, grace)
+                .await
+                .unwrap();
+        });
+        (url, tx, handle)
+    }
+
+    fn slow_app(delay: Duration) -> Router {
+        Router::new().route(
+            "/slow",
+            get(move || async move {
+                tokio::time::sleep(delay).await;
+                "done"
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn in_flight_requests_drain_before_exit() {
+        let (url, stop, handle) =
+            spawn(slow_app(Duration::from_millis(200)), Duration::from_secs(5)).await;
+
+        let req = tokio::spawn(reqwest::get(format!("{url}/slow")));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stop.send(()).unwrap();
+
+        let res = req.await.unwrap().unwrap();
+        assert_eq!(res.text().await.unwrap(), "done");
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn grace_period_bounds_shutdown() {
+        let (url, stop, handle) = spawn(
+            slow_app(Duration::from_secs(30)),
+            Duration::from_millis(100),
+        )
+        .await;
+
+        let _req = tokio::spawn(reqwest::get(format!("{url}/slow")));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stop.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("server should stop after the grace period")
+            .unwrap();
+    }
+}
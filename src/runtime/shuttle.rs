@@ -0,0 +1,32 @@
+// src/runtime/shuttle.rs
+use axum::Router;
+use shuttle_runtime::{CustomError, Error};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+/// What the Shuttle entry point returns.
+pub type ShuttleApp = Result<ShuttleService, Error>;
+
+/// Serves the router on Shuttle like `shuttle_axum::AxumService`, but with
+/// `ConnectInfo<SocketAddr>` so client address resolution sees the peer.
+pub struct ShuttleService(pub Router);
+
+impl From<Router> for ShuttleService {
+    fn from(router: Router) -> Self {
+        Self(router)
+    }
+}
+
+#[shuttle_runtime::async_trait]
+impl shuttle_runtime::Service for ShuttleService {
+    async fn bind(mut self, addr: SocketAddr) -> Result<(), Error> {
+        let listener = TcpListener::bind(addr).await.map_err(CustomError::new)?;
+        axum::serve(
+            listener,
+            self.0.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .map_err(CustomError::new)?;
+        Ok(())
+    }
+}
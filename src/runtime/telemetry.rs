@@ -0,0 +1,13 @@
+// src/runtime/telemetry.rs
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Tracing: `RUST_LOG=tower_http=info,axum_server_shuttle=debug` etc.
+pub fn init_tracing() {
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "info,tower_http=info,sqlx=warn".into()),
+        )
+        .init();
+}
@@ -0,0 +1,37 @@
+// src/runtime/trash.rs
+use crate::config::TrashConfig;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval};
+
+/// Permanently deletes todos that have been in the trash for longer than
+/// `retention`; returns how many went.
+pub async fn purge_trash(pool: &PgPool, retention: Duration) -> sqlx::Result<u64> {
+    let res = sqlx::query(
+        "DELETE FROM todos WHERE deleted_at < now() - make_interval(secs => $1::DOUBLE PRECISION)",
+    )
+    .bind(retention.as_secs_f64())
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Runs [`purge_trash`] every `purge_interval`, starting right away. `None`
+/// when retention is disabled. Running it on several instances at once is
+/// harmless; they just race for the same rows.
+pub fn spawn_trash_purge(pool: PgPool, cfg: &TrashConfig) -> Option<JoinHandle<()>> {
+    let retention = cfg.retention?;
+    let mut ticks = interval(cfg.purge_interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    Some(tokio::spawn(async move {
+        loop {
+            ticks.tick().await;
+            match purge_trash(&pool, retention).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!(purged, "purged expired todos from the trash"),
+                Err(e) => tracing::error!(error = %e, "trash purge failed"),
+            }
+        }
+    }))
+}
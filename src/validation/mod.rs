@@ -0,0 +1,5 @@
+mod rules;
+mod trim;
+
+pub use rules::{FieldError, FieldRules, FieldValue, Validate, ValidationErrors, Validator};
+pub use trim::{nullable, trim, trim_all, trim_all_opt, trim_opt};
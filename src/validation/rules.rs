@@ -0,0 +1,202 @@
+// src/validation/rules.rs
+use serde::Serialize;
+use std::fmt;
+
+/// Implemented by request bodies that `ValidatedJson` should check after
+/// deserializing. Usually generated with [`validate_fields!`](crate::validate_fields).
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Every failed rule across every field, so clients can fix a form in one round trip.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} field(s) failed validation", self.0.len())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Text view of a field; `None` means "absent", which every rule accepts.
+pub trait FieldValue {
+    fn as_text(&self) -> Option<&str>;
+}
+
+impl FieldValue for String {
+    fn as_text(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl FieldValue for Option<String> {
+    fn as_text(&self) -> Option<&str> {
+        self.as_deref()
+    }
+}
+
+#[derive(Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field<'a>(
+        &'a mut self,
+        name: &'static str,
+        value: &'a impl FieldValue,
+    ) -> FieldRules<'a> {
+        FieldRules {
+            errors: &mut self.errors,
+            name,
+            value: value.as_text(),
+        }
+    }
+
+    /// Records a failure found by a check the built-in rules don't cover.
+    pub fn reject(&mut self, field: &'static str, code: &'static str, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.to_owned(),
+            code,
+            message: message.into(),
+        });
+    }
+
+    pub fn finish(self) -> Result<(), ValidationErrors> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors(self.errors))
+        }
+    }
+}
+
+pub struct FieldRules<'a> {
+    errors: &'a mut Vec<FieldError>,
+    name: &'static str,
+    value: Option<&'a str>,
+}
+
+impl FieldRules<'_> {
+    pub fn not_blank(self) -> Self {
+        self.check(
+            |s| !s.trim().is_empty(),
+            "blank",
+            || "must not be blank".to_owned(),
+        )
+    }
+
+    pub fn min_chars(self, min: usize) -> Self {
+        self.check(
+            |s| s.chars().count() >= min,
+            "too_short",
+            || format!("must be at least {} characters", min),
+        )
+    }
+
+    pub fn max_chars(self, max: usize) -> Self {
+        self.check(
+            |s| s.chars().count() <= max,
+            "too_long",
+            || format!("must be at most {} characters", max),
+        )
+    }
+
+    fn check(
+        self,
+        ok: impl FnOnce(&str) -> bool,
+        code: &'static str,
+        message: impl FnOnce() -> String,
+    ) -> Self {
+        if let Some(value) = self.value
+            && !ok(value)
+        {
+            self.errors.push(FieldError {
+                field: self.name.to_owned(),
+                code,
+                message: message(),
+            });
+        }
+        self
+    }
+}
+
+/// Declares a [`Validate`] impl from a per-field rule list:
+///
+/// ```ignore
+/// validate_fields!(CreateTodo {
+///     title: [not_blank, max_chars(200)],
+///     description: [max_chars(10_000)],
+/// });
+/// ```
+#[macro_export]
+macro_rules! validate_fields {
+    ($ty:ty { $($field:ident : [$($rule:ident $(($($arg:expr),* $(,)?))?),* $(,)?]),* $(,)? }) => {
+        impl $crate::validation::Validate for $ty {
+            fn validate(&self) -> ::std::result::Result<(), $crate::validation::ValidationErrors> {
+                let mut v = $crate::validation::Validator::new();
+                $(
+                    v.field(stringify!($field), &self.$field)
+                        $(.$rule($($($arg),*)?))*;
+                )*
+                v.finish()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Signup {
+        name: String,
+        bio: Option<String>,
+    }
+
+    crate::validate_fields!(Signup {
+        name: [not_blank, min_chars(2), max_chars(5)],
+        bio: [max_chars(3)],
+    });
+
+    #[test]
+    fn collects_every_field_error() {
+        let errs = Signup {
+            name: "".into(),
+            bio: Some("too long".into()),
+        }
+        .validate()
+        .unwrap_err();
+        let codes: Vec<_> = errs.0.iter().map(|e| (e.field.as_str(), e.code)).collect();
+        assert_eq!(
+            codes,
+            [
+                ("name", "blank"),
+                ("name", "too_short"),
+                ("bio", "too_long")
+            ]
+        );
+    }
+
+    #[test]
+    fn absent_optional_fields_pass() {
+        let ok = Signup {
+            name: "abc".into(),
+            bio: None,
+        };
+        assert_eq!(ok.validate(), Ok(()));
+    }
+}
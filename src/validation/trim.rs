@@ -0,0 +1,36 @@
+// src/validation/trim.rs
+// serde helpers for `#[serde(deserialize_with = "...")]` that normalize input before validation.
+use serde::{Deserialize, Deserializer};
+
+pub fn trim<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let s = String::deserialize(d)?;
+    Ok(s.trim().to_owned())
+}
+
+/// Pair with `#[serde(default)]` so an absent field stays `None`.
+pub fn trim_opt<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    let s = Option::<String>::deserialize(d)?;
+    Ok(s.map(|s| s.trim().to_owned()))
+}
+
+/// Trims every element of a list.
+pub fn trim_all<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    let v = Vec::<String>::deserialize(d)?;
+    Ok(v.into_iter().map(|s| s.trim().to_owned()).collect())
+}
+
+/// Pair with `#[serde(default)]` so an absent list stays `None`.
+pub fn trim_all_opt<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<String>>, D::Error> {
+    let v = Option::<Vec<String>>::deserialize(d)?;
+    Ok(v.map(|v| v.into_iter().map(|s| s.trim().to_owned()).collect()))
+}
+
+/// Pair with `#[serde(default)]` to tell an absent field (`None`) from an
+/// explicit `null` (`Some(None)`).
+pub fn nullable<'de, T, D>(d: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(d).map(Some)
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use common::{ADMIN, admin_config, db::try_setup_ephemeral_db, spawn_app_with_config};
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn scoped_keys_are_created_used_and_revoked() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool, admin_config()).await;
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{base}/todos"))
+        .bearer_auth(ADMIN)
+        .json(&json!({ "title": "seeded", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+
+    let res = client
+        .post(format!("{base}/admin/api-keys"))
+        .bearer_auth(ADMIN)
+        .json(&json!({ "name": "dashboard", "scopes": ["todos:read"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    let created: Value = res.json().await.unwrap();
+    let key = created["key"].as_str().unwrap().to_owned();
+    let id = created["id"].as_i64().unwrap();
+    assert!(key.starts_with(&format!("tk_{}_", created["prefix"].as_str().unwrap())));
+
+    let res = client
+        .get(format!("{base}/todos"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let page: Value = res.json().await.unwrap();
+    assert_eq!(page["items"][0]["title"], "seeded");
+
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&key)
+        .json(&json!({ "title": "nope", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "forbidden");
+
+    // Keys can't manage keys unless granted api_keys:manage
+    let res = client
+        .get(format!("{base}/admin/api-keys"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+
+    let listed: Value = client
+        .get(format!("{base}/admin/api-keys"))
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry = &listed.as_array().unwrap()[0];
+    assert_eq!(entry["scopes"], json!(["todos:read"]));
+    assert!(entry.get("key").is_none());
+    assert!(entry["last_used_at"].is_string());
+
+    let res = client
+        .delete(format!("{base}/admin/api-keys/{id}"))
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+
+    let res = client
+        .get(format!("{base}/todos"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn key_creation_is_validated() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool, admin_config()).await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{base}/admin/api-keys"))
+        .bearer_auth(ADMIN)
+        .json(&json!({
+            "name": "old",
+            "scopes": ["todos:read"],
+            "expires_at": "2000-01-01T00:00:00Z"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+
+    let res = client
+        .post(format!("{base}/admin/api-keys"))
+        .bearer_auth(ADMIN)
+        .json(&json!({ "name": "bad", "scopes": ["todos:delete"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+}
+
+#[tokio::test]
+async fn user_scoped_callers_only_manage_their_own_keys() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool.clone(), admin_config()).await;
+    let client = reqwest::Client::new();
+
+    // A role-admin user holds api_keys:manage but is still scoped to itself
+    let mut ids = Vec::new();
+    for (username, role) in [("alice", "admin"), ("bob", "member")] {
+        let (id,): (i64,) =
+            sqlx::query_as("INSERT INTO users (username, role) VALUES ($1, $2) RETURNING id")
+                .bind(username)
+                .bind(role)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        ids.push(id);
+    }
+    let (alice, bob) = (ids[0], ids[1]);
+
+    let create = |token: &str, body: Value| {
+        client
+            .post(format!("{base}/admin/api-keys"))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+    };
+    let created: Value = create(
+        ADMIN,
+        json!({ "name": "bob", "scopes": ["todos:read"], "user_id": bob }),
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let bobs_key = created["id"].as_i64().unwrap();
+    let created: Value = create(
+        ADMIN,
+        json!({ "name": "alice", "scopes": ["api_keys:manage", "todos:read"], "user_id": alice }),
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let key = created["key"].as_str().unwrap().to_owned();
+
+    // Neither an unscoped key nor one for another user
+    for user_id in [Value::Null, json!(bob)] {
+        let res = create(
+            &key,
+            json!({ "name": "escalate", "scopes": ["todos:read"], "user_id": user_id }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.status(), 403, "user_id: {user_id}");
+    }
+    let res = create(
+        &key,
+        json!({ "name": "mine", "scopes": ["todos:read"], "user_id": alice }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 201);
+
+    let listed: Value = client
+        .get(format!("{base}/admin/api-keys"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let names: Vec<_> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|k| k["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["alice", "mine"]);
+
+    let res = client
+        .delete(format!("{base}/admin/api-keys/{bobs_key}"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
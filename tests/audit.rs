@@ -0,0 +1,148 @@
+mod common;
+
+use axum_server_shuttle::audit::{AuditLog, LinkError};
+use common::{ADMIN, admin_config, db::try_setup_ephemeral_db, spawn_app_with_config};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+async fn events(base: &str, client: &reqwest::Client, query: &str) -> Vec<Value> {
+    let res = client
+        .get(format!("{base}/admin/audit-events?{query}"))
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let page: Value = res.json().await.unwrap();
+    page["items"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn mutating_requests_are_recorded_and_chained() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool.clone(), admin_config()).await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(ADMIN)
+        .header("x-request-id", "req-create")
+        .json(&json!({ "title": "audited", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    let res = client
+        .post(format!("{base}/auth/register"))
+        .bearer_auth(ADMIN)
+        .header("content-type", "application/json")
+        .body(r#"{"username":"alice","password":"correct horse battery"}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    let res = client
+        .delete(format!("{base}/todos/1"))
+        .bearer_auth("not-the-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    // Reads outside /admin are not recorded
+    client
+        .get(format!("{base}/todos"))
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap();
+
+    let created = events(&base, &client, "request_id=req-create").await;
+    assert_eq!(created.len(), 1);
+    let created = &created[0];
+    assert_eq!(created["principal"], "admin");
+    assert_eq!(created["method"], "POST");
+    assert_eq!(created["route"], "/todos");
+    assert_eq!(created["status"], 201);
+    assert_eq!(created["source_ip"], "127.0.0.1");
+
+    let registered = events(&base, &client, "route=/auth/register").await;
+    let expected = Sha256::digest(br#"{"password":"[REDACTED]","username":"alice"}"#);
+    let expected: String = expected.iter().map(|b| format!("{b:02x}")).collect();
+    assert_eq!(registered[0]["body_digest"], expected);
+
+    let rejected = events(&base, &client, "method=delete").await;
+    assert_eq!(rejected[0]["status"], 403);
+    assert!(rejected[0]["principal"].is_null());
+
+    // Three mutations, then admin queries (each recorded after it answers)
+    let all = events(&base, &client, "limit=2").await;
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0]["method"], "GET");
+    assert_eq!(all[0]["prev_hash"], all[1]["hash"]);
+
+    let audit = AuditLog::new(pool.clone());
+    let report = audit.verify().await.unwrap();
+    assert!(report.is_intact(), "{report:?}");
+    assert_eq!(report.events, 7);
+
+    // Editing a row breaks its own hash; deleting one breaks its successor's link
+    let edited = created["id"].as_i64().unwrap();
+    sqlx::query("UPDATE audit_events SET status = 200 WHERE id = $1")
+        .bind(edited)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM audit_events WHERE id = $1")
+        .bind(edited + 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let report = audit.verify().await.unwrap();
+    assert_eq!(report.events, 6);
+    let broken: Vec<_> = report.broken.iter().map(|b| (b.id, b.error)).collect();
+    assert_eq!(
+        broken,
+        [
+            (edited, LinkError::HashMismatch),
+            (edited + 2, LinkError::PrevHashMismatch)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn audit_log_requires_audit_scope() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool, admin_config()).await;
+    let client = reqwest::Client::new();
+
+    let key: Value = client
+        .post(format!("{base}/admin/api-keys"))
+        .bearer_auth(ADMIN)
+        .json(&json!({ "name": "ops", "scopes": ["todos:read"] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let res = client
+        .get(format!("{base}/admin/audit-events"))
+        .bearer_auth(key["key"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+
+    let res = client
+        .get(format!("{base}/admin/audit-events?limit=0"))
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
@@ -0,0 +1,266 @@
+mod common;
+
+use axum_server_shuttle::config::{AuthConfig, CorsPolicy, Secret, ServerConfig};
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_config, spawn_app_with_pool};
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn register_login_and_me() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+
+    let token = sign_up(&base, &client, "Alice").await;
+    let me: Value = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["username"], "Alice");
+
+    // Usernames are case-insensitively unique
+    let res = client
+        .post(format!("{base}/auth/register"))
+        .json(&json!({ "username": "alice", "password": "another password" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 409);
+
+    let res = client
+        .post(format!("{base}/auth/token"))
+        .json(&json!({ "username": "alice", "password": "wrong password" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+
+    let res = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth("not-a-jwt")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+    assert_eq!(res.headers().get("www-authenticate").unwrap(), "Bearer");
+}
+
+#[tokio::test]
+async fn users_only_see_their_own_todos() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let alice = sign_up(&base, &client, "alice").await;
+    let bob = sign_up(&base, &client, "bob").await;
+
+    let created: Value = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&alice)
+        .json(&json!({ "title": "Alice's", "description": "" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_i64().unwrap();
+
+    for res in [
+        client.get(format!("{base}/todos/{id}")),
+        client
+            .patch(format!("{base}/todos/{id}"))
+            .json(&json!({ "done": true })),
+        client.delete(format!("{base}/todos/{id}")),
+    ] {
+        let res = res.bearer_auth(&bob).send().await.unwrap();
+        assert_eq!(res.status(), 404);
+    }
+
+    let page: Value = client
+        .get(format!("{base}/todos"))
+        .bearer_auth(&bob)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(page["items"].as_array().unwrap().is_empty());
+
+    let res = client.get(format!("{base}/todos")).send().await.unwrap();
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn admin_token_locks_down_and_sees_everything() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let cfg = ServerConfig {
+        cors: CorsPolicy::Disabled,
+        auth: AuthConfig {
+            admin_tokens: vec![Secret::new("admin-secret"), Secret::new("admin-next")],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (base, _handle) = spawn_app_with_config(pool, cfg).await;
+    let client = reqwest::Client::new();
+
+    // Probes stay reachable without a token
+    let res = client
+        .get(format!("{base}/health/live"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    // Missing token is 401, a wrong static token 403; the rotation token works too
+    let res = client.get(format!("{base}/todos")).send().await.unwrap();
+    assert_eq!(res.status(), 401);
+    assert_eq!(res.headers()["www-authenticate"], "Bearer");
+    let res = client
+        .get(format!("{base}/todos"))
+        .bearer_auth("admin-old")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    let res = client
+        .get(format!("{base}/todos"))
+        .bearer_auth("admin-next")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    // Registration is closed to anonymous callers under lockdown
+    let res = client
+        .post(format!("{base}/auth/register"))
+        .json(&json!({ "username": "carol", "password": "correct horse battery" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+
+    let res = client
+        .post(format!("{base}/auth/register"))
+        .bearer_auth("admin-secret")
+        .json(&json!({ "username": "carol", "password": "correct horse battery" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+
+    let token: Value = client
+        .post(format!("{base}/auth/token"))
+        .json(&json!({ "username": "carol", "password": "correct horse battery" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let carol = token["access_token"].as_str().unwrap();
+
+    client
+        .post(format!("{base}/todos"))
+        .bearer_auth(carol)
+        .json(&json!({ "title": "Carol's", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+
+    let page: Value = client
+        .get(format!("{base}/todos"))
+        .bearer_auth("admin-secret")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(page["items"][0]["title"], "Carol's");
+}
+
+#[tokio::test]
+async fn refresh_tokens_rotate_and_detect_reuse() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let creds = json!({ "username": "dave", "password": "correct horse battery" });
+    client
+        .post(format!("{base}/auth/register"))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+
+    let first: Value = client
+        .post(format!("{base}/auth/token"))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let original = first["refresh_token"].as_str().unwrap();
+
+    let refresh = |token: &str| {
+        client
+            .post(format!("{base}/auth/refresh"))
+            .json(&json!({ "refresh_token": token }))
+            .send()
+    };
+
+    let res = refresh(original).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let second: Value = res.json().await.unwrap();
+    let rotated = second["refresh_token"].as_str().unwrap();
+    assert_ne!(rotated, original);
+
+    let me = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth(second["access_token"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(me.status(), 200);
+
+    // Replaying the spent token revokes the whole family, including its successor
+    assert_eq!(refresh(original).await.unwrap().status(), 401);
+    assert_eq!(refresh(rotated).await.unwrap().status(), 401);
+
+    // Logout revokes a fresh family; it is idempotent and never reveals anything
+    let third: Value = client
+        .post(format!("{base}/auth/token"))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = third["refresh_token"].as_str().unwrap();
+    for _ in 0..2 {
+        let res = client
+            .post(format!("{base}/auth/logout"))
+            .json(&json!({ "refresh_token": token }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 204);
+    }
+    assert_eq!(refresh(token).await.unwrap().status(), 401);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use axum_server_shuttle::config::ServerConfig;
+use common::{ADMIN, admin_config, db::try_setup_ephemeral_db, spawn_app_with_config};
+use serde_json::{Value, json};
+
+fn config(trusted_proxies: &[&str]) -> ServerConfig {
+    ServerConfig {
+        trusted_proxies: trusted_proxies.iter().map(|n| n.parse().unwrap()).collect(),
+        ..admin_config()
+    }
+}
+
+/// Creates a todo through a pretend proxy and returns the audited source IP.
+async fn source_ip(base: &str, forwarding: &[(&str, &str)], request_id: &str) -> Value {
+    let client = reqwest::Client::new();
+    let mut req = client.post(format!("{base}/todos")).bearer_auth(ADMIN);
+    for (name, value) in forwarding {
+        req = req.header(*name, *value);
+    }
+    let res = req
+        .header("x-request-id", request_id)
+        .json(&json!({ "title": "proxied", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+
+    let page: Value = client
+        .get(format!("{base}/admin/audit-events?request_id={request_id}"))
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    page["items"][0]["source_ip"].clone()
+}
+
+#[tokio::test]
+async fn forwarded_for_is_honoured_only_from_trusted_proxies() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+
+    let (trusting, _h1) = spawn_app_with_config(pool.clone(), config(&["127.0.0.0/8"])).await;
+    assert_eq!(
+        source_ip(
+            &trusting,
+            &[("x-forwarded-for", "198.51.100.1, 203.0.113.7")],
+            "via-proxy"
+        )
+        .await,
+        "203.0.113.7"
+    );
+
+    // Only the header the proxy sets counts; a Forwarded the client added
+    // itself is passed through by the proxy and must not win
+    assert_eq!(
+        source_ip(
+            &trusting,
+            &[
+                ("forwarded", "for=192.0.2.66"),
+                ("x-forwarded-for", "203.0.113.7"),
+            ],
+            "spoofed"
+        )
+        .await,
+        "203.0.113.7"
+    );
+
+    // Same headers, but the loopback peer is not a known proxy
+    let (direct, _h2) = spawn_app_with_config(pool, config(&["10.0.0.0/8"])).await;
+    assert_eq!(
+        source_ip(&direct, &[("x-forwarded-for", "203.0.113.7")], "direct").await,
+        "127.0.0.1"
+    );
+}
@@ -1,16 +1,37 @@
-use sqlx::{PgPool, postgres::PgPoolOptions, Executor};
+use sqlx::{Executor, PgPool, postgres::PgPoolOptions};
 use uuid::Uuid;
 
+/// Returns `None` when `TEST_DATABASE_URL` is unset so DB-backed tests can skip.
+pub async fn try_setup_ephemeral_db() -> Option<(String, PgPool)> {
+    if std::env::var("TEST_DATABASE_URL").is_err() {
+        eprintln!("TEST_DATABASE_URL not set; skipping DB-backed test");
+        return None;
+    }
+    Some(setup_ephemeral_db().await)
+}
+
 pub async fn setup_ephemeral_db() -> (String, PgPool) {
     let base_url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
-    let admin_pool = PgPoolOptions::new().max_connections(1).connect(&base_url).await.unwrap();
+    let admin_pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&base_url)
+        .await
+        .unwrap();
     let db_name = format!("test_{}", Uuid::new_v4());
     let create = format!("CREATE DATABASE \"{}\"", db_name);
     admin_pool.execute(create.as_str()).await.unwrap();
 
-    let db_url = if base_url.ends_with('/') { format!("{base_url}{db_name}") } else { format!("{base_url}/{db_name}") };
-    let pool = PgPoolOptions::new().max_connections(5).connect(&db_url).await.unwrap();
+    // Swap the database segment of the base URL for the freshly created one
+    let root = base_url
+        .rsplit_once('/')
+        .filter(|(head, _)| !head.ends_with('/'))
+        .map_or(base_url.as_str(), |(head, _)| head);
+    let db_url = format!("{}/{}", root.trim_end_matches('/'), db_name);
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&db_url)
+        .await
+        .unwrap();
     sqlx::migrate!("./migrations").run(&pool).await.unwrap();
     (db_url, pool)
 }
-
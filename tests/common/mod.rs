@@ -1,32 +1,84 @@
+#![allow(dead_code)]
+
 pub mod db;
+pub mod oidc;
 
-use std::{net::TcpListener};
-use axum::Router;
-use tokio::net::TcpListener as TokioListener;
-use tokio::task::JoinHandle;
-use hyper::Server;
-use axum_server_shuttle::{models::Server, config::{AppState, ServerConfig}};
+use axum_server_shuttle::{
+    config::{AppState, AuthConfig, CorsPolicy, Secret, ServerConfig},
+    models::Server,
+};
 use sqlx::PgPool;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
 
 pub async fn spawn_app_with_pool(pool: PgPool) -> (String, JoinHandle<()>) {
-    let cfg = ServerConfig::load_from_env().expect("cfg");
+    // Disable CORS to avoid invalid wildcard+credentials combo in default permissive mode
+    let cfg = ServerConfig {
+        cors: CorsPolicy::Disabled,
+        ..Default::default()
+    };
+    spawn_app_with_config(pool, cfg).await
+}
+
+/// Static admin token of [`admin_config`].
+pub const ADMIN: &str = "admin-secret";
+
+/// CORS off and the API locked down behind [`ADMIN`].
+pub fn admin_config() -> ServerConfig {
+    ServerConfig {
+        cors: CorsPolicy::Disabled,
+        auth: AuthConfig {
+            admin_tokens: vec![Secret::new(ADMIN)],
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+pub async fn spawn_app_with_config(pool: PgPool, cfg: ServerConfig) -> (String, JoinHandle<()>) {
     let state = AppState::new(pool, cfg);
     let server = Server::new(state);
     let app = server.router();
 
-    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
     let addr = listener.local_addr().unwrap();
     let url = format!("http://{}:{}", addr.ip(), addr.port());
 
-    let listener = TokioListener::from_std(listener).unwrap();
-    let server = Server::from_tcp(listener.into_std().unwrap())
-        .unwrap()
-        .serve(app.into_make_service());
-
     let handle = tokio::spawn(async move {
-        if let Err(e) = server.await { eprintln!("server error: {e}"); }
+        let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("server error: {e}");
+        }
     });
 
     (url, handle)
 }
 
+/// Registers `username` and returns a bearer access token for it.
+pub async fn sign_up(base: &str, client: &reqwest::Client, username: &str) -> String {
+    let creds = serde_json::json!({ "username": username, "password": "correct horse battery" });
+    let res = client
+        .post(format!("{base}/auth/register"))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201, "register {username}");
+
+    log_in(base, client, username).await
+}
+
+/// Fresh access token for a user created by [`sign_up`], e.g. after a role change.
+pub async fn log_in(base: &str, client: &reqwest::Client, username: &str) -> String {
+    let creds = serde_json::json!({ "username": username, "password": "correct horse battery" });
+    let token: serde_json::Value = client
+        .post(format!("{base}/auth/token"))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    token["access_token"].as_str().unwrap().to_owned()
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_pool};
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn etags_guard_updates_and_deletes() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&token)
+        .json(&json!({ "title": "milk", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    let v1 = res.headers()["etag"].to_str().unwrap().to_owned();
+    assert_eq!(v1, r#""1""#);
+    let todo: Value = res.json().await.unwrap();
+    assert_eq!(todo["version"], 1);
+    let url = format!("{base}/todos/{}", todo["id"]);
+
+    // Conditional GET
+    let res = client
+        .get(&url)
+        .bearer_auth(&token)
+        .header("if-none-match", &v1)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 304);
+    assert_eq!(res.headers()["etag"], v1.as_str());
+    assert!(res.bytes().await.unwrap().is_empty());
+
+    // Update with the current tag bumps the version
+    let res = client
+        .patch(&url)
+        .bearer_auth(&token)
+        .header("if-match", &v1)
+        .json(&json!({ "done": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let v2 = res.headers()["etag"].to_str().unwrap().to_owned();
+    assert_eq!(v2, r#""2""#);
+
+    // A writer still holding v1 loses
+    let res = client
+        .put(&url)
+        .bearer_auth(&token)
+        .header("if-match", &v1)
+        .json(&json!({ "title": "eggs", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 412);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "precondition_failed");
+
+    let res = client
+        .get(&url)
+        .bearer_auth(&token)
+        .header("if-none-match", &v1)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["etag"], v2.as_str());
+    let todo: Value = res.json().await.unwrap();
+    assert_eq!(todo["title"], "milk");
+    assert_eq!(todo["done"], true);
+
+    let res = client
+        .delete(&url)
+        .bearer_auth(&token)
+        .header("if-match", &v1)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 412);
+
+    let res = client
+        .delete(&url)
+        .bearer_auth(&token)
+        .header("if-match", &v2)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+
+    // Missing rows stay 404 whatever the preconditions say
+    let res = client
+        .delete(&url)
+        .bearer_auth(&token)
+        .header("if-match", "*")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
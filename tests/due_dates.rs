@@ -0,0 +1,197 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_pool};
+use serde_json::{Value, json};
+
+fn titles(page: &Value) -> Vec<&str> {
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["title"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn completion_is_stamped_and_overdue_todos_are_found() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let create = |body: Value| {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    let list = |query: String| {
+        let client = client.clone();
+        let (base, token) = (base.clone(), token.clone());
+        async move {
+            let res = client
+                .get(format!("{base}/todos?{query}"))
+                .bearer_auth(&token)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), 200, "{query}");
+            res.json::<Value>().await.unwrap()
+        }
+    };
+
+    let yesterday = (Utc::now() - Duration::days(1)).to_rfc3339();
+    let next_week = (Utc::now() + Duration::days(7)).to_rfc3339();
+    let late: Value = create(json!({
+        "title": "late", "description": "", "due_at": yesterday, "priority": "high"
+    }))
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    assert_eq!(late["priority"], "high");
+    assert!(late["completed_at"].is_null());
+    create(
+        json!({ "title": "later", "description": "", "due_at": next_week, "priority": "urgent" }),
+    )
+    .await
+    .unwrap();
+    create(json!({ "title": "someday", "description": "", "priority": "urgent" }))
+        .await
+        .unwrap();
+    create(json!({ "title": "chore", "description": "" }))
+        .await
+        .unwrap();
+    let res = create(json!({ "title": "x", "description": "", "priority": "asap" }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+
+    assert_eq!(titles(&list("overdue=true".into()).await), ["late"]);
+    assert_eq!(
+        titles(&list("overdue=false".into()).await),
+        ["later", "someday", "chore"]
+    );
+    let before = (Utc::now() + Duration::days(1)).format("%Y-%m-%dT%H:%M:%SZ");
+    assert_eq!(
+        titles(&list(format!("due_before={before}")).await),
+        ["late"]
+    );
+
+    // Urgent first, earliest due within a priority, undated last; pages keep the order
+    let page = list("sort=priority&limit=2".into()).await;
+    assert_eq!(titles(&page), ["later", "someday"]);
+    let rest = list(format!(
+        "sort=priority&limit=2&cursor={}",
+        page["next_cursor"].as_str().unwrap()
+    ))
+    .await;
+    assert_eq!(titles(&rest), ["late", "chore"]);
+    assert!(rest["next_cursor"].is_null());
+    assert_eq!(
+        titles(&list("sort=due".into()).await),
+        ["late", "later", "someday", "chore"]
+    );
+
+    // completed_at follows done; clearing due_at takes the todo out of overdue
+    let url = format!("{base}/todos/{}", late["id"]);
+    let patch = |body: Value| client.patch(&url).bearer_auth(&token).json(&body).send();
+    let done: Value = patch(json!({ "done": true }))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!done["completed_at"].is_null());
+    assert_eq!(done["due_at"], late["due_at"]);
+    assert!(titles(&list("overdue=true".into()).await).is_empty());
+    let reopened: Value = patch(json!({ "done": false, "due_at": null }))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(reopened["completed_at"].is_null());
+    assert!(reopened["due_at"].is_null());
+    assert_eq!(reopened["priority"], "high");
+}
+
+#[tokio::test]
+async fn due_today_uses_the_users_timezone() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+
+    let me: Value = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["timezone"], "UTC");
+    let res = client
+        .patch(format!("{base}/auth/me"))
+        .bearer_auth(&token)
+        .json(&json!({ "timezone": "Mars/Olympus_Mons" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+
+    // Kiritimati is UTC+14, so which day a todo due in 12 hours falls on
+    // depends on whose calendar is asked
+    let due = Utc::now() + Duration::hours(12);
+    client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&token)
+        .json(&json!({ "title": "call", "description": "", "due_at": due.to_rfc3339() }))
+        .send()
+        .await
+        .unwrap();
+    let due_on = |window: &'static str| {
+        let client = client.clone();
+        let (base, token) = (base.clone(), token.clone());
+        async move {
+            let page: Value = client
+                .get(format!("{base}/todos?due={window}"))
+                .bearer_auth(&token)
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            !titles(&page).is_empty()
+        }
+    };
+    let today_utc = due.date_naive() == Utc::now().date_naive();
+    assert_eq!(due_on("today").await, today_utc);
+    assert_eq!(due_on("tomorrow").await, !today_utc);
+
+    let updated: Value = client
+        .patch(format!("{base}/auth/me"))
+        .bearer_auth(&token)
+        .json(&json!({ "timezone": "Pacific/Kiritimati" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(updated["timezone"], "Pacific/Kiritimati");
+    let tz = chrono::FixedOffset::east_opt(14 * 3600).unwrap();
+    let today_there =
+        due.with_timezone(&tz).date_naive() == Utc::now().with_timezone(&tz).date_naive();
+    assert_eq!(due_on("today").await, today_there);
+    assert_eq!(due_on("tomorrow").await, !today_there);
+}
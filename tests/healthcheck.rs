@@ -1,6 +1,38 @@
+use axum_server_shuttle::{
+    config::{AppState, CorsPolicy, ServerConfig},
+    models::Server,
+    runtime::serve_with_graceful_shutdown,
+};
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
 #[tokio::test]
 async fn e2e_health_is_ok() {
-    // Placeholder: full E2E spawn would require adapting Shuttle resource injection.
-    // Health is covered in unit-style test in src/routes/routes.rs.
-    // Test passes by doing nothing - this is intentional for a placeholder.
+    let cfg = ServerConfig {
+        cors: CorsPolicy::Disabled,
+        ..Default::default()
+    };
+    // /health never touches the pool, so a lazy one is enough here.
+    let pool = PgPool::connect_lazy("postgres://127.0.0.1/postgres").expect("lazy pool");
+    let app = Server::new(AppState::new(pool, cfg)).router();
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let url = format!("http://{}/health", listener.local_addr().unwrap());
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_graceful_shutdown(
+        listener,
+        app,
+        async {
+            let _ = stopped.await;
+        },
+        Duration::from_secs(1),
+    ));
+
+    let res = reqwest::get(url).await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert!(res.headers().contains_key("x-request-id"));
+
+    stop.send(()).unwrap();
+    server.await.unwrap().unwrap();
 }
@@ -0,0 +1,129 @@
+mod common;
+
+use axum_server_shuttle::config::{CorsPolicy, IdempotencyConfig, ServerConfig};
+use axum_server_shuttle::idempotency::fingerprint;
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_config};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use std::time::Duration;
+
+fn config(ttl: Duration) -> ServerConfig {
+    ServerConfig {
+        cors: CorsPolicy::Disabled,
+        idempotency: IdempotencyConfig { enabled: true, ttl },
+        ..Default::default()
+    }
+}
+
+async fn todo_count(pool: &PgPool) -> i64 {
+    sqlx::query_scalar("SELECT count(*) FROM todos")
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn retries_replay_the_first_response() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) =
+        spawn_app_with_config(pool.clone(), config(Duration::from_secs(60))).await;
+    let client = reqwest::Client::new();
+    let alice = sign_up(&base, &client, "alice").await;
+    let bob = sign_up(&base, &client, "bob").await;
+    let create = |token: &str, key: &str, title: &str| {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(token)
+            .header("idempotency-key", key)
+            .json(&json!({ "title": title, "description": "" }))
+            .send()
+    };
+
+    let first = create(&alice, "key-1", "milk").await.unwrap();
+    assert_eq!(first.status(), 201);
+    assert!(first.headers().get("idempotent-replayed").is_none());
+    let first: Value = first.json().await.unwrap();
+
+    let retry = create(&alice, "key-1", "milk").await.unwrap();
+    assert_eq!(retry.status(), 201);
+    assert_eq!(retry.headers()["idempotent-replayed"], "true");
+    assert_eq!(retry.json::<Value>().await.unwrap(), first);
+    assert_eq!(todo_count(&pool).await, 1);
+
+    // Same key, different body
+    let res = create(&alice, "key-1", "eggs").await.unwrap();
+    assert_eq!(res.status(), 422);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "idempotency_key_reused");
+
+    // Keys are per client
+    let res = create(&bob, "key-1", "milk").await.unwrap();
+    assert_eq!(res.status(), 201);
+    assert!(res.headers().get("idempotent-replayed").is_none());
+    assert_eq!(todo_count(&pool).await, 2);
+
+    // A claim whose request is still running
+    let alice_id: i64 = sqlx::query_scalar("SELECT id FROM users WHERE username = 'alice'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let body = serde_json::to_vec(&json!({ "title": "busy", "description": "" })).unwrap();
+    sqlx::query(
+        "INSERT INTO idempotency_keys (client, key, fingerprint, expires_at) \
+         VALUES ($1, 'key-2', $2, now() + interval '1 minute')",
+    )
+    .bind(format!("user:{alice_id}"))
+    .bind(fingerprint("POST", "/todos", &body))
+    .execute(&pool)
+    .await
+    .unwrap();
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&alice)
+        .header("idempotency-key", "key-2")
+        .header("content-type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 409);
+    assert_eq!(res.headers()["retry-after"], "1");
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "idempotency_key_in_flight");
+
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&alice)
+        .header("idempotency-key", "bad key")
+        .json(&json!({ "title": "x", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn keys_expire_after_the_ttl() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool.clone(), config(Duration::from_secs(1))).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let create = |title: &str| {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(&token)
+            .header("idempotency-key", "short-lived")
+            .json(&json!({ "title": title, "description": "" }))
+            .send()
+    };
+
+    assert_eq!(create("first").await.unwrap().status(), 201);
+    assert_eq!(create("second").await.unwrap().status(), 422);
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(create("second").await.unwrap().status(), 201);
+    assert_eq!(todo_count(&pool).await, 2);
+}
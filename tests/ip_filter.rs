@@ -0,0 +1,68 @@
+mod common;
+
+use axum_server_shuttle::config::{IpFilterConfig, IpRules, RouteGroup, ServerConfig};
+use common::{ADMIN, admin_config, db::try_setup_ephemeral_db, spawn_app_with_config};
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn admin_routes_only_answer_allowlisted_networks() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let cfg = ServerConfig {
+        // The test client plays a load balancer on loopback
+        trusted_proxies: vec!["127.0.0.0/8".parse().unwrap()],
+        ip_filter: IpFilterConfig {
+            rules: HashMap::from([(
+                RouteGroup::Admin,
+                IpRules {
+                    allow: vec!["10.0.0.0/8".parse().unwrap()],
+                    deny: vec!["10.66.0.0/16".parse().unwrap()],
+                },
+            )]),
+        },
+        ..admin_config()
+    };
+    let (base, _handle) = spawn_app_with_config(pool, cfg).await;
+    let client = reqwest::Client::new();
+    let admin_from = |ip: &'static str| {
+        client
+            .get(format!("{base}/admin/audit-events"))
+            .bearer_auth(ADMIN)
+            .header("x-forwarded-for", ip)
+            .send()
+    };
+
+    assert_eq!(admin_from("10.1.2.3").await.unwrap().status(), 200);
+
+    let res = admin_from("203.0.113.7").await.unwrap();
+    assert_eq!(res.status(), 403);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "ip_forbidden");
+    assert!(problem["request_id"].is_string());
+
+    assert_eq!(admin_from("10.66.0.1").await.unwrap().status(), 403);
+
+    // Other groups are unaffected
+    let res = client
+        .get(format!("{base}/todos"))
+        .bearer_auth(ADMIN)
+        .header("x-forwarded-for", "203.0.113.7")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let metrics = client
+        .get(format!("{base}/metrics"))
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains(r#"http_ip_rejections_total{group="admin",reason="not_allowed"} 1"#));
+    assert!(metrics.contains(r#"http_ip_rejections_total{group="admin",reason="denied"} 1"#));
+}
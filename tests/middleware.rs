@@ -1,10 +1,15 @@
+use axum::Router;
 use axum::body::Body;
-use axum::http::Request;
+use axum::http::{HeaderValue, Request};
+use axum::middleware::map_response;
+use axum::response::Response;
 use axum_server_shuttle::config::AppState;
 use axum_server_shuttle::config::{CorsPolicy, ServerConfig};
+use axum_server_shuttle::middleware::{Middleware, MiddlewareSuite};
 use axum_server_shuttle::models::Server;
 use sqlx::PgPool;
 use tower::ServiceExt;
+use tower_http::cors::CorsLayer;
 
 #[tokio::test]
 async fn request_id_is_set() {
@@ -34,3 +39,137 @@ async fn request_id_is_set() {
     let header = res.headers().get("x-request-id");
     assert!(header.is_some());
 }
+
+#[tokio::test]
+async fn errors_are_problem_json_with_request_id() {
+    let cfg = ServerConfig {
+        cors: CorsPolicy::Disabled,
+        ..Default::default()
+    };
+    // Authentication is rejected before the handler touches the pool.
+    let pool = PgPool::connect_lazy("postgres://127.0.0.1/postgres").expect("lazy pool");
+    let app = Server::new(AppState::new(pool, cfg)).router();
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .uri("/todos/not-a-number")
+                .header("x-request-id", "req-123")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), 401);
+    assert_eq!(
+        res.headers().get("content-type").unwrap(),
+        "application/problem+json"
+    );
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(problem["code"], "unauthorized");
+    assert_eq!(problem["status"], 401);
+    assert_eq!(problem["request_id"], "req-123");
+}
+
+/// A downstream suite that reuses the defaults and adds one header.
+#[derive(Clone)]
+struct Branded(Middleware);
+
+impl MiddlewareSuite for Branded {
+    fn trace(&self, router: Router) -> Router {
+        self.0
+            .trace(router)
+            .layer(map_response(|mut res: Response| async move {
+                res.headers_mut()
+                    .insert("x-powered-by", HeaderValue::from_static("branded"));
+                res
+            }))
+    }
+    fn normalize_path(&self, router: Router) -> Router {
+        self.0.normalize_path(router)
+    }
+    fn request_id_stack(&self, router: Router) -> Router {
+        self.0.request_id_stack(router)
+    }
+    fn timeout(&self, router: Router) -> Router {
+        self.0.timeout(router)
+    }
+    fn problem_details(&self, router: Router) -> Router {
+        self.0.problem_details(router)
+    }
+    fn cors_layer(&self) -> Option<CorsLayer> {
+        self.0.cors_layer()
+    }
+}
+
+#[tokio::test]
+async fn custom_middleware_suite_is_used() {
+    let cfg = ServerConfig {
+        cors: CorsPolicy::Disabled,
+        ..Default::default()
+    };
+    let pool = PgPool::connect_lazy("postgres://127.0.0.1/postgres").expect("lazy pool");
+    let server = Server::new(AppState::new(pool, cfg));
+    let mw = Branded(server.mw.clone());
+    let app = server.with_middleware(mw).router();
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .uri("/health/")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers().get("x-powered-by").unwrap(), "branded");
+    assert!(res.headers().get("x-request-id").is_some());
+}
+
+#[tokio::test]
+async fn metrics_are_labelled_by_route_template() {
+    let cfg = ServerConfig {
+        cors: CorsPolicy::Disabled,
+        ..Default::default()
+    };
+    let pool = PgPool::connect_lazy("postgres://127.0.0.1/postgres").expect("lazy pool");
+    let app = Server::new(AppState::new(pool, cfg)).router();
+
+    // Rejected before the handler runs, so no database is needed.
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/todos/abc")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(
+        text.contains(r#"http_requests_total{method="GET",route="/todos/{id}",status="401"} 1"#)
+    );
+    assert!(text.contains("db_pool_max_connections"));
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use axum_server_shuttle::config::ServerConfig;
+use common::oidc::{MockIdp, MockUser, authorize, browser};
+use common::{admin_config, db::try_setup_ephemeral_db, spawn_app_with_config};
+use reqwest::header::{COOKIE, SET_COOKIE};
+use serde_json::Value;
+
+fn config(idp: &MockIdp) -> ServerConfig {
+    // Lockdown must not get in the way of the login round trip
+    ServerConfig {
+        oidc: Some(idp.config()),
+        ..admin_config()
+    }
+}
+
+#[tokio::test]
+async fn login_provisions_and_then_reuses_a_local_user() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let idp = MockIdp::start().await;
+    let (base, _handle) = spawn_app_with_config(pool, config(&idp)).await;
+    let client = browser();
+
+    let callback = authorize(&base, &client).await;
+    let res = callback.request(&base, &client).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    let tokens: Value = res.json().await.unwrap();
+    let access = tokens["access_token"].as_str().unwrap();
+    assert!(tokens["refresh_token"].is_string());
+
+    let me: Value = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["username"], "alice");
+    assert_eq!(me["role"], "member");
+
+    // The same subject maps to the same user
+    let callback = authorize(&base, &client).await;
+    let tokens: Value = callback
+        .request(&base, &client)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let again: Value = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth(tokens["access_token"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(again["id"], me["id"]);
+
+    // A different subject claiming the same username gets a fresh account
+    idp.log_in_as(MockUser::new("sub-2", "alice"));
+    let callback = authorize(&base, &client).await;
+    let tokens: Value = callback
+        .request(&base, &client)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let other: Value = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth(tokens["access_token"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_ne!(other["id"], me["id"]);
+    assert!(other["username"].as_str().unwrap().starts_with("alice-"));
+
+    // OIDC-only accounts have no password to log in with
+    let res = client
+        .post(format!("{base}/auth/token"))
+        .json(&serde_json::json!({ "username": "alice", "password": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn callbacks_are_single_use_and_tokens_are_checked() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let idp = MockIdp::start().await;
+    let (base, _handle) = spawn_app_with_config(pool, config(&idp)).await;
+    let client = browser();
+
+    // A callback only completes in the browser that started the login, so
+    // an attacker can't slip their own login into a victim's browser
+    let callback = authorize(&base, &client).await;
+    let other = authorize(&base, &client).await;
+    for cookie in [None, Some(&other.cookie)] {
+        let mut req = client.get(format!("{base}{}", callback.path));
+        if let Some(cookie) = cookie {
+            req = req.header(COOKIE, cookie);
+        }
+        assert_eq!(req.send().await.unwrap().status(), 400);
+    }
+    let res = callback.request(&base, &client).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert!(
+        res.headers()[SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .starts_with("oidc_state=; ")
+    );
+    let res = callback.request(&base, &client).send().await.unwrap();
+    assert_eq!(res.status(), 400);
+
+    let res = client
+        .get(format!("{base}/auth/oidc/callback?code=x&state=forged"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    let res = client
+        .get(format!(
+            "{base}/auth/oidc/callback?state=x&error=access_denied"
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+
+    // An ID token minted for another client is refused
+    let mut user = MockUser::new("sub-3", "mallory");
+    user.audience = "someone-else".to_owned();
+    idp.log_in_as(user);
+    let callback = authorize(&base, &client).await;
+    let res = callback.request(&base, &client).send().await.unwrap();
+    assert_eq!(res.status(), 401);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "unauthorized");
+}
+
+#[tokio::test]
+async fn concurrent_first_logins_share_one_user() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let idp = MockIdp::start().await;
+    let (base, _handle) = spawn_app_with_config(pool.clone(), config(&idp)).await;
+    let client = browser();
+
+    let mut callbacks = Vec::new();
+    for _ in 0..4 {
+        callbacks.push(authorize(&base, &client).await);
+    }
+    let logins: Vec<_> = callbacks
+        .iter()
+        .map(|callback| {
+            let request = callback.request(&base, &client);
+            tokio::spawn(async move { request.send().await.unwrap().status() })
+        })
+        .collect();
+    for login in logins {
+        assert_eq!(login.await.unwrap(), 200);
+    }
+    let (users,): (i64,) = sqlx::query_as("SELECT count(*) FROM users")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(users, 1);
+}
+
+#[tokio::test]
+async fn issuer_is_matched_exactly_as_discovered() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let idp = MockIdp::start_with_trailing_slash().await;
+    let (base, _handle) = spawn_app_with_config(pool.clone(), config(&idp)).await;
+    let client = browser();
+
+    let callback = authorize(&base, &client).await;
+    let res = callback.request(&base, &client).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    let (issuer,): (String,) = sqlx::query_as("SELECT issuer FROM user_identities")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(issuer, idp.issuer);
+    assert!(issuer.ends_with('/'));
+}
+
+#[tokio::test]
+async fn oidc_routes_are_absent_without_config() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = common::spawn_app_with_pool(pool).await;
+    let res = browser()
+        .get(format!("{base}/auth/oidc/login"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use axum_server_shuttle::auth::JwtKey;
+use axum_server_shuttle::config::{
+    CorsPolicy, RateLimitBackend, RateLimitConfig, RouteGroup, Secret, ServerConfig,
+};
+use axum_server_shuttle::ratelimit::Quota;
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_config};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn config(backend: RateLimitBackend, quotas: &[(RouteGroup, u32)]) -> ServerConfig {
+    ServerConfig {
+        cors: CorsPolicy::Disabled,
+        rate_limit: RateLimitConfig {
+            enabled: true,
+            backend,
+            quotas: quotas
+                .iter()
+                .map(|&(group, limit)| (group, Quota::new(limit, Duration::from_secs(60))))
+                .collect::<HashMap<_, _>>(),
+        },
+        ..Default::default()
+    }
+}
+
+fn retry_after(res: &reqwest::Response) -> u64 {
+    res.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+async fn failed_login(base: &str, client: &reqwest::Client) -> reqwest::Response {
+    client
+        .post(format!("{base}/auth/token"))
+        .json(&json!({ "username": "nobody", "password": "wrong password" }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn auth_group_is_limited_per_client_ip() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let cfg = config(
+        RateLimitBackend::Memory,
+        &[(RouteGroup::Auth, 3), (RouteGroup::Api, 100)],
+    );
+    let (base, _handle) = spawn_app_with_config(pool, cfg).await;
+    let client = reqwest::Client::new();
+
+    for remaining in ["2", "1", "0"] {
+        let res = failed_login(&base, &client).await;
+        assert_eq!(res.status(), 401);
+        assert_eq!(res.headers()["ratelimit-remaining"], remaining);
+    }
+
+    let res = failed_login(&base, &client).await;
+    assert_eq!(res.status(), 429);
+    assert_eq!(res.headers()["ratelimit-limit"], "3");
+    assert_eq!(res.headers()["ratelimit-policy"], "3;w=60");
+    assert!((1..=20).contains(&retry_after(&res)));
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "rate_limited");
+
+    // Health probes belong to an unlimited group
+    let res = client.get(format!("{base}/health")).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert!(res.headers().get("ratelimit-limit").is_none());
+}
+
+#[tokio::test]
+async fn postgres_store_shares_quotas_between_instances() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let mut cfg = config(RateLimitBackend::Postgres, &[(RouteGroup::Api, 2)]);
+    // Both instances must accept each other's tokens
+    cfg.auth.signing_key = Some(JwtKey::hs256("test", &Secret::new("shared-secret")).unwrap());
+    let (first, _h1) = spawn_app_with_config(pool.clone(), cfg.clone()).await;
+    let (second, _h2) = spawn_app_with_config(pool, cfg).await;
+    let client = reqwest::Client::new();
+    let alice = sign_up(&first, &client, "alice").await;
+    let bob = sign_up(&first, &client, "bob").await;
+
+    let todos = |base: &str, token: &str| {
+        client
+            .get(format!("{base}/todos"))
+            .bearer_auth(token)
+            .send()
+    };
+    assert_eq!(todos(&first, &alice).await.unwrap().status(), 200);
+    assert_eq!(todos(&second, &alice).await.unwrap().status(), 200);
+    let res = todos(&first, &alice).await.unwrap();
+    assert_eq!(res.status(), 429);
+    assert!((1..=30).contains(&retry_after(&res)));
+
+    // Users are keyed separately, even from the same address
+    assert_eq!(todos(&second, &bob).await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn rejected_credentials_are_charged_to_the_client_ip() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let cfg = config(RateLimitBackend::Memory, &[(RouteGroup::Api, 3)]);
+    let (base, _handle) = spawn_app_with_config(pool, cfg).await;
+    let client = reqwest::Client::new();
+    let alice = sign_up(&base, &client, "alice").await;
+
+    let todos = |token: &str| {
+        client
+            .get(format!("{base}/todos"))
+            .bearer_auth(token)
+            .send()
+    };
+    for guess in ["a.b.c", "d.e.f", "g.h.i"] {
+        assert_eq!(todos(guess).await.unwrap().status(), 401);
+    }
+    let res = todos("j.k.l").await.unwrap();
+    assert_eq!(res.status(), 429);
+    assert!((1..=20).contains(&retry_after(&res)));
+
+    // Until the quota refills the address is refused before its credentials
+    // are checked, valid ones included
+    let res = todos(&alice).await.unwrap();
+    assert_eq!(res.status(), 429);
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use axum_server_shuttle::auth::RbacPolicy;
+use axum_server_shuttle::config::{AuthConfig, CorsPolicy, ServerConfig};
+use common::{db::try_setup_ephemeral_db, log_in, sign_up, spawn_app_with_config};
+use serde_json::{Value, json};
+
+fn config(policy: RbacPolicy) -> ServerConfig {
+    ServerConfig {
+        cors: CorsPolicy::Disabled,
+        auth: AuthConfig {
+            policy,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+async fn user_id(base: &str, client: &reqwest::Client, token: &str) -> i64 {
+    let me: Value = client
+        .get(format!("{base}/auth/me"))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    me["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn roles_gate_routes_and_changes_apply_on_next_token() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool.clone(), config(RbacPolicy::default())).await;
+    let client = reqwest::Client::new();
+
+    let root = sign_up(&base, &client, "root").await;
+    let alice = sign_up(&base, &client, "alice").await;
+    let bob = sign_up(&base, &client, "bob").await;
+    let alice_id = user_id(&base, &client, &alice).await;
+    let bob_id = user_id(&base, &client, &bob).await;
+
+    // The first admin is bootstrapped out of band
+    let root_id = user_id(&base, &client, &root).await;
+    sqlx::query("UPDATE users SET role = 'admin' WHERE id = $1")
+        .bind(root_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let root = log_in(&base, &client, "root").await;
+
+    // Members manage todos but not users
+    let res = client
+        .get(format!("{base}/admin/users"))
+        .bearer_auth(&alice)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "forbidden");
+
+    let users: Value = client
+        .get(format!("{base}/admin/users"))
+        .bearer_auth(&root)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(users[0]["role"], "admin");
+    assert_eq!(users[2]["username"], "bob");
+    assert_eq!(users[2]["role"], "member");
+
+    let res = client
+        .put(format!("{base}/admin/users/{bob_id}/role"))
+        .bearer_auth(&root)
+        .json(&json!({ "role": "read_only" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let updated: Value = res.json().await.unwrap();
+    assert_eq!(updated["role"], "read_only");
+
+    // The old token still carries the member role until it is replaced
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&bob)
+        .json(&json!({ "title": "before", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+
+    let bob = log_in(&base, &client, "bob").await;
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&bob)
+        .json(&json!({ "title": "after", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    let res = client
+        .get(format!("{base}/todos"))
+        .bearer_auth(&bob)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    // An admin user gets user and key management
+    client
+        .put(format!("{base}/admin/users/{alice_id}/role"))
+        .bearer_auth(&root)
+        .json(&json!({ "role": "admin" }))
+        .send()
+        .await
+        .unwrap();
+    let alice = log_in(&base, &client, "alice").await;
+    let res = client
+        .post(format!("{base}/admin/api-keys"))
+        .bearer_auth(&alice)
+        .json(
+            &json!({ "name": "ci", "scopes": ["todos:read", "todos:write"], "user_id": alice_id }),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    let key: Value = res.json().await.unwrap();
+
+    // A key bound to a read-only user is capped by that role
+    client
+        .put(format!("{base}/admin/users/{alice_id}/role"))
+        .bearer_auth(&root)
+        .json(&json!({ "role": "read_only" }))
+        .send()
+        .await
+        .unwrap();
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(key["key"].as_str().unwrap())
+        .json(&json!({ "title": "via key", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+
+    // Alice's access token still carries the admin role until it is refreshed
+    let res = client
+        .put(format!("{base}/admin/users/{bob_id}/role"))
+        .bearer_auth(&alice)
+        .json(&json!({ "role": "owner" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+    let res = client
+        .put(format!("{base}/admin/users/999999/role"))
+        .bearer_auth(&alice)
+        .json(&json!({ "role": "member" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn configured_policy_overrides_defaults() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let policy = RbacPolicy::parse("member=todos:read").unwrap();
+    let (base, _handle) = spawn_app_with_config(pool, config(policy)).await;
+    let client = reqwest::Client::new();
+
+    let alice = sign_up(&base, &client, "alice").await;
+    let res = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&alice)
+        .json(&json!({ "title": "nope", "description": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["detail"], "Missing required scope todos:write");
+
+    let res = client
+        .get(format!("{base}/todos"))
+        .bearer_auth(&alice)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+}
@@ -0,0 +1,341 @@
+mod common;
+
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_pool};
+use serde_json::{Value, json};
+
+fn slots(list: &Value) -> Vec<(&str, bool)> {
+    list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| (o["at"].as_str().unwrap(), !o["todo_id"].is_null()))
+        .collect()
+}
+
+#[tokio::test]
+async fn completing_an_occurrence_creates_the_next() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let get = |path: String| {
+        let client = client.clone();
+        let (base, token) = (base.clone(), token.clone());
+        async move {
+            let res = client
+                .get(format!("{base}{path}"))
+                .bearer_auth(&token)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), 200, "{path}");
+            res.json::<Value>().await.unwrap()
+        }
+    };
+    let open_todos = || async { get("/todos?done=false".to_owned()).await["items"].clone() };
+
+    client
+        .patch(format!("{base}/auth/me"))
+        .bearer_auth(&token)
+        .json(&json!({ "timezone": "Europe/Berlin" }))
+        .send()
+        .await
+        .unwrap();
+
+    let create = |body: Value| {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+    let res = create(json!({ "title": "x", "description": "", "rrule": "FREQ=HOURLY" }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+    let problem: Value = res.json().await.unwrap();
+    let fields: Vec<_> = problem["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["rrule", "due_at"]);
+
+    // 09:00 in Berlin, across the switch to summer time on March 29th
+    let standup: Value = create(json!({
+        "title": "standup", "description": "", "tags": ["work"],
+        "due_at": "2026-03-27T08:00:00Z", "rrule": "freq=daily;count=3"
+    }))
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    assert_eq!(standup["rrule"], "FREQ=DAILY;COUNT=3");
+    assert_eq!(standup["occurrence_at"], "2026-03-27T08:00:00Z");
+    assert!(standup["series_id"].is_i64());
+
+    let occurrences = format!(
+        "/todos/{}/occurrences?from=2026-03-27&to=2026-04-01",
+        standup["id"]
+    );
+    let expanded = get(occurrences.clone()).await;
+    assert_eq!(expanded["timezone"], "Europe/Berlin");
+    assert_eq!(
+        slots(&expanded),
+        [
+            ("2026-03-27T08:00:00Z", true),
+            ("2026-03-28T08:00:00Z", false),
+            ("2026-03-29T07:00:00Z", false),
+        ]
+    );
+    assert_eq!(expanded["truncated"], false);
+
+    let res = client
+        .patch(format!("{base}/todos/{}", standup["id"]))
+        .bearer_auth(&token)
+        .json(&json!({ "done": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let open = open_todos().await;
+    assert_eq!(open.as_array().unwrap().len(), 1);
+    let second = &open[0];
+    assert_eq!(second["title"], "standup");
+    assert_eq!(second["due_at"], "2026-03-28T08:00:00Z");
+    assert_eq!(second["series_id"], standup["series_id"]);
+    assert_eq!(second["tags"][0]["name"], "work");
+
+    // Deleting just this occurrence skips it; the series moves on
+    let res = client
+        .delete(format!("{base}/todos/{}", second["id"]))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+    let open = open_todos().await;
+    let third = &open[0];
+    assert_eq!(third["due_at"], "2026-03-29T07:00:00Z");
+    assert_eq!(
+        slots(&get(occurrences).await),
+        [
+            ("2026-03-27T08:00:00Z", true),
+            ("2026-03-29T07:00:00Z", true),
+        ]
+    );
+
+    // COUNT=3 is used up
+    client
+        .patch(format!("{base}/todos/{}", third["id"]))
+        .bearer_auth(&token)
+        .json(&json!({ "done": true }))
+        .send()
+        .await
+        .unwrap();
+    assert!(open_todos().await.as_array().unwrap().is_empty());
+
+    // A one-off todo's only occurrence is its due date
+    let once: Value = create(json!({
+        "title": "dentist", "description": "", "due_at": "2026-04-02T10:00:00Z"
+    }))
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let expanded = get(format!(
+        "/todos/{}/occurrences?from=2026-04-01&to=2026-04-30",
+        once["id"]
+    ))
+    .await;
+    assert!(expanded["rrule"].is_null());
+    assert_eq!(slots(&expanded), [("2026-04-02T10:00:00Z", true)]);
+}
+
+#[tokio::test]
+async fn series_edits_respect_their_scope() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let send = |req: reqwest::RequestBuilder| async {
+        let res = req.bearer_auth(&token).send().await.unwrap();
+        let status = res.status().as_u16();
+        (status, res.json::<Value>().await.unwrap_or(Value::Null))
+    };
+    let todo_url = |todo: &Value| format!("{base}/todos/{}", todo["id"]);
+
+    let (_, first) = send(client.post(format!("{base}/todos")).json(&json!({
+        "title": "chores", "description": "", "due_at": "2026-01-05T18:00:00Z",
+        "rrule": "FREQ=WEEKLY"
+    })))
+    .await;
+    let (_, first) = send(
+        client
+            .patch(todo_url(&first))
+            .json(&json!({ "done": true })),
+    )
+    .await;
+    let (_, open) = send(client.get(format!("{base}/todos?done=false"))).await;
+    let second = open["items"][0].clone();
+    send(
+        client
+            .patch(todo_url(&second))
+            .json(&json!({ "done": true })),
+    )
+    .await;
+    let (_, open) = send(client.get(format!("{base}/todos?done=false"))).await;
+    let third = open["items"][0].clone();
+    assert_eq!(third["occurrence_at"], "2026-01-19T18:00:00Z");
+
+    let (status, _) = send(
+        client
+            .patch(format!("{}?scope=all", todo_url(&third)))
+            .json(&json!({ "done": true })),
+    )
+    .await;
+    assert_eq!(status, 400);
+    let (status, _) = send(
+        client
+            .patch(todo_url(&third))
+            .json(&json!({ "rrule": "FREQ=DAILY" })),
+    )
+    .await;
+    assert_eq!(status, 400);
+
+    // scope=all renames the open occurrence; completed ones are history
+    let (status, renamed) = send(
+        client
+            .patch(format!("{}?scope=all", todo_url(&third)))
+            .json(&json!({ "title": "house chores" })),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(renamed["title"], "house chores");
+    assert_eq!(
+        renamed["version"].as_i64(),
+        Some(third["version"].as_i64().unwrap() + 1)
+    );
+    let (_, done) = send(client.get(todo_url(&first))).await;
+    assert_eq!(done["title"], "chores");
+
+    // scope=following splits the series at this occurrence
+    let (status, split) = send(
+        client
+            .patch(format!("{}?scope=following", todo_url(&third)))
+            .json(&json!({ "rrule": "FREQ=WEEKLY;INTERVAL=2" })),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(split["rrule"], "FREQ=WEEKLY;INTERVAL=2");
+    assert_ne!(split["series_id"], first["series_id"]);
+    let range = "occurrences?from=2026-01-01&to=2026-03-01";
+    let (_, head) = send(client.get(format!("{}/{range}", todo_url(&first)))).await;
+    assert_eq!(
+        slots(&head),
+        [
+            ("2026-01-05T18:00:00Z", true),
+            ("2026-01-12T18:00:00Z", true),
+        ]
+    );
+    let (_, tail) = send(client.get(format!("{}/{range}", todo_url(&third)))).await;
+    assert_eq!(
+        slots(&tail),
+        [
+            ("2026-01-19T18:00:00Z", true),
+            ("2026-02-02T18:00:00Z", false),
+            ("2026-02-16T18:00:00Z", false),
+        ]
+    );
+
+    // Deleting all of the new series trashes its todos and ends it
+    let (status, _) = send(client.delete(format!("{}?scope=all", todo_url(&third)))).await;
+    assert_eq!(status, 204);
+    let (_, open) = send(client.get(format!("{base}/todos?done=false"))).await;
+    assert!(open["items"].as_array().unwrap().is_empty());
+    let (_, trash) = send(client.get(format!("{base}/todos/trash"))).await;
+    assert_eq!(trash["items"][0]["id"], third["id"]);
+
+    // Ending the old series leaves its todos as one-offs
+    let (status, detached) = send(
+        client
+            .patch(format!("{}?scope=all", todo_url(&first)))
+            .json(&json!({ "rrule": null })),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert!(detached["series_id"].is_null());
+    assert!(detached["rrule"].is_null());
+    assert!(detached["occurrence_at"].is_null());
+}
+
+#[tokio::test]
+async fn far_future_ranges_expand_quickly() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let create = |rrule: &str| {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(&token)
+            .json(&json!({
+                "title": "water plants", "description": "",
+                "due_at": "2026-01-01T09:00:00Z", "rrule": rrule
+            }))
+            .send()
+    };
+    let get = |todo: Value| {
+        let (client, base, token) = (client.clone(), base.clone(), token.clone());
+        async move {
+            let started = std::time::Instant::now();
+            let res = client
+                .get(format!(
+                    "{base}/todos/{}/occurrences?from=9000-01-01&to=9000-01-04",
+                    todo["id"]
+                ))
+                .bearer_auth(&token)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), 200);
+            let list: Value = res.json().await.unwrap();
+            (list, started.elapsed())
+        }
+    };
+
+    // Walking every day from 2026 took seconds; jumping ahead takes none
+    let daily: Value = create("FREQ=DAILY").await.unwrap().json().await.unwrap();
+    let (list, took) = get(daily).await;
+    assert_eq!(
+        slots(&list),
+        [
+            ("9000-01-01T09:00:00Z", false),
+            ("9000-01-02T09:00:00Z", false),
+            ("9000-01-03T09:00:00Z", false),
+        ]
+    );
+    assert!(took < std::time::Duration::from_secs(1), "took {took:?}");
+
+    // A counted series can't jump, but COUNT is capped so it ends early
+    let res = create("FREQ=DAILY;COUNT=10001").await.unwrap();
+    assert_eq!(res.status(), 422);
+    let counted: Value = create("FREQ=DAILY;COUNT=10000")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let (list, took) = get(counted).await;
+    assert!(slots(&list).is_empty());
+    assert!(took < std::time::Duration::from_secs(1), "took {took:?}");
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use axum_server_shuttle::config::{AuthConfig, CorsPolicy, ServerConfig, SessionConfig};
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_config};
+use reqwest::header::{COOKIE, SET_COOKIE};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+fn config(idle_timeout: Duration) -> ServerConfig {
+    ServerConfig {
+        cors: CorsPolicy::Disabled,
+        auth: AuthConfig {
+            session: SessionConfig {
+                idle_timeout,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Logs in through `POST /auth/session`, returning the `Cookie` header value
+/// and the session body.
+async fn open_session(base: &str, client: &reqwest::Client, username: &str) -> (String, Value) {
+    let res = client
+        .post(format!("{base}/auth/session"))
+        .header("user-agent", "session-test")
+        .json(&json!({ "username": username, "password": "correct horse battery" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    let set_cookie = res.headers()[SET_COOKIE].to_str().unwrap().to_owned();
+    assert!(set_cookie.contains("HttpOnly"));
+    assert!(set_cookie.contains("SameSite=Lax"));
+    let cookie = set_cookie.split(';').next().unwrap().to_owned();
+    (cookie, res.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn cookie_sessions_authenticate_and_enforce_csrf() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool, config(Duration::from_secs(1800))).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "browser").await;
+
+    let (cookie, info) = open_session(&base, &client, "browser").await;
+    assert_eq!(info["user"]["username"], "browser");
+    let csrf = info["csrf_token"].as_str().unwrap().to_owned();
+
+    let res = client
+        .get(format!("{base}/todos"))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let todo = json!({ "title": "from the browser", "description": "" });
+    let res = client
+        .post(format!("{base}/todos"))
+        .header(COOKIE, &cookie)
+        .json(&todo)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+
+    let res = client
+        .post(format!("{base}/todos"))
+        .header(COOKIE, &cookie)
+        .header("x-csrf-token", &csrf)
+        .json(&todo)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+
+    // Bearer requests never need the CSRF token, even with a cookie attached
+    let res = client
+        .post(format!("{base}/todos"))
+        .header(COOKIE, &cookie)
+        .bearer_auth(&token)
+        .json(&todo)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+
+    let current: Value = client
+        .get(format!("{base}/auth/session"))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(current["id"], info["id"]);
+    assert_eq!(current["csrf_token"], csrf);
+
+    // A second device shows up in the listing and can be signed out remotely
+    let (other, other_info) = open_session(&base, &client, "browser").await;
+    let listed: Value = client
+        .get(format!("{base}/auth/sessions"))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let listed = listed.as_array().unwrap();
+    assert_eq!(listed.len(), 2);
+    let mine = listed.iter().find(|s| s["id"] == info["id"]).unwrap();
+    assert_eq!(mine["current"], true);
+    assert_eq!(mine["user_agent"], "session-test");
+
+    let res = client
+        .delete(format!("{base}/auth/sessions/{}", other_info["id"]))
+        .header(COOKIE, &cookie)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+    let res = client
+        .get(format!("{base}/auth/session"))
+        .header(COOKIE, &other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+
+    // Other users' sessions are invisible
+    let stranger = sign_up(&base, &client, "stranger").await;
+    let res = client
+        .delete(format!("{base}/auth/sessions/{}", info["id"]))
+        .bearer_auth(&stranger)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let res = client
+        .delete(format!("{base}/auth/session"))
+        .header(COOKIE, &cookie)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+    assert!(
+        res.headers()[SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .contains("Max-Age=0")
+    );
+    let res = client
+        .get(format!("{base}/auth/session"))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn tampered_and_idle_sessions_are_anonymous() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_config(pool, config(Duration::from_secs(1))).await;
+    let client = reqwest::Client::new();
+    sign_up(&base, &client, "sleepy").await;
+
+    let (cookie, _) = open_session(&base, &client, "sleepy").await;
+    let res = client
+        .get(format!("{base}/auth/session"))
+        .header(COOKIE, format!("{cookie}x"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let res = client
+        .get(format!("{base}/auth/session"))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+
+    let res = client
+        .post(format!("{base}/auth/session"))
+        .json(&json!({ "username": "sleepy", "password": "wrong password" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+}
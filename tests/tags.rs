@@ -0,0 +1,230 @@
+mod common;
+
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_pool};
+use serde_json::{Value, json};
+
+fn tag_names(todo: &Value) -> Vec<&str> {
+    todo["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn todos_carry_and_filter_by_tags() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let create = |title: &str, tags: &[&str]| {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(&token)
+            .json(&json!({ "title": title, "description": "", "tags": tags }))
+            .send()
+    };
+    let titles = |query: &'static str| {
+        let client = client.clone();
+        let (base, token) = (base.clone(), token.clone());
+        async move {
+            let page: Value = client
+                .get(format!("{base}/todos?{query}"))
+                .bearer_auth(&token)
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t["title"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let res = create("milk", &["Shopping", " home ", "shopping"])
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    let milk: Value = res.json().await.unwrap();
+    assert_eq!(tag_names(&milk), ["home", "Shopping"]);
+    create("report", &["work"]).await.unwrap();
+    create("fix sink", &["home", "work"]).await.unwrap();
+    create("untagged", &[]).await.unwrap();
+
+    assert_eq!(titles("tag=home").await, ["milk", "fix sink"]);
+    assert_eq!(
+        titles("tag=HOME&tag=work").await,
+        ["milk", "report", "fix sink"]
+    );
+    assert_eq!(
+        titles("tag=home&tag=work&tag_match=all").await,
+        ["fix sink"]
+    );
+    assert!(titles("tag=nope").await.is_empty());
+
+    // Replacing a todo's tags bumps its version; resending the same set does not
+    let url = format!("{base}/todos/{}", milk["id"]);
+    let patched: Value = client
+        .patch(&url)
+        .bearer_auth(&token)
+        .json(&json!({ "tags": ["home"] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(tag_names(&patched), ["home"]);
+    assert_eq!(patched["version"], 2);
+    let patched: Value = client
+        .patch(&url)
+        .bearer_auth(&token)
+        .json(&json!({ "tags": ["HOME"] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(patched["version"], 2);
+
+    let tags: Value = client
+        .get(format!("{base}/tags"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let counts: Vec<(&str, i64)> = tags
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            (
+                t["name"].as_str().unwrap(),
+                t["todo_count"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(counts, [("home", 2), ("Shopping", 0), ("work", 2)]);
+}
+
+#[tokio::test]
+async fn renames_and_merges_update_every_todo() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let bob = sign_up(&base, &client, "bob").await;
+
+    let create_tag = |token: &str, name: &str| {
+        client
+            .post(format!("{base}/tags"))
+            .bearer_auth(token)
+            .json(&json!({ "name": name }))
+            .send()
+    };
+    let job: Value = create_tag(&token, "job")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let res = create_tag(&token, "JOB").await.unwrap();
+    assert_eq!(res.status(), 409);
+    let bobs: Value = create_tag(&bob, "job").await.unwrap().json().await.unwrap();
+
+    let todo: Value = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&token)
+        .json(&json!({ "title": "report", "description": "", "tags": ["job", "urgent"] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let url = format!("{base}/todos/{}", todo["id"]);
+    let etag = || {
+        let client = client.clone();
+        let (url, token) = (url.clone(), token.clone());
+        async move {
+            let res = client.get(&url).bearer_auth(&token).send().await.unwrap();
+            let etag = res.headers()["etag"].to_str().unwrap().to_owned();
+            (etag, res.json::<Value>().await.unwrap())
+        }
+    };
+    let (before, _) = etag().await;
+
+    let res = client
+        .patch(format!("{base}/tags/{}", job["id"]))
+        .bearer_auth(&token)
+        .json(&json!({ "name": "work" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let (after, todo) = etag().await;
+    assert_ne!(before, after);
+    assert_eq!(tag_names(&todo), ["urgent", "work"]);
+
+    // Another user's tag is invisible, and can't be a merge target
+    let res = client
+        .post(format!("{base}/tags/{}/merge", job["id"]))
+        .bearer_auth(&token)
+        .json(&json!({ "into": bobs["id"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let urgent = todo["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "urgent")
+        .unwrap()
+        .clone();
+    let res = client
+        .post(format!("{base}/tags/{}/merge", urgent["id"]))
+        .bearer_auth(&token)
+        .json(&json!({ "into": job["id"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let merged: Value = res.json().await.unwrap();
+    assert_eq!(merged["name"], "work");
+    assert_eq!(merged["todo_count"], 1);
+    let (_, todo) = etag().await;
+    assert_eq!(tag_names(&todo), ["work"]);
+    let res = client
+        .get(format!("{base}/tags/{}", urgent["id"]))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let res = client
+        .delete(format!("{base}/tags/{}", job["id"]))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+    let (_, todo) = etag().await;
+    assert!(tag_names(&todo).is_empty());
+}
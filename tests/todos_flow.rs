@@ -1,4 +1,152 @@
+mod common;
+
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_pool};
+use serde_json::{Value, json};
+
 #[tokio::test]
-async fn placeholder() {
-    // Placeholder test - passes by doing nothing.
+async fn todo_crud_round_trip() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+
+    let created: Value = client
+        .post(format!("{base}/todos"))
+        .bearer_auth(&token)
+        .json(&json!({ "title": "Buy milk", "description": "2% organic" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_i64().unwrap();
+
+    let res = client
+        .patch(format!("{base}/todos/{id}"))
+        .bearer_auth(&token)
+        .json(&json!({ "done": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let patched: Value = res.json().await.unwrap();
+    assert_eq!(patched["done"], true);
+    assert_eq!(patched["title"], "Buy milk");
+
+    let res = client
+        .put(format!("{base}/todos/{id}"))
+        .bearer_auth(&token)
+        .json(&json!({ "title": "Buy oat milk", "description": "barista" }))
+        .send()
+        .await
+        .unwrap();
+    let replaced: Value = res.json().await.unwrap();
+    assert_eq!(replaced["title"], "Buy oat milk");
+    assert_eq!(replaced["done"], false);
+
+    let res = client
+        .get(format!("{base}/todos/{id}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let res = client
+        .delete(format!("{base}/todos/{id}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+
+    let res = client
+        .get(format!("{base}/todos/{id}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+    let problem: Value = res.json().await.unwrap();
+    assert_eq!(problem["code"], "not_found");
+}
+
+#[tokio::test]
+async fn list_todos_pages_with_cursor() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+
+    for (title, done) in [("a", false), ("b", true), ("c", false), ("d", false)] {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(&token)
+            .json(&json!({ "title": title, "description": "", "done": done }))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let res = client
+        .get(format!("{base}/todos?done=false&sort=-id&limit=2"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert!(res.headers().contains_key("link"));
+    let first: Value = res.json().await.unwrap();
+    let titles: Vec<_> = first["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["title"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(titles, ["d", "c"]);
+
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let second: Value = client
+        .get(format!(
+            "{base}/todos?done=false&sort=-id&limit=2&cursor={cursor}"
+        ))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(second["items"][0]["title"], "a");
+    assert!(second["next_cursor"].is_null());
+
+    let res = client
+        .get(format!("{base}/todos?sort=id&cursor={cursor}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn readiness_reports_migrated_database() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool).await;
+
+    let res = reqwest::get(format!("{base}/health/ready")).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let report: Value = res.json().await.unwrap();
+    assert_eq!(report["status"], "ok");
+    assert_eq!(report["components"]["migrations"]["status"], "ok");
+    assert_eq!(
+        report["components"]["migrations"]["applied"],
+        report["components"]["migrations"]["expected"]
+    );
 }
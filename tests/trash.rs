@@ -0,0 +1,189 @@
+mod common;
+
+use axum_server_shuttle::runtime::purge_trash;
+use chrono::{DateTime, Utc};
+use common::{db::try_setup_ephemeral_db, sign_up, spawn_app_with_pool};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+fn timestamp(v: &Value) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(v.as_str().unwrap())
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+#[tokio::test]
+async fn deleted_todos_go_to_the_trash() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    let (base, _handle) = spawn_app_with_pool(pool.clone()).await;
+    let client = reqwest::Client::new();
+    let token = sign_up(&base, &client, "alice").await;
+    let create = |title: &str| {
+        client
+            .post(format!("{base}/todos"))
+            .bearer_auth(&token)
+            .json(&json!({ "title": title, "description": "" }))
+            .send()
+    };
+    let ids = |page: Value| -> Vec<i64> {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["id"].as_i64().unwrap())
+            .collect()
+    };
+
+    let milk: Value = create("milk").await.unwrap().json().await.unwrap();
+    let eggs: Value = create("eggs").await.unwrap().json().await.unwrap();
+    assert_eq!(milk["created_at"], milk["updated_at"]);
+    assert!(milk["deleted_at"].is_null());
+    let (milk_id, eggs_id) = (milk["id"].as_i64().unwrap(), eggs["id"].as_i64().unwrap());
+
+    // Triggers keep created_at and move updated_at
+    let updated: Value = client
+        .patch(format!("{base}/todos/{milk_id}"))
+        .bearer_auth(&token)
+        .json(&json!({ "done": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(updated["created_at"], milk["created_at"]);
+    assert!(timestamp(&updated["updated_at"]) > timestamp(&milk["updated_at"]));
+
+    for id in [milk_id, eggs_id] {
+        let res = client
+            .delete(format!("{base}/todos/{id}"))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 204);
+    }
+    let res = client
+        .get(format!("{base}/todos/{milk_id}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+    let live: Value = client
+        .get(format!("{base}/todos"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(ids(live).is_empty());
+
+    let trash: Value = client
+        .get(format!("{base}/todos/trash"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!trash["items"][0]["deleted_at"].is_null());
+    assert_eq!(ids(trash), [milk_id, eggs_id]);
+
+    // Another user's trash is separate
+    let bob = sign_up(&base, &client, "bob").await;
+    let res = client
+        .post(format!("{base}/todos/trash/{milk_id}/restore"))
+        .bearer_auth(&bob)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let res = client
+        .post(format!("{base}/todos/trash/{milk_id}/restore"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let restored: Value = res.json().await.unwrap();
+    assert!(restored["deleted_at"].is_null());
+    assert_eq!(restored["done"], true);
+
+    // Live todos cannot be purged directly
+    let res = client
+        .delete(format!("{base}/todos/trash/{milk_id}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+    let res = client
+        .delete(format!("{base}/todos/trash/{eggs_id}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+    let remaining: i64 = sqlx::query_scalar("SELECT count(*) FROM todos")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 1);
+
+    client
+        .delete(format!("{base}/todos/{milk_id}"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    let res = client
+        .delete(format!("{base}/todos/trash"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+    let trash: Value = client
+        .get(format!("{base}/todos/trash"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(ids(trash).is_empty());
+}
+
+#[tokio::test]
+async fn purge_removes_only_expired_trash() {
+    let Some((_url, pool)) = try_setup_ephemeral_db().await else {
+        return;
+    };
+    sqlx::query(
+        "INSERT INTO todos (title, description, deleted_at) VALUES \
+         ('old', '', now() - interval '2 days'), \
+         ('recent', '', now() - interval '1 hour'), \
+         ('live', '', NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let purged = purge_trash(&pool, Duration::from_secs(24 * 60 * 60))
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+    let left: Vec<String> = sqlx::query_scalar("SELECT title FROM todos ORDER BY id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(left, ["recent", "live"]);
+}